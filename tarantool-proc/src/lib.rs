@@ -6,6 +6,7 @@ use syn::{
     ItemFn, Signature, Token,
 };
 
+mod space;
 mod test;
 
 /// Mark a function as a test.
@@ -217,6 +218,16 @@ pub fn derive_encode(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
+/// Macro to automatically derive `tarantool::space::SpaceSchema`, which
+/// describes the format and the indexes of a space storing the struct.
+///
+/// See `tarantool::space::TypedSpace` doc-comments in tarantool crate for
+/// details.
+#[proc_macro_derive(Space, attributes(space))]
+pub fn derive_space(input: TokenStream) -> TokenStream {
+    space::derive(input)
+}
+
 #[proc_macro]
 pub fn impl_tuple_encode(_input: TokenStream) -> TokenStream {
     let mut impls = vec![];
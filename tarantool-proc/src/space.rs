@@ -0,0 +1,263 @@
+use darling::{ast, util::Flag, util::PathList, FromDeriveInput, FromField, FromMeta};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, DeriveInput, Ident, Path};
+
+#[derive(FromDeriveInput)]
+#[darling(attributes(space), supports(struct_named))]
+struct SpaceArgs {
+    ident: Ident,
+    generics: syn::Generics,
+    data: ast::Data<(), FieldArgs>,
+    /// Name of the space, defaults to the struct name in snake case.
+    name: Option<String>,
+    /// Path to tarantool crate
+    tarantool: Option<String>,
+    /// Index definitions, the first one is the primary key.
+    #[darling(multiple, rename = "index")]
+    indexes: Vec<IndexArgs>,
+}
+
+#[derive(FromField)]
+#[darling(attributes(space))]
+struct FieldArgs {
+    ident: Option<Ident>,
+    ty: syn::Type,
+    /// Overrides the field type deduced from the rust type.
+    field_type: Option<String>,
+    /// Marks the field as nullable, only used with `field_type`.
+    is_nullable: Flag,
+}
+
+#[derive(FromMeta)]
+struct IndexArgs {
+    name: String,
+    parts: PathList,
+    #[darling(rename = "type")]
+    index_type: Option<String>,
+    unique: Option<bool>,
+}
+
+const FIELD_TYPES: &[(&str, &str)] = &[
+    ("any", "Any"),
+    ("unsigned", "Unsigned"),
+    ("string", "String"),
+    ("number", "Number"),
+    ("double", "Double"),
+    ("integer", "Integer"),
+    ("boolean", "Boolean"),
+    ("varbinary", "Varbinary"),
+    ("scalar", "Scalar"),
+    ("decimal", "Decimal"),
+    ("uuid", "Uuid"),
+    ("datetime", "Datetime"),
+    ("interval", "Interval"),
+    ("array", "Array"),
+    ("map", "Map"),
+];
+
+const INDEX_TYPES: &[(&str, &str)] = &[
+    ("hash", "Hash"),
+    ("tree", "Tree"),
+    ("bitset", "Bitset"),
+    ("rtree", "Rtree"),
+];
+
+pub fn derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let args = match SpaceArgs::from_derive_input(&input) {
+        Ok(args) => args,
+        Err(e) => return e.write_errors().into(),
+    };
+    match impl_space_schema(args) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
+/// Format and index part definitions of a single struct field.
+struct FieldInfo {
+    name: String,
+    field_type: TokenStream2,
+    is_nullable: TokenStream2,
+}
+
+fn impl_space_schema(args: SpaceArgs) -> darling::Result<TokenStream2> {
+    let tarantool_crate = args.tarantool.as_deref().unwrap_or("tarantool");
+    let tarantool_crate: Path = Ident::new(tarantool_crate, Span::call_site()).into();
+    let name = &args.ident;
+    let space_name = args
+        .name
+        .clone()
+        .unwrap_or_else(|| to_snake_case(&name.to_string()));
+
+    let mut errors = darling::Error::accumulator();
+
+    let fields = args
+        .data
+        .take_struct()
+        .expect("only named structs are supported");
+    let fields: Vec<_> = fields
+        .fields
+        .into_iter()
+        .filter_map(|field| errors.handle(field_info(field, &tarantool_crate)))
+        .collect();
+
+    if args.indexes.is_empty() {
+        errors.push(darling::Error::custom(
+            "at least one index must be specified with `#[space(index(name = \"...\", parts(...)))]`",
+        ));
+    }
+    let indexes: Vec<_> = args
+        .indexes
+        .iter()
+        .filter_map(|index| errors.handle(index_options(index, &fields, &tarantool_crate)))
+        .collect();
+
+    errors.finish()?;
+
+    let format = fields.iter().map(|field| {
+        let FieldInfo {
+            name,
+            field_type,
+            is_nullable,
+        } = field;
+        quote! {
+            #tarantool_crate::space::Field::from((#name, #field_type)).is_nullable(#is_nullable)
+        }
+    });
+    let (impl_generics, ty_generics, where_clause) = args.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #tarantool_crate::space::SpaceSchema for #name #ty_generics #where_clause {
+            const SPACE_NAME: &'static str = #space_name;
+
+            fn format() -> ::std::vec::Vec<#tarantool_crate::space::Field> {
+                ::std::vec![#(#format),*]
+            }
+
+            fn indexes() -> ::std::vec::Vec<(&'static str, #tarantool_crate::index::IndexOptions)> {
+                ::std::vec![#(#indexes),*]
+            }
+        }
+    })
+}
+
+fn field_info(field: FieldArgs, tarantool_crate: &Path) -> darling::Result<FieldInfo> {
+    let ident = field
+        .ident
+        .as_ref()
+        .expect("only named fields are supported");
+    let name = ident.to_string().trim_start_matches("r#").to_string();
+    let ty = &field.ty;
+    let (field_type, is_nullable) = match &field.field_type {
+        Some(field_type) => {
+            let variant = lookup(FIELD_TYPES, field_type)
+                .ok_or_else(|| darling::Error::unknown_value(field_type).with_span(ident))?;
+            let is_nullable = field.is_nullable.is_present();
+            (
+                quote! { #tarantool_crate::space::FieldType::#variant },
+                quote! { #is_nullable },
+            )
+        }
+        None => {
+            if field.is_nullable.is_present() {
+                return Err(darling::Error::custom(
+                    "`is_nullable` can only be used together with `field_type`, \
+                    use `Option<T>` instead",
+                )
+                .with_span(ident));
+            }
+            (
+                quote_spanned! {ty.span()=>
+                    <#ty as #tarantool_crate::space::KnownFieldType>::FIELD_TYPE
+                },
+                quote_spanned! {ty.span()=>
+                    <#ty as #tarantool_crate::space::KnownFieldType>::IS_NULLABLE
+                },
+            )
+        }
+    };
+    Ok(FieldInfo {
+        name,
+        field_type,
+        is_nullable,
+    })
+}
+
+fn index_options(
+    index: &IndexArgs,
+    fields: &[FieldInfo],
+    tarantool_crate: &Path,
+) -> darling::Result<TokenStream2> {
+    let name = &index.name;
+    if index.parts.is_empty() {
+        return Err(darling::Error::custom(format!(
+            "index `{}` must have at least one part",
+            name
+        )));
+    }
+    let mut parts = vec![];
+    for part in index.parts.iter() {
+        let part_name = part
+            .get_ident()
+            .map(|ident| ident.to_string().trim_start_matches("r#").to_string());
+        let field = fields
+            .iter()
+            .find(|field| Some(&field.name) == part_name.as_ref())
+            .ok_or_else(|| {
+                darling::Error::custom(format!("unknown field in index `{}`", name)).with_span(part)
+            })?;
+        let field_name = &field.name;
+        let is_nullable = &field.is_nullable;
+        parts.push(quote! {
+            #tarantool_crate::index::Part::field(#field_name).is_nullable(#is_nullable)
+        });
+    }
+    let index_type = match &index.index_type {
+        Some(index_type) => {
+            let variant = lookup(INDEX_TYPES, index_type)
+                .ok_or_else(|| darling::Error::unknown_value(index_type))?;
+            quote! { ::std::option::Option::Some(#tarantool_crate::index::IndexType::#variant) }
+        }
+        None => quote! { ::std::option::Option::None },
+    };
+    let unique = match index.unique {
+        Some(unique) => quote! { ::std::option::Option::Some(#unique) },
+        None => quote! { ::std::option::Option::None },
+    };
+    Ok(quote! {
+        (
+            #name,
+            #tarantool_crate::index::IndexOptions {
+                r#type: #index_type,
+                unique: #unique,
+                parts: ::std::option::Option::Some(::std::vec![#(#parts),*]),
+                ..::std::default::Default::default()
+            },
+        )
+    })
+}
+
+fn lookup(table: &[(&str, &str)], key: &str) -> Option<Ident> {
+    table
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, variant)| Ident::new(variant, Span::call_site()))
+}
+
+fn to_snake_case(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                res.push('_');
+            }
+            res.extend(c.to_lowercase());
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
@@ -0,0 +1,154 @@
+//! Authentication of the binary protocol connections.
+//!
+//! See [tarantool docs](https://www.tarantool.io/en/doc/latest/dev_guide/internals/iproto/authentication/).
+
+use std::io::Write;
+
+use rmp::encode::ValueWriteError;
+use sha1::{Digest, Sha1};
+
+use crate::error::TarantoolErrorCode;
+
+crate::define_str_enum! {
+    /// Method used to authenticate the user of a binary protocol connection,
+    /// see [`network::protocol::Config`] and [`net_box::ConnOptions`].
+    ///
+    /// [`network::protocol::Config`]: crate::network::protocol::Config
+    /// [`net_box::ConnOptions`]: crate::net_box::ConnOptions
+    pub enum AuthMethod {
+        /// Challenge-response authentication, the password itself is never
+        /// sent over the network. Supported by every Tarantool version.
+        ChapSha1 = "chap-sha1",
+        /// The password is sent in plain text and is checked against its
+        /// salted SHA-256 hash stored on the server. Must only be used over
+        /// an encrypted connection. Supported by Tarantool Enterprise Edition.
+        PapSha256 = "pap-sha256",
+    }
+}
+
+impl Default for AuthMethod {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::ChapSha1
+    }
+}
+
+impl AuthMethod {
+    /// Returns `true` if the error with the given `code` returned in response
+    /// to the auth request means that the server doesn't support the method.
+    pub(crate) fn is_rejected_by(self, code: u32) -> bool {
+        if code == TarantoolErrorCode::UnknownAuthMethod as u32 {
+            return true;
+        }
+        // Servers prior to 2.11 ignore the method and fail to decode the
+        // auth data as a chap-sha1 scramble
+        self != Self::ChapSha1 && code == TarantoolErrorCode::InvalidMsgpack as u32
+    }
+}
+
+/// Encodes the `IPROTO_TUPLE` value of the auth request: the name of the
+/// `method` followed by the data the server expects for this method.
+///
+/// `salt` is the one received in the greeting message.
+pub(crate) fn encode_auth_data(
+    stream: &mut impl Write,
+    method: AuthMethod,
+    password: &str,
+    salt: &[u8],
+) -> Result<(), ValueWriteError> {
+    rmp::encode::write_array_len(stream, 2)?;
+    rmp::encode::write_str(stream, method.as_str())?;
+    match method {
+        AuthMethod::ChapSha1 => {
+            let scramble = chap_sha1_scramble(password, salt);
+            rmp::encode::write_str_len(stream, scramble.len() as _)?;
+            stream
+                .write_all(&scramble)
+                .map_err(ValueWriteError::InvalidDataWrite)?;
+        }
+        AuthMethod::PapSha256 => {
+            rmp::encode::write_str(stream, password)?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn chap_sha1_scramble(password: &str, salt: &[u8]) -> [u8; 20] {
+    // prepare 'chap-sha1' scramble:
+    // salt = base64_decode(encoded_salt);
+    // step_1 = sha1(password);
+    // step_2 = sha1(step_1);
+    // step_3 = sha1(first_20_bytes_of_salt, step_2);
+    // scramble = xor(step_1, step_3);
+
+    let mut hasher = Sha1::new();
+    hasher.update(password.as_bytes());
+    let step_1 = hasher.finalize();
+
+    let mut hasher = Sha1::new();
+    hasher.update(step_1);
+    let step_2 = hasher.finalize();
+
+    let mut hasher = Sha1::new();
+    hasher.update(&salt[0..20]);
+    hasher.update(step_2);
+    let step_3 = hasher.finalize();
+
+    let mut scramble = [0; 20];
+    scramble
+        .iter_mut()
+        .zip(step_1.iter().zip(step_3.iter()))
+        .for_each(|(s, (a, b))| *s = a ^ b);
+    scramble
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn salt() -> Vec<u8> {
+        base64::decode("QK2HoFZGXTXBq2vFj7soCsHqTo6PGTF575ssUBAJLAI=").unwrap()
+    }
+
+    #[test]
+    fn chap_sha1() {
+        let mut data = vec![];
+        encode_auth_data(&mut data, AuthMethod::ChapSha1, "password", &salt()).unwrap();
+
+        let mut expected = vec![0x92, 0xa9];
+        expected.extend(b"chap-sha1");
+        expected.push(0xb4);
+        expected.extend([
+            0xe5, 0x26, 0x3c, 0xc6, 0x4c, 0xe7, 0x95, 0x9b, 0x15, 0x82, 0x13, 0x0f, 0x8b, 0x99,
+            0x49, 0x65, 0xe0, 0x1a, 0x05, 0xbe,
+        ]);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn pap_sha256() {
+        let mut data = vec![];
+        encode_auth_data(&mut data, AuthMethod::PapSha256, "password", &salt()).unwrap();
+
+        let mut expected = vec![0x92, 0xaa];
+        expected.extend(b"pap-sha256");
+        expected.push(0xa8);
+        expected.extend(b"password");
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn rejected_method() {
+        let unknown = TarantoolErrorCode::UnknownAuthMethod as u32;
+        let invalid_msgpack = TarantoolErrorCode::InvalidMsgpack as u32;
+        let mismatch = TarantoolErrorCode::PasswordMismatch as u32;
+
+        assert!(AuthMethod::ChapSha1.is_rejected_by(unknown));
+        assert!(!AuthMethod::ChapSha1.is_rejected_by(invalid_msgpack));
+        assert!(!AuthMethod::ChapSha1.is_rejected_by(mismatch));
+
+        assert!(AuthMethod::PapSha256.is_rejected_by(unknown));
+        assert!(AuthMethod::PapSha256.is_rejected_by(invalid_msgpack));
+        assert!(!AuthMethod::PapSha256.is_rejected_by(mismatch));
+    }
+}
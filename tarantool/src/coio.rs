@@ -7,10 +7,12 @@ use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::ffi::c_void;
 use std::io::{self, Read, Write};
-use std::mem::forget;
+use std::mem::{self, forget};
 use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+use std::path::Path;
 use std::rc::Rc;
 use std::time::Duration;
 
@@ -55,6 +57,21 @@ impl CoIOStream {
         })
     }
 
+    /// Connect to a Unix domain socket at `path`. Yields if the connection
+    /// can't be established right away.
+    pub fn connect_unix(path: impl AsRef<Path>) -> Result<CoIOStream, io::Error> {
+        connect_unix(path.as_ref(), TIMEOUT_INFINITY)
+    }
+
+    /// Connect to a Unix domain socket at `path` with a timeout.
+    /// Yields if the connection can't be established right away.
+    pub fn connect_unix_timeout(
+        path: impl AsRef<Path>,
+        timeout: Duration,
+    ) -> Result<CoIOStream, io::Error> {
+        connect_unix(path.as_ref(), timeout.as_secs_f64())
+    }
+
     /// Opens a TCP connection to a remote host with a timeout.
     pub fn connect_timeout(addr: &SocketAddr, timeout: Duration) -> Result<CoIOStream, io::Error> {
         let inner_stream = TcpStream::connect_timeout(addr, timeout)?;
@@ -147,11 +164,22 @@ impl CoIOListener {
         }
     }
 
+    /// Returns the local socket address of this listener.
+    pub fn local_addr(&self) -> Result<SocketAddr, io::Error> {
+        self.inner.local_addr()
+    }
+
     pub fn inner_listener(&mut self) -> &mut TcpListener {
         &mut self.inner
     }
 }
 
+impl AsRawFd for CoIOListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
 impl TryFrom<TcpListener> for CoIOListener {
     type Error = io::Error;
 
@@ -231,6 +259,89 @@ pub fn getaddrinfo(
     }
 }
 
+fn connect_unix(path: &Path, timeout: f64) -> Result<CoIOStream, io::Error> {
+    let (stream, in_progress) = start_connect_unix(path)?;
+    if in_progress {
+        coio_wait(stream.fd, ffi::CoIOFlags::WRITE, timeout)?;
+        take_socket_error(stream.fd)?;
+    }
+    Ok(stream)
+}
+
+/// Creates a nonblocking Unix domain socket and starts connecting it to
+/// `path`. Returns the stream and `true` if the connection is in progress,
+/// in which case it is established once the socket becomes writable and
+/// its result must be checked with [`take_socket_error`].
+pub(crate) fn start_connect_unix(path: &Path) -> Result<(CoIOStream, bool), io::Error> {
+    // SAFETY: all zeros is a valid `sockaddr_un`
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    let path = path.as_os_str().as_bytes();
+    // The path must be nul terminated
+    if path.len() >= addr.sun_path.len() || path.contains(&0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid unix socket path",
+        ));
+    }
+    addr.sun_family = libc::AF_UNIX as _;
+    for (dst, &src) in addr.sun_path.iter_mut().zip(path) {
+        *dst = src as c_char;
+    }
+
+    let fd = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Is closed on error
+    let stream = CoIOStream { fd };
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL, 0) };
+    if flags < 0
+        || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0
+        || unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } < 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+
+    let res = unsafe {
+        libc::connect(
+            fd,
+            &addr as *const libc::sockaddr_un as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_un>() as libc::socklen_t,
+        )
+    };
+    if res == 0 {
+        return Ok((stream, false));
+    }
+    let err = io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::EINPROGRESS) {
+        return Ok((stream, true));
+    }
+    Err(err)
+}
+
+/// Takes the pending error of the socket `fd`, e.g. the result of
+/// a nonblocking connect.
+pub(crate) fn take_socket_error(fd: RawFd) -> Result<(), io::Error> {
+    let mut err: c_int = 0;
+    let mut len = mem::size_of::<c_int>() as libc::socklen_t;
+    let res = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_ERROR,
+            &mut err as *mut c_int as *mut c_void,
+            &mut len,
+        )
+    };
+    if res < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if err != 0 {
+        return Err(io::Error::from_raw_os_error(err));
+    }
+    Ok(())
+}
+
 #[inline(always)]
 pub(crate) fn read(
     fd: RawFd,
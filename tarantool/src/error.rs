@@ -16,6 +16,7 @@
 //! the current value and returning -1 to Tarantool from your
 //! stored procedure.
 
+use std::collections::HashMap;
 use std::ffi::CStr;
 use std::fmt::{self, Display, Formatter};
 use std::io;
@@ -74,6 +75,15 @@ pub enum Error {
     #[error("Server responded with error: {0}")]
     Remote(crate::net_box::ResponseError),
 
+    #[error("Authentication method {0} is not supported by the server")]
+    UnsupportedAuthMethod(crate::auth::AuthMethod),
+
+    /// The server announced its shutdown, see
+    /// [`ConnOptions::shutdown_timeout`](crate::net_box::ConnOptions::shutdown_timeout).
+    #[cfg(feature = "net_box")]
+    #[error("Server is shutting down")]
+    ServerShutdown,
+
     #[error("Lua error: {0}")]
     LuaError(LuaError),
 
@@ -168,6 +178,29 @@ pub enum TransactionError {
 
     #[error("Failed to rollback")]
     FailedToRollback,
+
+    #[error("Failed to create a savepoint")]
+    FailedToCreateSavepoint,
+
+    #[error("Failed to rollback to savepoint")]
+    FailedToRollbackToSavepoint,
+
+    /// The savepoint was created in another transaction, which has already
+    /// ended or belongs to a different fiber.
+    #[error("Savepoint does not belong to the current transaction")]
+    SavepointExpired,
+
+    #[error("Failed to set transaction options")]
+    FailedToSetOptions,
+
+    /// The transaction was aborted because of a conflict with a concurrent
+    /// transaction and can be retried.
+    #[error("Transaction has been aborted by conflict")]
+    Conflict,
+
+    /// The transaction was aborted because its timeout expired.
+    #[error("Transaction has been aborted by timeout")]
+    TimedOut,
 }
 
 impl From<TransactionError> for Error {
@@ -223,6 +256,12 @@ impl TarantoolError {
         self.code
     }
 
+    /// Return the error message without the error code
+    #[inline(always)]
+    pub(crate) fn message(&self) -> &str {
+        &self.message
+    }
+
     /// Return the error type, e.g. "ClientError", "SocketError", etc.
     pub fn error_type(&self) -> String {
         let result = unsafe { ffi::box_error_type(&*self.error_ptr) };
@@ -507,6 +546,13 @@ pub enum TarantoolErrorCode {
     NoElectionQuorum = 242,
     Ssl = 243,
     SplitBrain = 244,
+    OldTerm = 245,
+    InterferingElections = 246,
+    IteratorPosition = 247,
+    DefaultValueType = 248,
+    UnknownAuthMethod = 249,
+    InvalidAuthData = 250,
+    InvalidAuthRequest = 251,
 }
 
 impl TarantoolErrorCode {
@@ -553,6 +599,124 @@ macro_rules! set_error {
     };
 }
 
+/// Error received from a remote Tarantool instance with all the details
+/// transferred in the `MP_ERROR` format.
+///
+/// Represents a `box.error` object. The error it was caused by (`prev` in
+/// terms of `box.error`) is available via [`ExtendedError::cause`] and
+/// [`std::error::Error::source`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtendedError {
+    /// Error type, e.g. `"ClientError"` or `"CustomError"`.
+    pub error_type: String,
+    /// Source file in which the error was raised.
+    pub file: String,
+    /// Line of the source file in which the error was raised.
+    pub line: u32,
+    pub message: String,
+    /// Saved `errno` value or 0 if there is none.
+    pub errno: u32,
+    /// Error code. Usually it is one of [`TarantoolErrorCode`].
+    pub code: u32,
+    /// Additional fields specific to the error type, e.g. `custom_type` of
+    /// a `CustomError` or the fields passed to `box.error.new`.
+    pub fields: HashMap<String, rmpv::Value>,
+    pub cause: Option<Box<ExtendedError>>,
+}
+
+impl ExtendedError {
+    /// Decodes a `MP_ERROR` map containing the error stack.
+    ///
+    /// Returns the top error of the stack with the rest of the stack set as
+    /// its causes or `None` if the stack is empty.
+    pub fn decode(stream: &mut (impl io::Read + io::Seek)) -> Result<Option<Self>> {
+        let mut stack = Vec::new();
+        let map_len = rmp::decode::read_map_len(stream)?;
+        for _ in 0..map_len {
+            if rmp::decode::read_int::<u8, _>(stream)? != MP_ERROR_STACK {
+                crate::msgpack::skip_value(stream)?;
+                continue;
+            }
+            let stack_len = rmp::decode::read_array_len(stream)?;
+            for _ in 0..stack_len {
+                stack.push(Self::decode_one(stream)?);
+            }
+        }
+
+        // The stack starts with the most recent error
+        let mut result = None;
+        for mut error in stack.into_iter().rev() {
+            error.cause = result.map(Box::new);
+            result = Some(error);
+        }
+        Ok(result)
+    }
+
+    fn decode_one(stream: &mut (impl io::Read + io::Seek)) -> Result<Self> {
+        fn read_str(stream: &mut impl io::Read) -> Result<String> {
+            let len = rmp::decode::read_str_len(stream)? as usize;
+            let mut buf = vec![0; len];
+            stream.read_exact(&mut buf)?;
+            String::from_utf8(buf).map_err(|e| e.utf8_error().into())
+        }
+
+        let mut error = Self::default();
+        let map_len = rmp::decode::read_map_len(stream)?;
+        for _ in 0..map_len {
+            match rmp::decode::read_int(stream)? {
+                MP_ERROR_TYPE => error.error_type = read_str(stream)?,
+                MP_ERROR_FILE => error.file = read_str(stream)?,
+                MP_ERROR_LINE => error.line = rmp::decode::read_int(stream)?,
+                MP_ERROR_MESSAGE => error.message = read_str(stream)?,
+                MP_ERROR_ERRNO => error.errno = rmp::decode::read_int(stream)?,
+                MP_ERROR_CODE => error.code = rmp::decode::read_int(stream)?,
+                MP_ERROR_FIELDS => {
+                    let len = rmp::decode::read_map_len(stream)?;
+                    for _ in 0..len {
+                        let key = read_str(stream)?;
+                        let value = rmp_serde::from_read(&mut *stream)?;
+                        error.fields.insert(key, value);
+                    }
+                }
+                _ => crate::msgpack::skip_value(stream)?,
+            }
+        }
+        Ok(error)
+    }
+
+    /// Returns the error this error was caused by.
+    #[inline(always)]
+    pub fn cause(&self) -> Option<&ExtendedError> {
+        self.cause.as_deref()
+    }
+}
+
+impl Display for ExtendedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.error_type, self.message)
+    }
+}
+
+impl std::error::Error for ExtendedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn std::error::Error + 'static))
+    }
+}
+
+// Keys of the `MP_ERROR` map
+const MP_ERROR_STACK: u8 = 0x00;
+
+// Keys of the error maps within the `MP_ERROR_STACK`
+const MP_ERROR_TYPE: u8 = 0x00;
+const MP_ERROR_FILE: u8 = 0x01;
+const MP_ERROR_LINE: u8 = 0x02;
+const MP_ERROR_MESSAGE: u8 = 0x03;
+const MP_ERROR_ERRNO: u8 = 0x04;
+const MP_ERROR_CODE: u8 = 0x05;
+const MP_ERROR_FIELDS: u8 = 0x06;
+
 /// Error that can happen when serializing a tuple
 #[derive(Debug, thiserror::Error)]
 pub enum Encode {
@@ -32,6 +32,26 @@ pub fn has_fiber_channel() -> bool {
     }
 }
 
+/// Check whether the current tarantool executable supports setting the
+/// isolation level and the timeout of a transaction.
+/// If this function returns `false` then starting a transaction with
+/// non-default [`TransactionOptions`] will always result in an `Error`.
+///
+/// [`TransactionOptions`]: crate::transaction::TransactionOptions
+pub fn has_transaction_options() -> bool {
+    unsafe { helper::has_dyn_symbol(crate::c_str!("box_txn_set_isolation")) }
+}
+
+/// Check whether the current tarantool executable supports keyset pagination.
+/// If this function returns `false` then [`Index::select_page`] and
+/// [`Index::tuple_position`] will always result in an `Error`.
+///
+/// [`Index::select_page`]: crate::index::Index::select_page
+/// [`Index::tuple_position`]: crate::index::Index::tuple_position
+pub fn has_keyset_pagination() -> bool {
+    unsafe { helper::has_dyn_symbol(crate::c_str!("box_index_iterator_after")) }
+}
+
 /// Check whether the current tarantool executable supports getting tuple fields
 /// by json pattern.
 /// If this function returns `false` then
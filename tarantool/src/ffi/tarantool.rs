@@ -645,12 +645,32 @@ extern "C" {
 }
 
 // Transaction.
+#[repr(C)]
+pub struct BoxTxnSavepoint {
+    _unused: [u8; 0],
+}
+
 extern "C" {
     pub fn box_txn() -> bool;
+    /// Returns the unique id of the current transaction or -1 if there is
+    /// none.
+    pub fn box_txn_id() -> i64;
     pub fn box_txn_begin() -> c_int;
     pub fn box_txn_commit() -> c_int;
     pub fn box_txn_rollback() -> c_int;
     pub fn box_txn_alloc(size: usize) -> *mut c_void;
+    pub fn box_txn_savepoint() -> *mut BoxTxnSavepoint;
+    pub fn box_txn_rollback_to_savepoint(savepoint: *mut BoxTxnSavepoint) -> c_int;
+}
+
+crate::define_dlsym_reloc! {
+    /// Set the timeout in seconds after which the current transaction is
+    /// rolled back. Available since tarantool 2.10.
+    pub fn box_txn_set_timeout(timeout: f64) -> c_int;
+
+    /// Set the isolation level of the current transaction. Must be called
+    /// before the first statement. Available since tarantool 2.10.
+    pub fn box_txn_set_isolation(level: u32) -> c_int;
 }
 
 // Indexes, spaces and tuples.
@@ -758,6 +778,48 @@ extern "C" {
     pub fn box_iterator_free(iterator: *mut BoxIterator);
 }
 
+crate::define_dlsym_reloc! {
+    /// Allocate and initialize iterator for `space_id`, `index_id` starting
+    /// after the tuple at the packed position (`pos`, `pos_end`). Starts from
+    /// the `key` if `pos` is null. Available since tarantool 2.11.
+    pub fn box_index_iterator_after(
+        space_id: u32,
+        index_id: u32,
+        type_: c_int,
+        key: *const c_char,
+        key_end: *const c_char,
+        pos: *const c_char,
+        pos_end: *const c_char,
+    ) -> *mut BoxIterator;
+
+    /// Return the packed position of the last tuple returned by the
+    /// `iterator`. The position is allocated on the box region. Available
+    /// since tarantool 2.11.
+    pub fn box_iterator_position(
+        iterator: *mut BoxIterator,
+        pos: *mut *const c_char,
+        pos_end: *mut *const c_char,
+    ) -> c_int;
+
+    /// Return the packed position of the `tuple` in the index. The position
+    /// is allocated on the box region. Available since tarantool 2.11.
+    pub fn box_index_tuple_position(
+        space_id: u32,
+        index_id: u32,
+        tuple: *const c_char,
+        tuple_end: *const c_char,
+        pos: *mut *const c_char,
+        pos_end: *mut *const c_char,
+    ) -> c_int;
+
+    /// Return the size of the memory allocated on the box region.
+    pub fn box_region_used() -> usize;
+
+    /// Free the memory allocated on the box region after the first `size`
+    /// bytes.
+    pub fn box_region_truncate(size: usize);
+}
+
 #[cfg(not(feature = "picodata"))]
 #[repr(C)]
 pub struct BoxTuple {
@@ -8,14 +8,14 @@
 //! - [Indexes](https://www.tarantool.io/en/doc/latest/book/box/data_model/#indexes)
 //! - [Lua reference: Submodule box.index](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_index/)
 use std::mem::MaybeUninit;
-use std::os::raw::c_char;
-use std::ptr::null_mut;
+use std::os::raw::{c_char, c_int};
+use std::ptr::{null, null_mut};
 
 use num_derive::ToPrimitive;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 
-use crate::error::{Error, TarantoolError};
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
 use crate::ffi::tarantool as ffi;
 use crate::msgpack;
 use crate::tuple::{ToTupleBuffer, Tuple, TupleBuffer};
@@ -47,7 +47,7 @@ pub struct Index {
 /// use `GE` or `LE` iteration types with start key equal to `None`.
 /// For `EQ`, the key must not be `None`.
 #[repr(i32)]
-#[derive(Debug, Copy, Clone, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ToPrimitive)]
 pub enum IteratorType {
     /// key == x ASC order
     Eq = 0,
@@ -535,6 +535,121 @@ impl Index {
         })
     }
 
+    /// Select at most `limit` tuples following the tuple at the `after`
+    /// position (or starting from the beginning if it's `None`) and return
+    /// them along with the position of the last one, which can be passed as
+    /// `after` to get the next page. The returned position is `None` if no
+    /// tuples were selected.
+    ///
+    /// Unlike paging with an offset, finding the start of the page doesn't
+    /// require scanning the previous tuples and isn't affected by concurrent
+    /// inserts and deletes.
+    ///
+    /// - `type` - iterator type
+    /// - `key` - encoded key in MsgPack Array format (`[part1, part2, ...]`).
+    ///
+    /// Requires Tarantool 2.11 or later (see [`has_keyset_pagination`]) and is
+    /// only supported for `TREE` indexes.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use tarantool::index::{Index, IteratorType};
+    /// # fn f(index: Index) -> tarantool::Result<()> {
+    /// let mut after = None;
+    /// loop {
+    ///     let (tuples, position) = index.select_page(IteratorType::All, &(), after.as_ref(), 100)?;
+    ///     for tuple in tuples {
+    ///         // ...
+    ///     }
+    ///     if position.is_none() {
+    ///         break;
+    ///     }
+    ///     after = position;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`has_keyset_pagination`]: crate::ffi::has_keyset_pagination
+    pub fn select_page<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        after: Option<&TuplePosition>,
+        limit: u32,
+    ) -> Result<(Vec<Tuple>, Option<TuplePosition>), Error>
+    where
+        K: ToTupleBuffer,
+    {
+        if !crate::ffi::has_keyset_pagination() {
+            return Err(pagination_unsupported());
+        }
+        let key_buf = key.to_tuple_buffer()?;
+        let key_buf_ptr = key_buf.as_ptr() as *const c_char;
+        let after = after.map_or(&[][..], |position| &position.0);
+        let after_ptr = match after {
+            [] => null(),
+            after => after.as_ptr() as *const c_char,
+        };
+
+        let ptr = unsafe {
+            ffi::box_index_iterator_after(
+                self.space_id,
+                self.index_id,
+                iterator_type.to_i32().unwrap(),
+                key_buf_ptr,
+                key_buf_ptr.add(key_buf.len()),
+                after_ptr,
+                after_ptr.add(after.len()),
+            )
+        };
+        if ptr.is_null() {
+            return Err(TarantoolError::last().into());
+        }
+
+        let mut iterator = IndexIterator {
+            ptr,
+            _key_data: key_buf,
+        };
+        let tuples: Vec<_> = iterator.by_ref().take(limit as usize).collect();
+        if tuples.is_empty() {
+            return Ok((tuples, None));
+        }
+        let position = unsafe {
+            position_from_region(|pos, pos_end| {
+                ffi::box_iterator_position(iterator.ptr, pos, pos_end)
+            })?
+        };
+        Ok((tuples, Some(position)))
+    }
+
+    /// Returns the position of the `tuple` in the index, which can be passed
+    /// to [`Index::select_page`] to select the tuples following it.
+    ///
+    /// Requires Tarantool 2.11 or later (see [`has_keyset_pagination`]) and is
+    /// only supported for `TREE` indexes.
+    ///
+    /// [`has_keyset_pagination`]: crate::ffi::has_keyset_pagination
+    pub fn tuple_position(&self, tuple: &Tuple) -> Result<TuplePosition, Error> {
+        if !crate::ffi::has_keyset_pagination() {
+            return Err(pagination_unsupported());
+        }
+        let data = tuple.as_buffer();
+        let data_ptr = data.as_ptr() as *const c_char;
+        unsafe {
+            position_from_region(|pos, pos_end| {
+                ffi::box_index_tuple_position(
+                    self.space_id,
+                    self.index_id,
+                    data_ptr,
+                    data_ptr.add(data.len()),
+                    pos,
+                    pos_end,
+                )
+            })
+        }
+    }
+
     /// Delete a tuple identified by a key.
     ///
     /// Same as [space.delete()](../space/struct.Space.html#method.delete), but a key is searched in this index instead
@@ -803,6 +918,35 @@ impl Index {
     }
 }
 
+/// Position of a tuple in an index used for keyset pagination, see
+/// [`Index::select_page`].
+///
+/// Is an opaque value only meaningful for the index it was obtained from.
+/// Positions returned by the local [`Index`] and the remote indexes are not
+/// interchangeable.
+///
+/// Can be converted to bytes with [`TuplePosition::as_bytes`] and restored
+/// with [`TuplePosition::from_bytes`], e.g. to resume the pagination later.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TuplePosition(Vec<u8>);
+
+impl TuplePosition {
+    #[inline(always)]
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        Self(data)
+    }
+
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
 /// Index iterator. Can be used with `for` statement.
 pub struct IndexIterator {
     ptr: *mut ffi::BoxIterator,
@@ -826,3 +970,32 @@ impl Drop for IndexIterator {
         unsafe { ffi::box_iterator_free(self.ptr) };
     }
 }
+
+fn pagination_unsupported() -> Error {
+    crate::set_error!(
+        TarantoolErrorCode::Unsupported,
+        "Tarantool does not support keyset pagination"
+    );
+    TarantoolError::last().into()
+}
+
+/// Copies the position returned by `f` and frees the box region memory it
+/// was allocated on.
+///
+/// # Safety
+/// `f` must return a position allocated on the box region.
+unsafe fn position_from_region(
+    f: impl FnOnce(*mut *const c_char, *mut *const c_char) -> c_int,
+) -> Result<TuplePosition, Error> {
+    let used = ffi::box_region_used();
+    let mut pos = null();
+    let mut pos_end = null();
+    if f(&mut pos, &mut pos_end) < 0 {
+        ffi::box_region_truncate(used);
+        return Err(TarantoolError::last().into());
+    }
+    let len = pos_end.offset_from(pos) as usize;
+    let position = std::slice::from_raw_parts(pos as *const u8, len).to_vec();
+    ffi::box_region_truncate(used);
+    Ok(TuplePosition(position))
+}
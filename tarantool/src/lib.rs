@@ -153,6 +153,7 @@
 //! As you can see, calling a Rust function is as straightforward as it can be.
 //!
 //! [stored procedure]: macro@crate::proc
+pub mod auth;
 pub mod clock;
 pub mod coio;
 pub mod datetime;
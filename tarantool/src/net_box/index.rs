@@ -2,10 +2,11 @@ use std::rc::Rc;
 use std::vec::IntoIter;
 
 use crate::error::Error;
-use crate::index::IteratorType;
+use crate::index::{IteratorType, TuplePosition};
 use crate::tuple::{Encode, ToTupleBuffer, Tuple};
 
 use super::inner::ConnInner;
+use super::promise::Promise;
 use super::protocol;
 use super::Options;
 
@@ -77,6 +78,41 @@ impl RemoteIndex {
         )
     }
 
+    /// The remote-call equivalent of the local call `Index::select_page(...)`
+    /// (see [details](crate::index::Index::select_page)).
+    ///
+    /// At most `options.limit` tuples are selected, `options.offset` is
+    /// ignored. Fails with [`std::io::ErrorKind::Unsupported`] if the server
+    /// doesn't support keyset pagination, which requires Tarantool 2.11 or
+    /// later.
+    pub fn select_page<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        after: Option<&TuplePosition>,
+        options: &Options,
+    ) -> Result<(Vec<Tuple>, Option<TuplePosition>), Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.conn_inner.request(
+            |buf, sync| {
+                protocol::encode_select_page(
+                    buf,
+                    sync,
+                    self.space_id,
+                    self.index_id,
+                    options.limit.unwrap_or(u32::MAX),
+                    iterator_type,
+                    key,
+                    after,
+                )
+            },
+            |buf, _| protocol::decode_select_page(buf),
+            options,
+        )
+    }
+
     /// The remote-call equivalent of the local call `Space::update(...)`
     /// (see [details](../index/struct.Index.html#method.update)).
     pub fn update<K, Op>(
@@ -131,6 +167,120 @@ impl RemoteIndex {
     }
 }
 
+impl RemoteIndex {
+    /// Same as [`get`](Self::get), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    pub fn get_async<K>(&self, key: &K) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.conn_inner.request_async_with_decoder(
+            protocol::Select {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                limit: 1,
+                offset: 0,
+                iterator_type: IteratorType::Eq,
+                key,
+            },
+            protocol::decode_first_row,
+        )
+    }
+
+    /// Same as [`select`](Self::select), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received. `options.timeout` is ignored, use
+    /// [`IntoTimeout`] on the promise instead.
+    ///
+    /// [`IntoTimeout`]: crate::fiber::r#async::timeout::IntoTimeout
+    pub fn select_async<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        options: &Options,
+    ) -> Result<Promise<Vec<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.conn_inner.request_async_with_decoder(
+            protocol::Select {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                limit: options.limit.unwrap_or(u32::MAX),
+                offset: options.offset,
+                iterator_type,
+                key,
+            },
+            protocol::decode_rows,
+        )
+    }
+
+    /// Same as [`update`](Self::update), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    pub fn update_async<K, Op>(&self, key: &K, ops: &[Op]) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.conn_inner.request_async_with_decoder(
+            protocol::Update {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                key,
+                ops,
+            },
+            protocol::decode_first_row,
+        )
+    }
+
+    /// Same as [`upsert`](Self::upsert), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    pub fn upsert_async<T, Op>(
+        &self,
+        value: &T,
+        ops: &[Op],
+    ) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        T: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.conn_inner.request_async_with_decoder(
+            protocol::Upsert {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                value,
+                ops,
+            },
+            protocol::decode_first_row,
+        )
+    }
+
+    /// Same as [`delete`](Self::delete), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    pub fn delete_async<K>(&self, key: &K) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.conn_inner.request_async_with_decoder(
+            protocol::Delete {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                key,
+            },
+            protocol::decode_first_row,
+        )
+    }
+}
+
 /// Remote index iterator. Can be used with `for` statement
 pub struct RemoteIndexIterator {
     inner: IntoIter<Tuple>,
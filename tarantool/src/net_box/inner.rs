@@ -1,38 +1,52 @@
 use core::cell::RefCell;
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::io::{self, Cursor, Read, Write};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::rc::{Rc, Weak};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::coio::CoIOStream;
 use crate::error::Error;
 use crate::fiber::{is_cancelled, set_cancellable, sleep, time, Cond, Fiber};
 use crate::net_box::stream::ConnStream;
+use crate::network::interceptor::{Interceptor, Interceptors};
+use crate::network::protocol::{Feature, ProtocolFeatures};
 use crate::tuple::Decode;
 use crate::unwrap_or;
 
 use super::options::{ConnOptions, ConnTriggers, Options};
 use super::promise::Promise;
-use super::protocol::{self, Header, Request};
-use super::recv_queue::RecvQueue;
+use super::protocol::{self, Consumer, Header, Request};
+use super::recv_queue::{RecvQueue, SHUTDOWN_KEY};
 use super::schema::ConnSchema;
 use super::send_queue::{self, SendQueue};
 use super::Conn;
 
+/// Resolved address of the remote server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Address {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
 #[derive(Debug, Copy, Clone)]
 enum ConnState {
     Init,
     Connecting,
     Auth,
     Active,
+    /// The server announced its shutdown, the requests in flight are
+    /// awaiting response.
+    ShuttingDown,
     Error,
     ErrorReconnect,
     Closed,
 }
 
 pub struct ConnInner {
-    addrs: Vec<SocketAddr>,
+    addrs: Vec<Address>,
     options: ConnOptions,
     state: Cell<ConnState>,
     state_change_cond: Cond,
@@ -45,11 +59,26 @@ pub struct ConnInner {
     recv_fiber: RefCell<Fiber<'static, Weak<ConnInner>>>,
     triggers: RefCell<Option<Rc<dyn ConnTriggers>>>,
     error: RefCell<Option<io::Error>>,
+    interceptors: Rc<Interceptors>,
+    /// Time until which the requests in flight are awaited once the server
+    /// announced its shutdown.
+    shutdown_deadline: Cell<Option<Instant>>,
+    /// Incremented every time the connection is established.
+    connection_id: Cell<u64>,
+    /// Features negotiated with the server when the connection was
+    /// established.
+    features: Cell<ProtocolFeatures>,
+    /// Number of [`PreparedStatement`] handles by statement id. Statements
+    /// belong to the session, so it is cleared once the connection is
+    /// re-established.
+    ///
+    /// [`PreparedStatement`]: super::PreparedStatement
+    prepared_statements: RefCell<HashMap<u32, usize>>,
 }
 
 impl ConnInner {
     pub fn new(
-        addrs: Vec<SocketAddr>,
+        addrs: Vec<Address>,
         options: ConnOptions,
         triggers: Option<Rc<dyn ConnTriggers>>,
     ) -> Rc<Self> {
@@ -61,6 +90,8 @@ impl ConnInner {
         let mut send_fiber = Fiber::new("_send_worker", &mut send_worker);
         send_fiber.set_joinable(true);
 
+        let interceptors = Rc::new(Interceptors::default());
+
         // construct object
         let conn_inner = Rc::new(ConnInner {
             state: Cell::new(ConnState::Init),
@@ -73,11 +104,16 @@ impl ConnInner {
                 options.send_buffer_limit,
                 options.send_buffer_flush_interval,
             ),
-            recv_queue: RecvQueue::new(options.recv_buffer_size),
+            recv_queue: RecvQueue::new(options.recv_buffer_size, interceptors.clone()),
             send_fiber: RefCell::new(send_fiber),
             recv_fiber: RefCell::new(recv_fiber),
             triggers: RefCell::new(triggers),
             error: RefCell::new(None),
+            interceptors,
+            shutdown_deadline: Cell::new(None),
+            connection_id: Cell::new(0),
+            features: Cell::new(ProtocolFeatures::default()),
+            prepared_statements: RefCell::new(HashMap::new()),
             addrs,
             options,
         });
@@ -99,6 +135,40 @@ impl ConnInner {
         matches!(self.state.get(), ConnState::Active)
     }
 
+    /// Registers a handle to the prepared statement with `stmt_id`. Returns
+    /// the id of the connection in the session of which the statement was
+    /// prepared.
+    pub(crate) fn acquire_statement(&self, stmt_id: u32) -> u64 {
+        *self
+            .prepared_statements
+            .borrow_mut()
+            .entry(stmt_id)
+            .or_default() += 1;
+        self.connection_id.get()
+    }
+
+    /// Unregisters a handle acquired with [`Self::acquire_statement`].
+    /// Returns `true` if it was the last handle to the statement and
+    /// the statement must be deallocated on the server, which is not needed
+    /// if the connection was re-established since it was prepared.
+    pub(crate) fn release_statement(&self, stmt_id: u32, connection_id: u64) -> bool {
+        if self.connection_id.get() != connection_id {
+            return false;
+        }
+        let mut statements = self.prepared_statements.borrow_mut();
+        match statements.get_mut(&stmt_id) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                false
+            }
+            Some(_) => {
+                statements.remove(&stmt_id);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn wait_connected(self: &Rc<Self>, timeout: Option<Duration>) -> Result<bool, Error> {
         let begin_ts = time();
         loop {
@@ -142,24 +212,27 @@ impl ConnInner {
                     self.init()?;
                 }
                 ConnState::Active => {
-                    return match self.send_queue.send(request_producer) {
+                    return match self.send(request_producer) {
                         Ok(sync) => {
-                            self.recv_queue
+                            let result = self
+                                .recv_queue
                                 .recv(sync, response_consumer, options)
                                 .map(|response| {
                                     self.schema_version
                                         .set(Some(response.header.schema_version));
                                     response.payload
-                                })
+                                });
+                            let error = result.as_ref().err().map(|e| e as _);
+                            self.interceptors.after_response(sync, error);
+                            result
                         }
                         Err(err) => Err(self.handle_error(err).err().unwrap()),
                     };
                 }
                 ConnState::Error => self.disconnect(),
                 ConnState::ErrorReconnect => self.reconnect_or_fail()?,
-                ConnState::Closed => {
-                    return Err(io::Error::from(io::ErrorKind::NotConnected).into())
-                }
+                ConnState::ShuttingDown => return Err(Error::ServerShutdown),
+                ConnState::Closed => return Err(self.closed_error()),
                 _ => {
                     self.wait_state_changed(None);
                 }
@@ -171,6 +244,35 @@ impl ConnInner {
     where
         I: Request,
         O: for<'de> Decode<'de> + 'static,
+    {
+        self.request_async_with_decoder(request, |data| O::decode(data))
+    }
+
+    /// Same as [`Self::request_async`], but the `IPROTO_DATA` value of the
+    /// response is decoded with `decode`.
+    pub(crate) fn request_async_with_decoder<I, O>(
+        self: &Rc<Self>,
+        request: I,
+        decode: fn(&[u8]) -> crate::Result<O>,
+    ) -> crate::Result<Promise<O>>
+    where
+        I: Request,
+        O: 'static,
+    {
+        let promise = Promise::new(Rc::downgrade(self), decode);
+        self.request_with_consumer(request, promise.downgrade())?;
+        Ok(promise)
+    }
+
+    /// Sends the request without yielding. The response and the pushes of the
+    /// request are passed to the `consumer` if it is still alive at that time.
+    pub(crate) fn request_with_consumer<I>(
+        self: &Rc<Self>,
+        request: I,
+        consumer: Weak<dyn Consumer>,
+    ) -> crate::Result<()>
+    where
+        I: Request,
     {
         loop {
             match self.state.get() {
@@ -179,18 +281,15 @@ impl ConnInner {
                 }
                 ConnState::Active => {
                     let sync = self
-                        .send_queue
                         .send(protocol::request_producer(request))
                         .map_err(|err| self.handle_error(err).err().unwrap())?;
-                    let promise = Promise::new(Rc::downgrade(self));
-                    self.recv_queue.add_consumer(sync, promise.downgrade());
-                    return Ok(promise);
+                    self.recv_queue.add_consumer(sync, consumer);
+                    return Ok(());
                 }
                 ConnState::Error => self.disconnect(),
                 ConnState::ErrorReconnect => self.reconnect_or_fail()?,
-                ConnState::Closed => {
-                    return Err(io::Error::from(io::ErrorKind::NotConnected).into())
-                }
+                ConnState::ShuttingDown => return Err(Error::ServerShutdown),
+                ConnState::Closed => return Err(self.closed_error()),
                 _ => {
                     self.wait_state_changed(None);
                 }
@@ -198,6 +297,33 @@ impl ConnInner {
         }
     }
 
+    /// Writes the request produced by `request_producer` to the send queue
+    /// and reports it to the interceptors. Returns the sync of the request.
+    fn send<Fp>(&self, request_producer: Fp) -> Result<u64, Error>
+    where
+        Fp: FnOnce(&mut Cursor<Vec<u8>>, u64) -> Result<(), Error>,
+    {
+        if self.interceptors.is_empty() {
+            return self.send_queue.send(request_producer);
+        }
+        // The request is copied, so that the interceptors are called after
+        // the send queue is released
+        let mut data = Vec::new();
+        let sync = self.send_queue.send(|buf, sync| {
+            let start = buf.position() as usize;
+            request_producer(buf, sync)?;
+            data.extend_from_slice(&buf.get_ref()[start..buf.position() as usize]);
+            Ok(())
+        })?;
+        self.interceptors
+            .before_request(&data, |space_id| self.schema.lookup_space_name(space_id));
+        Ok(sync)
+    }
+
+    pub fn add_interceptor(&self, interceptor: Rc<dyn Interceptor>) {
+        self.interceptors.add(interceptor);
+    }
+
     pub fn lookup_space(self: &Rc<Self>, name: &str) -> Result<Option<u32>, Error> {
         self.refresh_schema()?;
         Ok(self.schema.lookup_space(name))
@@ -243,14 +369,25 @@ impl ConnInner {
 
         // connect
         let connect_timeout = self.options.connect_timeout;
-        let mut stream = if connect_timeout.subsec_nanos() == 0 && connect_timeout.as_secs() == 0 {
-            CoIOStream::connect(&*self.addrs)?
-        } else {
-            CoIOStream::connect_timeout(self.addrs.first().unwrap(), connect_timeout)?
+        let tcp_addrs: Vec<_> = self
+            .addrs
+            .iter()
+            .filter_map(|addr| match addr {
+                Address::Tcp(addr) => Some(*addr),
+                Address::Unix(_) => None,
+            })
+            .collect();
+        let no_timeout = connect_timeout.subsec_nanos() == 0 && connect_timeout.as_secs() == 0;
+        let mut stream = match self.addrs.first() {
+            Some(Address::Unix(path)) if no_timeout => CoIOStream::connect_unix(path)?,
+            Some(Address::Unix(path)) => CoIOStream::connect_unix_timeout(path, connect_timeout)?,
+            _ if no_timeout => CoIOStream::connect(&*tcp_addrs)?,
+            _ => CoIOStream::connect_timeout(tcp_addrs.first().unwrap(), connect_timeout)?,
         };
 
         // receive greeting msg
         let salt = protocol::decode_greeting(&mut stream)?;
+        let features = self.identify(&mut stream)?;
 
         // auth if required
         if !self.options.user.is_empty() {
@@ -260,7 +397,16 @@ impl ConnInner {
 
         // if ok: put stream to result + set state to active
         self.stream.replace(Some(ConnStream::new(stream)?));
+        self.connection_id.set(self.connection_id.get() + 1);
+        self.features.set(features);
+        self.shutdown_deadline.set(None);
+        self.prepared_statements.borrow_mut().clear();
         self.update_state(ConnState::Active);
+        if features.supports(Feature::Watchers) {
+            if let Err(e) = self.watch_shutdown() {
+                log::warn!("failed to watch the server shutdown: {}", e);
+            }
+        }
 
         // call trigger (if available)
         if let Some(triggers) = self.triggers.borrow().as_ref() {
@@ -271,20 +417,51 @@ impl ConnInner {
     }
 
     fn auth(&self, stream: &mut CoIOStream, salt: &[u8]) -> Result<(), Error> {
-        let buf = Vec::new();
-        let mut cur = Cursor::new(buf);
-
-        // send auth request
-        let sync = self.send_queue.next_sync();
-        send_queue::write_to_buffer(&mut cur, sync, |buf, sync| {
+        let (header, mut cur) = self.roundtrip(stream, |buf, sync| {
             protocol::encode_auth(
                 buf,
                 self.options.user.as_str(),
                 self.options.password.as_str(),
                 salt,
+                self.options.auth_method,
                 sync,
             )
         })?;
+        if header.status_code != 0 {
+            let error = protocol::decode_error(&mut cur, &header)?;
+            if self.options.auth_method.is_rejected_by(error.code) {
+                return Err(Error::UnsupportedAuthMethod(self.options.auth_method));
+            }
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+
+    /// Negotiates the protocol features with the server. Servers older than
+    /// 2.10 respond with an error, in which case none of the features are
+    /// supported.
+    fn identify(&self, stream: &mut CoIOStream) -> Result<ProtocolFeatures, Error> {
+        let (header, mut cur) = self.roundtrip(stream, protocol::encode_id)?;
+        if header.status_code != 0 {
+            return Ok(ProtocolFeatures::default());
+        }
+        protocol::decode_id(&mut cur)
+    }
+
+    /// Sends a request directly to the `stream` and reads the response. Is
+    /// used before the send and receive fibers are started.
+    fn roundtrip(
+        &self,
+        stream: &mut CoIOStream,
+        request_producer: impl FnOnce(&mut Cursor<Vec<u8>>, u64) -> Result<(), Error>,
+    ) -> Result<(Header, Cursor<Vec<u8>>), Error> {
+        let buf = Vec::new();
+        let mut cur = Cursor::new(buf);
+
+        // send request
+        let sync = self.send_queue.next_sync();
+        send_queue::write_to_buffer(&mut cur, sync, request_producer)?;
         stream.write_all(cur.get_ref())?;
 
         // handle response
@@ -298,11 +475,7 @@ impl ConnInner {
         }
 
         let header = protocol::decode_header(&mut cur)?;
-        if header.status_code != 0 {
-            return Err(protocol::decode_error(stream)?.into());
-        }
-
-        Ok(())
+        Ok((header, cur))
     }
 
     fn refresh_schema(self: &Rc<Self>) -> Result<(), Error> {
@@ -318,6 +491,45 @@ impl ConnInner {
         Ok(())
     }
 
+    /// Subscribes to the server shutdown notifications. Is also called to
+    /// acknowledge a notification, so that the server sends the next one.
+    ///
+    /// Must only be called if the server supports [`Feature::Watchers`].
+    fn watch_shutdown(&self) -> Result<(), Error> {
+        self.send_queue
+            .send(|buf, sync| protocol::encode_watch(buf, SHUTDOWN_KEY, sync))?;
+        Ok(())
+    }
+
+    /// Stops sending new requests and starts awaiting the requests in flight.
+    fn start_shutdown(&self) {
+        if !matches!(self.state.get(), ConnState::Active) {
+            return;
+        }
+        let deadline = Instant::now() + self.options.shutdown_timeout;
+        self.shutdown_deadline.set(Some(deadline));
+        self.update_state(ConnState::ShuttingDown);
+    }
+
+    /// Closes the connection after the server announced its shutdown. The
+    /// requests still in flight fail with [`Error::ServerShutdown`].
+    fn finish_shutdown(&self) {
+        if !matches!(self.state.get(), ConnState::ShuttingDown) {
+            return;
+        }
+        self.recv_queue.shutdown();
+        self.disconnect();
+    }
+
+    /// Returns the error for requests made after the connection is closed.
+    fn closed_error(&self) -> Error {
+        if self.recv_queue.is_server_shutdown() {
+            Error::ServerShutdown
+        } else {
+            io::Error::from(io::ErrorKind::NotConnected).into()
+        }
+    }
+
     fn update_state(&self, state: ConnState) {
         self.state.set(state);
         self.state_change_cond.broadcast();
@@ -335,6 +547,8 @@ impl ConnInner {
             return Ok(());
         }
 
+        // The reconnected stream must not inherit the shutdown timeout
+        self.shutdown_deadline.set(None);
         match err {
             Error::IO(err) => {
                 self.error.replace(Some(err));
@@ -405,7 +619,9 @@ fn send_worker(conn: Box<Weak<ConnInner>>) -> i32 {
         let conn = unwrap_or!(weak_conn.upgrade(), return 0);
 
         match conn.state.get() {
-            ConnState::Active => {
+            // The requests sent before the shutdown was announced are
+            // still flushed
+            ConnState::Active | ConnState::ShuttingDown => {
                 let mut writer = conn.stream.borrow().as_ref().unwrap().acquire_writer();
                 if let Err(e) = conn.send_queue.flush_to_stream(&mut writer) {
                     if is_cancelled() {
@@ -435,24 +651,51 @@ fn recv_worker(conn: Box<Weak<ConnInner>>) -> i32 {
         let conn = unwrap_or!(weak_conn.upgrade(), return 0);
 
         match conn.state.get() {
-            ConnState::Active => {
+            ConnState::Active | ConnState::ShuttingDown => {
                 let result = {
                     let mut reader = conn.stream.borrow().as_ref().unwrap().acquire_reader();
+                    if let Some(deadline) = conn.shutdown_deadline.get() {
+                        reader
+                            .set_timeout(Some(deadline.saturating_duration_since(Instant::now())));
+                    }
                     conn.recv_queue.pull(&mut reader)
                 };
+                let is_shutting_down = matches!(conn.state.get(), ConnState::ShuttingDown);
                 match result {
                     Err(e) => {
                         if is_cancelled() {
                             return 0;
                         }
-                        conn.handle_error(e).unwrap();
+                        if is_shutting_down {
+                            // Either the shutdown timeout expired or the
+                            // server closed the connection
+                            conn.finish_shutdown();
+                        } else {
+                            conn.handle_error(e).unwrap();
+                        }
                     }
                     Ok(is_data_pulled) => {
-                        if !is_data_pulled && conn.is_connected() {
+                        if !is_data_pulled && is_shutting_down {
+                            conn.finish_shutdown();
+                        } else if !is_data_pulled && conn.is_connected() {
                             conn.disconnect();
                         }
                     }
                 }
+                match conn.recv_queue.take_shutdown_event() {
+                    Some(true) => conn.start_shutdown(),
+                    Some(false) if conn.is_connected() => {
+                        if let Err(e) = conn.watch_shutdown() {
+                            log::warn!("failed to watch the server shutdown: {}", e);
+                        }
+                    }
+                    _ => {}
+                }
+                if matches!(conn.state.get(), ConnState::ShuttingDown)
+                    && conn.recv_queue.in_flight() == 0
+                {
+                    conn.finish_shutdown();
+                }
             }
             ConnState::Closed => return 0,
             _ => {
@@ -461,3 +704,47 @@ fn recv_worker(conn: Box<Weak<ConnInner>>) -> i32 {
         }
     }
 }
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::net_box::Options;
+    use crate::network::mock::{Expect, MockPeer, MockServer, Reply};
+
+    #[crate::test(tarantool = "crate")]
+    fn reconnect_after_shutdown() {
+        let peer = MockPeer::new()
+            .expect(
+                Expect::watch("box.shutdown"),
+                Reply::event("box.shutdown", &true).after(Duration::from_millis(50)),
+            )
+            .expect(
+                Expect::call("slow"),
+                Reply::drop_connection().after(Duration::from_millis(100)),
+            )
+            .expect(Expect::ping(), Reply::ok());
+        let server = MockServer::start(peer).unwrap();
+
+        let options = ConnOptions {
+            reconnect_after: Duration::from_millis(10),
+            shutdown_timeout: Duration::from_millis(100),
+            ..ConnOptions::default()
+        };
+        let conn = Conn::new(server.addr(), options, None).unwrap();
+        let _slow = conn.call_async::<_, ()>("slow", ()).unwrap();
+        sleep(Duration::from_millis(75));
+        assert!(matches!(conn.inner.state.get(), ConnState::ShuttingDown));
+        // Sending fails while the requests in flight are awaited
+        let error = io::Error::from(io::ErrorKind::BrokenPipe);
+        conn.inner.handle_error(error.into()).unwrap();
+
+        // The shutdown timeout has expired by now, but it doesn't apply to
+        // the re-established connection
+        sleep(Duration::from_millis(125));
+        conn.ping(&Options::default()).unwrap();
+        assert!(conn.is_connected());
+        conn.close();
+
+        server.finish().assert_done();
+    }
+}
@@ -40,17 +40,23 @@
 #![cfg(feature = "net_box")]
 
 use core::time::Duration;
+use std::io;
 use std::net::ToSocketAddrs;
 use std::rc::Rc;
 
 pub use index::{RemoteIndex, RemoteIndexIterator};
-use inner::ConnInner;
+use inner::{Address, ConnInner};
 pub use options::{ConnOptions, ConnTriggers, Options};
 use promise::Promise;
-pub(crate) use protocol::ResponseError;
+pub use protocol::ResponseError;
+use pushes::Pushes;
 pub use space::RemoteSpace;
+use statement::PreparedStatement;
 
 use crate::error::Error;
+use crate::network::interceptor::Interceptor;
+use crate::network::uri::{ToUri, Uri};
+use crate::sql::SqlResponse;
 use crate::tuple::{Decode, ToTupleBuffer, Tuple};
 
 mod index;
@@ -58,10 +64,12 @@ mod inner;
 mod options;
 pub mod promise;
 mod protocol;
+pub mod pushes;
 mod recv_queue;
 mod schema;
 mod send_queue;
 mod space;
+pub mod statement;
 mod stream;
 
 /// Connection to remote Tarantool server
@@ -77,14 +85,30 @@ impl Conn {
     /// automatically after a disconnect (see [reconnect_after](struct.ConnOptions.html#structfield.reconnect_after) option).
     /// The returned conn object supports methods for making remote requests, such as select, update or delete.
     ///
+    /// `addr` is either a `(host, port)` pair, a socket address or a string
+    /// in one of the formats described in [`network::uri`], e.g. `"host:port"`
+    /// or `"unix/:path"` for connecting over a Unix domain socket.
+    ///
     /// See also: [ConnOptions](struct.ConnOptions.html)
+    ///
+    /// [`network::uri`]: crate::network::uri
     pub fn new(
-        addr: impl ToSocketAddrs,
+        addr: impl ToUri,
         options: ConnOptions,
         triggers: Option<Rc<dyn ConnTriggers>>,
     ) -> Result<Self, Error> {
+        let uri = addr
+            .to_uri()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let addrs = match uri {
+            Uri::Tcp { host, port } => (host.as_str(), port)
+                .to_socket_addrs()?
+                .map(Address::Tcp)
+                .collect(),
+            Uri::Unix(path) => vec![Address::Unix(path)],
+        };
         Ok(Conn {
-            inner: ConnInner::new(addr.to_socket_addrs()?.collect(), options, triggers),
+            inner: ConnInner::new(addrs, options, triggers),
             is_master: true,
         })
     }
@@ -111,6 +135,16 @@ impl Conn {
         self.inner.is_connected()
     }
 
+    /// Adds an [`Interceptor`] which is called for every request sent through
+    /// this connection. See [`network::interceptor`] for details.
+    ///
+    /// Requests sent before the interceptor was added are not reported to it.
+    ///
+    /// [`network::interceptor`]: crate::network::interceptor
+    pub fn add_interceptor(&self, interceptor: Rc<dyn Interceptor>) {
+        self.inner.add_interceptor(interceptor)
+    }
+
     /// Close a connection.
     pub fn close(&self) {
         self.inner.close()
@@ -159,6 +193,48 @@ impl Conn {
         self.inner.request_async(protocol::Call(func, args))
     }
 
+    /// Call a remote stored procedure which reports intermediate results with
+    /// `box.session.push`.
+    ///
+    /// Doesn't yield. If enqueuing a request succeeded a [`Pushes`] iterator
+    /// is returned which yields the pushed values followed by the value
+    /// returned by the procedure.
+    ///
+    /// - `options` – the supported option is `timeout`, which limits the time
+    ///   spent waiting for each message
+    ///
+    /// # Example
+    /// ```no_run
+    /// use tarantool::net_box::{Conn, Options, pushes::CallMessage};
+    ///
+    /// # fn get_conn() -> Conn { todo!() }
+    /// let conn: Conn = get_conn();
+    /// let messages = conn.call_with_pushes("long_job", (), &Options::default()).unwrap();
+    /// for message in messages {
+    ///     match message.unwrap() {
+    ///         CallMessage::Push(data) => {
+    ///             let progress: f64 = rmp_serde::from_slice(&data).unwrap();
+    ///             println!("progress: {}", progress);
+    ///         }
+    ///         CallMessage::Response(result) => println!("done: {:?}", result),
+    ///     }
+    /// }
+    /// ```
+    pub fn call_with_pushes<A>(
+        &self,
+        function_name: &str,
+        args: A,
+        options: &Options,
+    ) -> Result<Pushes, Error>
+    where
+        A: ToTupleBuffer,
+    {
+        let pushes = Pushes::new(Rc::downgrade(&self.inner), options.timeout);
+        self.inner
+            .request_with_consumer(protocol::Call(function_name, args), pushes.downgrade())?;
+        Ok(pushes)
+    }
+
     /// Evaluates and executes the expression in Lua-string, which may be any statement or series of statements.
     ///
     /// An execute privilege is required; if the user does not have it, an administrator may grant it with
@@ -204,18 +280,35 @@ impl Conn {
     }
 
     /// Remote execute of sql query.
+    ///
+    /// Returns the resulting rows along with the column metadata and the
+    /// information about the changes made by the statement.
     pub fn execute(
         &self,
         sql: &str,
         bind_params: &impl ToTupleBuffer,
         options: &Options,
-    ) -> Result<Vec<Tuple>, Error> {
+    ) -> Result<SqlResponse, Error> {
         self.inner.request(
             |buf, sync| protocol::encode_execute(buf, sync, sql, bind_params),
-            |buf, _| protocol::decode_multiple_rows(buf, None),
+            |buf, _| SqlResponse::decode(buf, None),
             options,
         )
     }
+
+    /// Prepares the sql statement on the remote server for repeated
+    /// execution with [`PreparedStatement::execute`].
+    ///
+    /// The statement is deallocated on the server once the returned
+    /// [`PreparedStatement`] is dropped.
+    pub fn prepare(&self, sql: &str, options: &Options) -> Result<PreparedStatement, Error> {
+        let response = self.inner.request(
+            protocol::request_producer(protocol::Prepare(sql)),
+            |buf, _| SqlResponse::decode(buf, None),
+            options,
+        )?;
+        PreparedStatement::new(self.inner.clone(), response)
+    }
 }
 
 impl Drop for Conn {
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use crate::auth::AuthMethod;
 use crate::error::Error;
 use crate::net_box::Conn;
 
@@ -53,6 +54,12 @@ pub struct ConnOptions {
     /// Authentication password.
     pub password: String,
 
+    /// Authentication method. Connecting fails with
+    /// [`Error::UnsupportedAuthMethod`] if the server doesn't support it.
+    ///
+    /// Default: [`AuthMethod::ChapSha1`]
+    pub auth_method: AuthMethod,
+
     /// If `reconnect_after` is greater than zero, then a [Conn](struct.Conn.html) instance will try to reconnect if a
     /// connection is broken or if a connection attempt fails.
     ///
@@ -88,6 +95,18 @@ pub struct ConnOptions {
     ///
     /// Default: 65536
     pub recv_buffer_size: usize,
+
+    /// Time to wait for the responses to the requests in flight once the
+    /// server announces its shutdown (requires Tarantool 2.10 or later).
+    ///
+    /// New requests fail with [`Error::ServerShutdown`] as soon as the
+    /// shutdown is announced. The connection is closed once the responses
+    /// to all the requests in flight are received or the timeout expires,
+    /// in which case the remaining requests fail with the same error.
+    /// The connection is not re-established after that.
+    ///
+    /// Default: 3s
+    pub shutdown_timeout: Duration,
 }
 
 impl Default for ConnOptions {
@@ -95,12 +114,14 @@ impl Default for ConnOptions {
         ConnOptions {
             user: "".to_string(),
             password: "".to_string(),
+            auth_method: AuthMethod::default(),
             reconnect_after: Default::default(),
             connect_timeout: Default::default(),
             send_buffer_flush_interval: Duration::from_millis(10),
             send_buffer_limit: 64000,
             send_buffer_size: 65536,
             recv_buffer_size: 65536,
+            shutdown_timeout: Duration::from_secs(3),
         }
     }
 }
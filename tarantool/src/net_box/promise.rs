@@ -1,29 +1,59 @@
 use std::{
     cell::{Cell, UnsafeCell},
+    future::Future,
     io,
+    pin::Pin,
     rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
     time::{Duration, Instant},
 };
 
-use crate::{clock::INFINITY, error::Error, fiber::Cond, tuple::Decode, Result};
+use crate::{clock::INFINITY, error::Error, fiber::Cond, Result};
 
 use super::{inner::ConnInner, protocol::Consumer};
 
 type StdResult<T, E> = std::result::Result<T, E>;
 
 /// An asynchronous [`net_box::Conn`](crate::net_box::Conn) response.
+///
+/// Besides the blocking [`wait`](Self::wait) methods, the promise implements
+/// [`Future`], so it can be `.await`ed in async code executed by
+/// [`fiber::block_on`](crate::fiber::block_on).
+///
+/// # Example
+/// ```no_run
+/// use tarantool::fiber;
+/// use tarantool::fiber::r#async::timeout::IntoTimeout;
+/// use tarantool::net_box::Conn;
+/// use std::time::Duration;
+///
+/// # fn get_conn() -> Conn { todo!() }
+/// let conn: Conn = get_conn();
+/// let res: (i32,) = fiber::block_on(async {
+///     conn.call_async("foo", ())
+///         .unwrap()
+///         .timeout(Duration::from_secs(1))
+///         .await
+/// })
+/// .unwrap();
+/// ```
+#[must_use = "promises do nothing unless you wait for them or `.await` them"]
 pub struct Promise<T> {
     inner: Rc<InnerPromise<T>>,
 }
 
 impl<T> Promise<T> {
+    /// Creates a promise which decodes the `IPROTO_DATA` value of the
+    /// response with `decode`.
     #[inline]
-    pub(crate) fn new(conn: Weak<ConnInner>) -> Self {
+    pub(crate) fn new(conn: Weak<ConnInner>, decode: fn(&[u8]) -> Result<T>) -> Self {
         Self {
             inner: Rc::new(InnerPromise {
                 conn,
                 cond: UnsafeCell::default(),
+                waker: Cell::new(None),
                 data: Cell::new(None),
+                decode,
             }),
         }
     }
@@ -175,6 +205,21 @@ impl<T> Promise<T> {
     }
 }
 
+impl<T> Future for Promise<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(res) = self.inner.data.take() {
+            return Poll::Ready(res);
+        }
+        if let Err(e) = self.check_connection() {
+            return Poll::Ready(Err(e));
+        }
+        self.inner.waker.set(Some(cx.waker().clone()));
+        Poll::Pending
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum State {
     Kept,
@@ -266,19 +311,22 @@ impl<T> fmt::Debug for Promise<T> {
 pub struct InnerPromise<T> {
     conn: Weak<ConnInner>,
     cond: UnsafeCell<Rc<Cond>>,
+    /// Set if the promise is being awaited.
+    waker: Cell<Option<Waker>>,
     data: Cell<Option<Result<T>>>,
+    decode: fn(&[u8]) -> Result<T>,
 }
 
 impl<T> InnerPromise<T> {
     fn signal(&self) {
         unsafe { &*self.cond.get() }.signal();
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
     }
 }
 
-impl<T> Consumer for InnerPromise<T>
-where
-    T: for<'de> Decode<'de>,
-{
+impl<T> Consumer for InnerPromise<T> {
     fn handle_error(&self, error: Error) {
         self.data.set(Some(Err(error)));
         self.signal();
@@ -289,7 +337,7 @@ where
     }
 
     fn consume_data(&self, data: &[u8]) {
-        self.data.set(Some(T::decode(data)));
+        self.data.set(Some((self.decode)(data)));
         self.signal();
     }
 }
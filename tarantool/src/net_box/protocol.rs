@@ -6,11 +6,12 @@ use std::os::raw::c_char;
 use std::str::from_utf8;
 
 use num_derive::FromPrimitive;
-use sha1::{Digest, Sha1};
 
-use crate::error::Error;
-use crate::index::IteratorType;
+use crate::auth::{self, AuthMethod};
+use crate::error::{Error, ExtendedError};
+use crate::index::{IteratorType, TuplePosition};
 use crate::msgpack;
+use crate::network::protocol::{Feature, ProtocolFeatures, PROTOCOL_VERSION};
 use crate::tuple::{ToTupleBuffer, Tuple};
 
 const REQUEST_TYPE: u8 = 0x00;
@@ -23,6 +24,7 @@ const LIMIT: u8 = 0x12;
 const OFFSET: u8 = 0x13;
 const ITERATOR: u8 = 0x14;
 const INDEX_BASE: u8 = 0x15;
+const FETCH_POSITION: u8 = 0x1f;
 
 const KEY: u8 = 0x20;
 const TUPLE: u8 = 0x21;
@@ -30,12 +32,21 @@ const FUNCTION_NAME: u8 = 0x22;
 const USER_NAME: u8 = 0x23;
 const EXPR: u8 = 0x27;
 const OPS: u8 = 0x28;
+const AFTER_POSITION: u8 = 0x2e;
 
 const DATA: u8 = 0x30;
 const ERROR: u8 = 0x31;
+const POSITION: u8 = 0x35;
+const ERROR_STACK: u8 = 0x52;
 
 const SQL_TEXT: u8 = 0x40;
 const SQL_BIND: u8 = 0x41;
+const STMT_ID: u8 = 0x43;
+
+const VERSION: u8 = 0x54;
+const FEATURES: u8 = 0x55;
+const EVENT_KEY: u8 = 0x57;
+const EVENT_DATA: u8 = 0x58;
 
 #[derive(Debug, Clone, Copy, serde::Deserialize, FromPrimitive)]
 #[serde(try_from = "u8")]
@@ -50,16 +61,21 @@ enum IProtoKey {
     Offset = OFFSET,
     Iterator = ITERATOR,
     IndexBase = INDEX_BASE,
+    FetchPosition = FETCH_POSITION,
     Key = KEY,
     Tuple = TUPLE,
     FunctionName = FUNCTION_NAME,
     UserName = USER_NAME,
     Expr = EXPR,
     Ops = OPS,
+    AfterPosition = AFTER_POSITION,
     Data = DATA,
     Error = ERROR,
+    Position = POSITION,
+    ErrorStack = ERROR_STACK,
     SqlText = SQL_TEXT,
     SqlBind = SQL_BIND,
+    StmtId = STMT_ID,
 }
 
 impl TryFrom<u8> for IProtoKey {
@@ -83,7 +99,12 @@ pub(crate) enum IProtoType {
     Upsert = 9,
     Call = 10,
     Execute = 11,
+    Prepare = 13,
     Ping = 64,
+    Id = 73,
+    Watch = 74,
+    Event = 76,
+    Chunk = 128,
 }
 
 pub(crate) trait Request {
@@ -122,21 +143,40 @@ pub trait Consumer {
     ///
     /// **Must not yield**
     fn consume(&self, header: &Header, body: &[u8]) {
-        let _ = header;
+        if header.status_code == IProtoType::Chunk as u32 {
+            let consume_push = || {
+                let mut cursor = Cursor::new(body);
+                let map_len = rmp::decode::read_map_len(&mut cursor)?;
+                for _ in 0..map_len {
+                    let key = rmp::decode::read_pfix(&mut cursor)?;
+                    if key == DATA {
+                        // Pushed value is wrapped into an array of one element
+                        rmp::decode::read_array_len(&mut cursor)?;
+                        self.consume_push(value_slice(&mut cursor)?);
+                        break;
+                    }
+                    value_slice(&mut cursor)?;
+                }
+                Ok(())
+            };
+            if let Err(e) = consume_push() {
+                self.handle_error(e)
+            }
+            return;
+        }
         let consume_impl = || {
+            if header.status_code != 0 {
+                let error = decode_error(&mut Cursor::new(body), header)?;
+                self.handle_error(error.into());
+                return Ok(());
+            }
             let mut cursor = Cursor::new(body);
             let map_len = rmp::decode::read_map_len(&mut cursor)?;
             for _ in 0..map_len {
                 let key = rmp::decode::read_pfix(&mut cursor)?;
                 let value = value_slice(&mut cursor)?;
-                // dbg!((IProtoKey::try_from(key), rmp_serde::from_slice::<rmpv::Value>(value)));
                 match key {
                     DATA => self.consume_data(value),
-                    ERROR => {
-                        let message = rmp_serde::from_slice(value)?;
-                        self.handle_error(ResponseError { message }.into());
-                    }
-                    // TODO: IPROTO_ERROR (0x52)
                     other => self.consume_other(other, value),
                 }
             }
@@ -148,7 +188,17 @@ pub trait Consumer {
         }
     }
 
-    /// Handles key-value pairs other than `IPROTO_DATA` and `IPROTO_ERROR_24`.
+    /// Handles a slice that covers a msgpack value pushed by the server with
+    /// `box.session.push` before the response. The default implementation
+    /// ignores pushes.
+    ///
+    /// **Must not yield**
+    fn consume_push(&self, data: &[u8]) {
+        let _ = data;
+    }
+
+    /// Handles key-value pairs other than `IPROTO_DATA` of a successful
+    /// response.
     /// The default implementation ignores them, so if nothing needs to be done
     /// for those, don't implement this method.
     ///
@@ -158,7 +208,7 @@ pub trait Consumer {
     }
 
     /// If an error happens during the consumption of the response or if the
-    /// response contains the `IPROTO_ERROR_24` key this function is called with
+    /// server responded with an error this function is called with
     /// the corresponding error value.
     ///
     /// **Must not yield**
@@ -199,33 +249,9 @@ pub fn encode_auth(
     user: &str,
     password: &str,
     salt: &[u8],
+    method: AuthMethod,
     sync: u64,
 ) -> Result<(), Error> {
-    // prepare 'chap-sha1' scramble:
-    // salt = base64_decode(encoded_salt);
-    // step_1 = sha1(password);
-    // step_2 = sha1(step_1);
-    // step_3 = sha1(first_20_bytes_of_salt, step_2);
-    // scramble = xor(step_1, step_3);
-
-    let mut hasher = Sha1::new();
-    hasher.update(password.as_bytes());
-    let mut step_1_and_scramble = hasher.finalize();
-
-    let mut hasher = Sha1::new();
-    hasher.update(step_1_and_scramble);
-    let step_2 = hasher.finalize();
-
-    let mut hasher = Sha1::new();
-    hasher.update(&salt[0..20]);
-    hasher.update(step_2);
-    let step_3 = hasher.finalize();
-
-    step_1_and_scramble
-        .iter_mut()
-        .zip(step_3.iter())
-        .for_each(|(a, b)| *a ^= *b);
-
     encode_header(stream, sync, IProtoType::Auth)?;
     rmp::encode::write_map_len(stream, 2)?;
 
@@ -233,12 +259,9 @@ pub fn encode_auth(
     rmp::encode::write_pfix(stream, USER_NAME)?;
     rmp::encode::write_str(stream, user)?;
 
-    // encrypted password:
+    // auth method and data:
     rmp::encode::write_pfix(stream, TUPLE)?;
-    rmp::encode::write_array_len(stream, 2)?;
-    rmp::encode::write_str(stream, "chap-sha1")?;
-    rmp::encode::write_str_len(stream, 20)?;
-    stream.write_all(&step_1_and_scramble)?;
+    auth::encode_auth_data(stream, method, password, salt)?;
     Ok(())
 }
 
@@ -248,6 +271,27 @@ pub fn encode_ping(stream: &mut impl Write, sync: u64) -> Result<(), Error> {
     Ok(())
 }
 
+pub fn encode_id(stream: &mut impl Write, sync: u64) -> Result<(), Error> {
+    encode_header(stream, sync, IProtoType::Id)?;
+    rmp::encode::write_map_len(stream, 2)?;
+    rmp::encode::write_pfix(stream, VERSION)?;
+    rmp::encode::write_uint(stream, PROTOCOL_VERSION)?;
+    rmp::encode::write_pfix(stream, FEATURES)?;
+    rmp::encode::write_array_len(stream, Feature::ALL.len() as u32)?;
+    for feature in Feature::ALL {
+        rmp::encode::write_uint(stream, feature as u64)?;
+    }
+    Ok(())
+}
+
+pub fn encode_watch(stream: &mut impl Write, key: &str, sync: u64) -> Result<(), Error> {
+    encode_header(stream, sync, IProtoType::Watch)?;
+    rmp::encode::write_map_len(stream, 1)?;
+    rmp::encode::write_pfix(stream, EVENT_KEY)?;
+    rmp::encode::write_str(stream, key)?;
+    Ok(())
+}
+
 pub fn encode_execute(
     stream: &mut impl Write,
     sync: u64,
@@ -264,6 +308,61 @@ pub fn encode_execute(
     Ok(())
 }
 
+pub(crate) struct ExecutePrepared<'a, A: ?Sized>(pub u32, pub &'a A);
+
+impl<'a, A: ToTupleBuffer + ?Sized> Request for ExecutePrepared<'a, A> {
+    const TYPE: IProtoType = IProtoType::Execute;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        let Self(stmt_id, bind_params) = self;
+        rmp::encode::write_map_len(out, 2)?;
+        rmp::encode::write_pfix(out, STMT_ID)?;
+        rmp::encode::write_uint(out, *stmt_id as u64)?;
+        rmp::encode::write_pfix(out, SQL_BIND)?;
+        bind_params.write_tuple_data(out)?;
+        Ok(())
+    }
+}
+
+pub(crate) struct Prepare<'a>(pub &'a str);
+
+impl<'a> Request for Prepare<'a> {
+    const TYPE: IProtoType = IProtoType::Prepare;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        let Self(sql) = self;
+        rmp::encode::write_map_len(out, 1)?;
+        rmp::encode::write_pfix(out, SQL_TEXT)?;
+        rmp::encode::write_str(out, sql)?;
+        Ok(())
+    }
+}
+
+/// Unprepare request is a prepare request with the statement id instead of
+/// the sql text.
+pub(crate) struct Unprepare(pub u32);
+
+impl Request for Unprepare {
+    const TYPE: IProtoType = IProtoType::Prepare;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        let Self(stmt_id) = self;
+        rmp::encode::write_map_len(out, 1)?;
+        rmp::encode::write_pfix(out, STMT_ID)?;
+        rmp::encode::write_uint(out, *stmt_id as u64)?;
+        Ok(())
+    }
+}
+
 pub fn encode_call<T>(
     stream: &mut impl Write,
     sync: u64,
@@ -356,19 +455,84 @@ where
     K: ?Sized,
 {
     encode_header(stream, sync, IProtoType::Select)?;
-    rmp::encode::write_map_len(stream, 6)?;
+    Select {
+        space_id,
+        index_id,
+        limit,
+        offset,
+        iterator_type,
+        key,
+    }
+    .encode_body(stream)
+}
+
+pub(crate) struct Select<'a, K: ?Sized> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub limit: u32,
+    pub offset: u32,
+    pub iterator_type: IteratorType,
+    pub key: &'a K,
+}
+
+impl<'a, K: ToTupleBuffer + ?Sized> Request for Select<'a, K> {
+    const TYPE: IProtoType = IProtoType::Select;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        rmp::encode::write_map_len(out, 6)?;
+        rmp::encode::write_pfix(out, SPACE_ID)?;
+        rmp::encode::write_u32(out, self.space_id)?;
+        rmp::encode::write_pfix(out, INDEX_ID)?;
+        rmp::encode::write_u32(out, self.index_id)?;
+        rmp::encode::write_pfix(out, LIMIT)?;
+        rmp::encode::write_u32(out, self.limit)?;
+        rmp::encode::write_pfix(out, OFFSET)?;
+        rmp::encode::write_u32(out, self.offset)?;
+        rmp::encode::write_pfix(out, ITERATOR)?;
+        rmp::encode::write_u32(out, self.iterator_type as u32)?;
+        rmp::encode::write_pfix(out, KEY)?;
+        self.key.write_tuple_data(out)?;
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn encode_select_page<K>(
+    stream: &mut impl Write,
+    sync: u64,
+    space_id: u32,
+    index_id: u32,
+    limit: u32,
+    iterator_type: IteratorType,
+    key: &K,
+    after: Option<&TuplePosition>,
+) -> Result<(), Error>
+where
+    K: ToTupleBuffer,
+    K: ?Sized,
+{
+    encode_header(stream, sync, IProtoType::Select)?;
+    rmp::encode::write_map_len(stream, 6 + after.is_some() as u32)?;
     rmp::encode::write_pfix(stream, SPACE_ID)?;
     rmp::encode::write_u32(stream, space_id)?;
     rmp::encode::write_pfix(stream, INDEX_ID)?;
     rmp::encode::write_u32(stream, index_id)?;
     rmp::encode::write_pfix(stream, LIMIT)?;
     rmp::encode::write_u32(stream, limit)?;
-    rmp::encode::write_pfix(stream, OFFSET)?;
-    rmp::encode::write_u32(stream, offset)?;
     rmp::encode::write_pfix(stream, ITERATOR)?;
     rmp::encode::write_u32(stream, iterator_type as u32)?;
     rmp::encode::write_pfix(stream, KEY)?;
     key.write_tuple_data(stream)?;
+    rmp::encode::write_pfix(stream, FETCH_POSITION)?;
+    rmp::encode::write_bool(stream, true)?;
+    if let Some(after) = after {
+        rmp::encode::write_pfix(stream, AFTER_POSITION)?;
+        rmp::encode::write_str_len(stream, after.as_bytes().len() as u32)?;
+        stream.write_all(after.as_bytes())?;
+    }
     Ok(())
 }
 
@@ -383,12 +547,23 @@ where
     T: ?Sized,
 {
     encode_header(stream, sync, IProtoType::Insert)?;
-    rmp::encode::write_map_len(stream, 2)?;
-    rmp::encode::write_pfix(stream, SPACE_ID)?;
-    rmp::encode::write_u32(stream, space_id)?;
-    rmp::encode::write_pfix(stream, TUPLE)?;
-    value.write_tuple_data(stream)?;
-    Ok(())
+    Insert { space_id, value }.encode_body(stream)
+}
+
+pub(crate) struct Insert<'a, T: ?Sized> {
+    pub space_id: u32,
+    pub value: &'a T,
+}
+
+impl<'a, T: ToTupleBuffer + ?Sized> Request for Insert<'a, T> {
+    const TYPE: IProtoType = IProtoType::Insert;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        encode_space_tuple(out, self.space_id, self.value)
+    }
 }
 
 pub fn encode_replace<T>(
@@ -402,11 +577,35 @@ where
     T: ?Sized,
 {
     encode_header(stream, sync, IProtoType::Replace)?;
-    rmp::encode::write_map_len(stream, 2)?;
-    rmp::encode::write_pfix(stream, SPACE_ID)?;
-    rmp::encode::write_u32(stream, space_id)?;
-    rmp::encode::write_pfix(stream, TUPLE)?;
-    value.write_tuple_data(stream)?;
+    Replace { space_id, value }.encode_body(stream)
+}
+
+pub(crate) struct Replace<'a, T: ?Sized> {
+    pub space_id: u32,
+    pub value: &'a T,
+}
+
+impl<'a, T: ToTupleBuffer + ?Sized> Request for Replace<'a, T> {
+    const TYPE: IProtoType = IProtoType::Replace;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        encode_space_tuple(out, self.space_id, self.value)
+    }
+}
+
+/// Encodes the body of an insert or a replace request.
+fn encode_space_tuple<T>(out: &mut impl Write, space_id: u32, value: &T) -> Result<(), Error>
+where
+    T: ToTupleBuffer + ?Sized,
+{
+    rmp::encode::write_map_len(out, 2)?;
+    rmp::encode::write_pfix(out, SPACE_ID)?;
+    rmp::encode::write_u32(out, space_id)?;
+    rmp::encode::write_pfix(out, TUPLE)?;
+    value.write_tuple_data(out)?;
     Ok(())
 }
 
@@ -424,16 +623,44 @@ where
     Op: ?Sized,
 {
     encode_header(stream, sync, IProtoType::Update)?;
-    rmp::encode::write_map_len(stream, 4)?;
-    rmp::encode::write_pfix(stream, SPACE_ID)?;
-    rmp::encode::write_u32(stream, space_id)?;
-    rmp::encode::write_pfix(stream, INDEX_ID)?;
-    rmp::encode::write_u32(stream, index_id)?;
-    rmp::encode::write_pfix(stream, KEY)?;
-    key.write_tuple_data(stream)?;
-    rmp::encode::write_pfix(stream, TUPLE)?;
-    ops.write_tuple_data(stream)?;
-    Ok(())
+    Update {
+        space_id,
+        index_id,
+        key,
+        ops,
+    }
+    .encode_body(stream)
+}
+
+pub(crate) struct Update<'a, K: ?Sized, Op: ?Sized> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub key: &'a K,
+    pub ops: &'a Op,
+}
+
+impl<'a, K, Op> Request for Update<'a, K, Op>
+where
+    K: ToTupleBuffer + ?Sized,
+    Op: ToTupleBuffer + ?Sized,
+{
+    const TYPE: IProtoType = IProtoType::Update;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        rmp::encode::write_map_len(out, 4)?;
+        rmp::encode::write_pfix(out, SPACE_ID)?;
+        rmp::encode::write_u32(out, self.space_id)?;
+        rmp::encode::write_pfix(out, INDEX_ID)?;
+        rmp::encode::write_u32(out, self.index_id)?;
+        rmp::encode::write_pfix(out, KEY)?;
+        self.key.write_tuple_data(out)?;
+        rmp::encode::write_pfix(out, TUPLE)?;
+        self.ops.write_tuple_data(out)?;
+        Ok(())
+    }
 }
 
 pub fn encode_upsert<T, Op>(
@@ -450,16 +677,44 @@ where
     Op: ?Sized,
 {
     encode_header(stream, sync, IProtoType::Upsert)?;
-    rmp::encode::write_map_len(stream, 4)?;
-    rmp::encode::write_pfix(stream, SPACE_ID)?;
-    rmp::encode::write_u32(stream, space_id)?;
-    rmp::encode::write_pfix(stream, INDEX_BASE)?;
-    rmp::encode::write_u32(stream, index_id)?;
-    rmp::encode::write_pfix(stream, OPS)?;
-    ops.write_tuple_data(stream)?;
-    rmp::encode::write_pfix(stream, TUPLE)?;
-    value.write_tuple_data(stream)?;
-    Ok(())
+    Upsert {
+        space_id,
+        index_id,
+        value,
+        ops,
+    }
+    .encode_body(stream)
+}
+
+pub(crate) struct Upsert<'a, T: ?Sized, Op: ?Sized> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub value: &'a T,
+    pub ops: &'a Op,
+}
+
+impl<'a, T, Op> Request for Upsert<'a, T, Op>
+where
+    T: ToTupleBuffer + ?Sized,
+    Op: ToTupleBuffer + ?Sized,
+{
+    const TYPE: IProtoType = IProtoType::Upsert;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        rmp::encode::write_map_len(out, 4)?;
+        rmp::encode::write_pfix(out, SPACE_ID)?;
+        rmp::encode::write_u32(out, self.space_id)?;
+        rmp::encode::write_pfix(out, INDEX_BASE)?;
+        rmp::encode::write_u32(out, self.index_id)?;
+        rmp::encode::write_pfix(out, OPS)?;
+        self.ops.write_tuple_data(out)?;
+        rmp::encode::write_pfix(out, TUPLE)?;
+        self.value.write_tuple_data(out)?;
+        Ok(())
+    }
 }
 
 pub fn encode_delete<K>(
@@ -474,14 +729,36 @@ where
     K: ?Sized,
 {
     encode_header(stream, sync, IProtoType::Delete)?;
-    rmp::encode::write_map_len(stream, 3)?;
-    rmp::encode::write_pfix(stream, SPACE_ID)?;
-    rmp::encode::write_u32(stream, space_id)?;
-    rmp::encode::write_pfix(stream, INDEX_ID)?;
-    rmp::encode::write_u32(stream, index_id)?;
-    rmp::encode::write_pfix(stream, KEY)?;
-    key.write_tuple_data(stream)?;
-    Ok(())
+    Delete {
+        space_id,
+        index_id,
+        key,
+    }
+    .encode_body(stream)
+}
+
+pub(crate) struct Delete<'a, K: ?Sized> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub key: &'a K,
+}
+
+impl<'a, K: ToTupleBuffer + ?Sized> Request for Delete<'a, K> {
+    const TYPE: IProtoType = IProtoType::Delete;
+
+    fn encode_body<W>(&self, out: &mut W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        rmp::encode::write_map_len(out, 3)?;
+        rmp::encode::write_pfix(out, SPACE_ID)?;
+        rmp::encode::write_u32(out, self.space_id)?;
+        rmp::encode::write_pfix(out, INDEX_ID)?;
+        rmp::encode::write_u32(out, self.index_id)?;
+        rmp::encode::write_pfix(out, KEY)?;
+        self.key.write_tuple_data(out)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -512,6 +789,15 @@ pub fn decode_header(stream: &mut (impl Read + Seek)) -> Result<Header, Error> {
         }
     }
 
+    if status_code == Some(IProtoType::Event as u32) {
+        // Events are not responses to requests and may lack these fields
+        return Ok(Header {
+            sync: sync.unwrap_or(0),
+            status_code: IProtoType::Event as u32,
+            schema_version: schema_version.unwrap_or(0),
+        });
+    }
+
     if sync.is_none() || status_code.is_none() || schema_version.is_none() {
         return Err(io::Error::from(io::ErrorKind::InvalidData).into());
     }
@@ -523,21 +809,89 @@ pub fn decode_header(stream: &mut (impl Read + Seek)) -> Result<Header, Error> {
     })
 }
 
-pub fn decode_error(stream: &mut impl Read) -> Result<ResponseError, Error> {
+pub fn decode_id(stream: &mut (impl Read + Seek)) -> Result<ProtocolFeatures, Error> {
+    let mut version = 0;
+    let mut features = Vec::new();
+
+    let map_len = rmp::decode::read_map_len(stream)?;
+    for _ in 0..map_len {
+        match rmp::decode::read_pfix(stream)? {
+            VERSION => version = rmp::decode::read_int(stream)?,
+            FEATURES => {
+                let len = rmp::decode::read_array_len(stream)?;
+                for _ in 0..len {
+                    features.push(rmp::decode::read_int(stream)?);
+                }
+            }
+            _ => msgpack::skip_value(stream)?,
+        }
+    }
+
+    Ok(ProtocolFeatures::new(version, features))
+}
+
+/// Decodes the body of an event into the key and the msgpack encoded value.
+pub fn decode_event(stream: &mut (impl Read + Seek)) -> Result<(String, Vec<u8>), Error> {
+    let mut key: Option<String> = None;
+    // Absent data means `nil`
+    let mut data = vec![0xc0];
+
+    let map_len = rmp::decode::read_map_len(stream)?;
+    for _ in 0..map_len {
+        match rmp::decode::read_pfix(stream)? {
+            EVENT_KEY => {
+                let str_len = rmp::decode::read_str_len(stream)? as usize;
+                let mut str_buf = vec![0u8; str_len];
+                stream.read_exact(&mut str_buf)?;
+                key = Some(from_utf8(&str_buf)?.to_string());
+            }
+            EVENT_DATA => {
+                let start = stream.stream_position()?;
+                msgpack::skip_value(stream)?;
+                let end = stream.stream_position()?;
+                data = vec![0; (end - start) as usize];
+                stream.seek(io::SeekFrom::Start(start))?;
+                stream.read_exact(&mut data)?;
+            }
+            _ => msgpack::skip_value(stream)?,
+        }
+    }
+
+    let key = key.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+    Ok((key, data))
+}
+
+pub fn decode_error(
+    stream: &mut (impl Read + Seek),
+    header: &Header,
+) -> Result<ResponseError, Error> {
     let mut message: Option<String> = None;
+    let mut extended = None;
 
     let map_len = rmp::decode::read_map_len(stream)?;
     for _ in 0..map_len {
-        if rmp::decode::read_pfix(stream)? == ERROR {
-            let str_len = rmp::decode::read_str_len(stream)? as usize;
-            let mut str_buf = vec![0u8; str_len];
-            stream.read_exact(&mut str_buf)?;
-            message = Some(from_utf8(&str_buf)?.to_string());
+        match rmp::decode::read_pfix(stream)? {
+            ERROR => {
+                let str_len = rmp::decode::read_str_len(stream)? as usize;
+                let mut str_buf = vec![0u8; str_len];
+                stream.read_exact(&mut str_buf)?;
+                message = Some(from_utf8(&str_buf)?.to_string());
+            }
+            ERROR_STACK => extended = ExtendedError::decode(stream)?.map(Box::new),
+            _ => msgpack::skip_value(stream)?,
         }
     }
 
+    let message = match (message, &extended) {
+        (Some(message), _) => message,
+        (None, Some(extended)) => extended.message.clone(),
+        (None, None) => return Err(io::Error::from(io::ErrorKind::InvalidData).into()),
+    };
     Ok(ResponseError {
-        message: message.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?,
+        // See `IPROTO_TYPE_ERROR` in tarantool docs
+        code: header.status_code & 0x7fff,
+        message,
+        extended,
     })
 }
 
@@ -593,6 +947,41 @@ pub fn decode_multiple_rows(
     Ok(vec![])
 }
 
+pub fn decode_select_page(
+    buffer: &mut Cursor<Vec<u8>>,
+) -> Result<(Vec<Tuple>, Option<TuplePosition>), Error> {
+    let mut tuples = Vec::new();
+    let mut position = None;
+    let payload_len = rmp::decode::read_map_len(buffer)?;
+    for _ in 0..payload_len {
+        match rmp::decode::read_pfix(buffer)? {
+            DATA => {
+                let items_count = rmp::decode::read_array_len(buffer)? as usize;
+                tuples.reserve(items_count);
+                for _ in 0..items_count {
+                    tuples.push(decode_tuple(buffer)?);
+                }
+            }
+            POSITION => {
+                let len = rmp::decode::read_str_len(buffer)? as usize;
+                let mut data = vec![0; len];
+                buffer.read_exact(&mut data)?;
+                position = Some(TuplePosition::from_bytes(data));
+            }
+            _ => msgpack::skip_value(buffer)?,
+        }
+    }
+    if !tuples.is_empty() && position.is_none() {
+        // Servers prior to 2.11 ignore the `FETCH_POSITION` key
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "keyset pagination is not supported by the server",
+        )
+        .into());
+    }
+    Ok((tuples, position))
+}
+
 pub fn decode_single_row(buffer: &mut Cursor<Vec<u8>>, _: &Header) -> Result<Option<Tuple>, Error> {
     let payload_len = rmp::decode::read_map_len(buffer)?;
     for _ in 0..payload_len {
@@ -614,6 +1003,25 @@ pub fn decode_single_row(buffer: &mut Cursor<Vec<u8>>, _: &Header) -> Result<Opt
     Ok(None)
 }
 
+/// Decodes the `IPROTO_DATA` value of a response to a select request.
+pub(crate) fn decode_rows(data: &[u8]) -> Result<Vec<Tuple>, Error> {
+    let mut cursor = Cursor::new(data);
+    let count = rmp::decode::read_array_len(&mut cursor)?;
+    (0..count)
+        .map(|_| Tuple::try_from_slice(value_slice(&mut cursor)?))
+        .collect()
+}
+
+/// Decodes the `IPROTO_DATA` value of a response to a request which returns
+/// at most one tuple, e.g. insert or delete.
+pub(crate) fn decode_first_row(data: &[u8]) -> Result<Option<Tuple>, Error> {
+    let mut cursor = Cursor::new(data);
+    if rmp::decode::read_array_len(&mut cursor)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Tuple::try_from_slice(value_slice(&mut cursor)?)?))
+}
+
 pub fn decode_tuple(buffer: &mut Cursor<Vec<u8>>) -> Result<Tuple, Error> {
     let payload_offset = buffer.position();
     msgpack::skip_value(buffer)?;
@@ -633,9 +1041,36 @@ pub fn value_slice(cursor: &mut Cursor<impl AsRef<[u8]>>) -> crate::Result<&[u8]
     Ok(&cursor.get_ref().as_ref()[start..(cursor.position() as usize)])
 }
 
+/// Error returned from the Tarantool server.
 #[derive(Debug)]
 pub struct ResponseError {
+    pub(crate) code: u32,
     pub(crate) message: String,
+    pub(crate) extended: Option<Box<ExtendedError>>,
+}
+
+impl ResponseError {
+    /// Returns the error code. Usually it is one of [`TarantoolErrorCode`].
+    ///
+    /// [`TarantoolErrorCode`]: crate::error::TarantoolErrorCode
+    #[inline(always)]
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Returns the error message.
+    #[inline(always)]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the error with all the details and the stack of errors it
+    /// was caused by. Is `None` if the server is older than 2.4.1 and
+    /// doesn't send this information.
+    #[inline(always)]
+    pub fn extended(&self) -> Option<&ExtendedError> {
+        self.extended.as_deref()
+    }
 }
 
 impl Display for ResponseError {
@@ -0,0 +1,129 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::fiber::Cond;
+use crate::tuple::{Decode, RawByteBuf, Tuple};
+
+use super::inner::ConnInner;
+use super::protocol::Consumer;
+
+/// Message received in response to [`Conn::call_with_pushes`].
+///
+/// [`Conn::call_with_pushes`]: super::Conn::call_with_pushes
+#[derive(Debug)]
+pub enum CallMessage {
+    /// Msgpack encoded value pushed by the procedure with `box.session.push`.
+    Push(RawByteBuf),
+    /// Value returned by the procedure. Is always the last message.
+    Response(Option<Tuple>),
+}
+
+/// Iterator over the messages received in response to a call made with
+/// [`Conn::call_with_pushes`].
+///
+/// Yields the values pushed by the procedure in the order they were pushed
+/// followed by the value returned by the procedure. Each call to `next`
+/// yields until the next message is received, the timeout expires or the
+/// connection is closed, in which case an error is returned as the last
+/// message.
+///
+/// [`Conn::call_with_pushes`]: super::Conn::call_with_pushes
+pub struct Pushes {
+    inner: Rc<PushesInner>,
+    timeout: Option<Duration>,
+    finished: bool,
+}
+
+impl Pushes {
+    #[inline]
+    pub(crate) fn new(conn: Weak<ConnInner>, timeout: Option<Duration>) -> Self {
+        Self {
+            inner: Rc::new(PushesInner {
+                conn,
+                cond: Cond::new(),
+                pushes: RefCell::new(VecDeque::new()),
+                response: Cell::new(None),
+            }),
+            timeout,
+            finished: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn downgrade(&self) -> Weak<PushesInner> {
+        Rc::downgrade(&self.inner)
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner
+            .conn
+            .upgrade()
+            .map(|c| c.is_connected())
+            .unwrap_or(false)
+    }
+
+    fn next_message(&self) -> Result<CallMessage, Error> {
+        loop {
+            if let Some(data) = self.inner.pushes.borrow_mut().pop_front() {
+                return Ok(CallMessage::Push(data));
+            }
+            if let Some(response) = self.inner.response.take() {
+                return response.map(CallMessage::Response);
+            }
+            if !self.is_connected() {
+                return Err(io::Error::from(io::ErrorKind::NotConnected).into());
+            }
+            let is_signaled = match self.timeout {
+                None => self.inner.cond.wait(),
+                Some(timeout) => self.inner.cond.wait_timeout(timeout),
+            };
+            if !is_signaled {
+                return Err(io::Error::from(io::ErrorKind::TimedOut).into());
+            }
+        }
+    }
+}
+
+impl Iterator for Pushes {
+    type Item = Result<CallMessage, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let message = self.next_message();
+        if !matches!(message, Ok(CallMessage::Push(_))) {
+            self.finished = true;
+        }
+        Some(message)
+    }
+}
+
+pub struct PushesInner {
+    conn: Weak<ConnInner>,
+    cond: Cond,
+    pushes: RefCell<VecDeque<RawByteBuf>>,
+    response: Cell<Option<Result<Option<Tuple>, Error>>>,
+}
+
+impl Consumer for PushesInner {
+    fn handle_error(&self, error: Error) {
+        self.response.set(Some(Err(error)));
+        self.cond.signal();
+    }
+
+    fn consume_push(&self, data: &[u8]) {
+        self.pushes.borrow_mut().push_back(RawByteBuf(data.into()));
+        self.cond.signal();
+    }
+
+    fn consume_data(&self, data: &[u8]) {
+        self.response
+            .set(Some(<Tuple as Decode>::decode(data).map(Some)));
+        self.cond.signal();
+    }
+}
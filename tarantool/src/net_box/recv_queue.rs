@@ -9,9 +9,12 @@ use rmp::decode;
 
 use crate::error::Error;
 use crate::fiber::{Cond, Latch};
+use crate::network::interceptor::Interceptors;
 
 use super::options::Options;
-use super::protocol::{decode_error, decode_header, Consumer, Header, Response, Sync};
+use super::protocol::{
+    decode_error, decode_event, decode_header, Consumer, Header, IProtoType, Response, Sync,
+};
 
 type Consumers = HashMap<Sync, Weak<dyn Consumer>>;
 
@@ -26,10 +29,19 @@ pub struct RecvQueue {
     read_completed_cond: Cond,
     header_recv_result: RefCell<Option<Result<Header, Error>>>,
     notification_lock: Latch,
+    interceptors: Rc<Interceptors>,
+    /// Latest value of `box.shutdown` received since the last call to
+    /// [`Self::take_shutdown_event`].
+    shutdown_event: Cell<Option<bool>>,
+    /// Whether the queue is closed because the server shut down.
+    is_server_shutdown: Cell<bool>,
 }
 
+/// Key broadcast by the server once it starts shutting down.
+pub const SHUTDOWN_KEY: &str = "box.shutdown";
+
 impl RecvQueue {
-    pub fn new(buffer_size: usize) -> Self {
+    pub fn new(buffer_size: usize, interceptors: Rc<Interceptors>) -> Self {
         let buffer = vec![0; buffer_size];
         RecvQueue {
             is_active: Cell::new(true),
@@ -42,6 +54,9 @@ impl RecvQueue {
             read_completed_cond: Cond::new(),
             header_recv_result: RefCell::new(None),
             notification_lock: Latch::new(),
+            interceptors,
+            shutdown_event: Cell::new(None),
+            is_server_shutdown: Cell::new(false),
         }
     }
 
@@ -55,7 +70,7 @@ impl RecvQueue {
         F: FnOnce(&mut Cursor<Vec<u8>>, &Header) -> Result<R, Error>,
     {
         if !self.is_active.get() {
-            return Err(io::Error::from(io::ErrorKind::ConnectionAborted).into());
+            return Err(self.closed_error());
         }
 
         let cond_ref = PoolRef::new(&self.cond_pool, Cond::new());
@@ -70,12 +85,18 @@ impl RecvQueue {
 
         if is_signaled {
             let result = {
-                let header = self.header_recv_result.replace(None).unwrap();
+                // Every waiter is signaled at once when the queue is closed
+                let header = self
+                    .header_recv_result
+                    .replace(None)
+                    .unwrap_or_else(|| Err(self.closed_error()));
 
                 match header {
                     Ok(header) => {
                         if header.status_code != 0 {
-                            return Err(decode_error(self.buffer.borrow_mut().by_ref())?.into());
+                            return Err(
+                                decode_error(self.buffer.borrow_mut().by_ref(), &header)?.into()
+                            );
                         }
 
                         payload_consumer(self.buffer.borrow_mut().by_ref(), &header)
@@ -102,6 +123,14 @@ impl RecvQueue {
             .and_then(|c| c.upgrade())
     }
 
+    /// Returns the consumer of the response to the request with `sync`
+    /// without unregistering it.
+    pub fn peek_consumer(&self, sync: Sync) -> Option<Rc<dyn Consumer>> {
+        unsafe { &*self.async_consumers.get() }
+            .get(&sync)
+            .and_then(|c| c.upgrade())
+    }
+
     pub fn iter_consumers(&self) -> HashMapIter<Sync, Weak<dyn Consumer>> {
         unsafe { &*self.async_consumers.get() }.iter()
     }
@@ -154,15 +183,46 @@ impl RecvQueue {
                 };
 
                 let sync = header.sync;
+                if header.status_code == IProtoType::Event as u32 {
+                    let mut buffer = self.buffer.borrow_mut();
+                    let (key, data) = decode_event(buffer.by_ref())?;
+                    if key == SHUTDOWN_KEY {
+                        // `true` once the server starts shutting down
+                        self.shutdown_event.set(Some(data == [0xc3]));
+                    }
+                    continue;
+                }
+                if header.status_code == IProtoType::Chunk as u32 {
+                    // Out-of-band push, the response is yet to come. Only async
+                    // consumers can handle pushes.
+                    if let Some(consumer) = self.peek_consumer(sync) {
+                        let buffer = self.buffer.borrow();
+                        let body_start = buffer.position() as usize;
+                        consumer.consume(&header, &buffer.get_ref()[body_start..end]);
+                    }
+                    continue;
+                }
+                self.interceptors.response_received(sync, end - start);
                 let cond_ref = self.cond_map.borrow_mut().remove(&sync);
                 if let Some(cond_ref) = cond_ref {
                     self.header_recv_result.replace(Some(Ok(header)));
                     cond_ref.signal();
                     self.read_completed_cond.wait();
                 } else if let Some(consumer) = self.get_consumer(sync) {
-                    let buffer = self.buffer.borrow();
-                    let body_start = buffer.position() as usize;
-                    consumer.consume(&header, &buffer.get_ref()[body_start..end]);
+                    let error = {
+                        let buffer = self.buffer.borrow();
+                        let body_start = buffer.position() as usize;
+                        let body = &buffer.get_ref()[body_start..end];
+                        consumer.consume(&header, body);
+                        (header.status_code != 0)
+                            .then(|| decode_error(&mut Cursor::new(body), &header).ok())
+                            .flatten()
+                            .map(Error::from)
+                    };
+                    let error = error.as_ref().map(|e| e as _);
+                    self.interceptors.after_response(sync, error);
+                } else {
+                    self.interceptors.discard(sync);
                 }
             }
         }
@@ -182,18 +242,58 @@ impl RecvQueue {
         Ok(true)
     }
 
+    /// Returns the value of `box.shutdown` if it was received since the last
+    /// call.
+    pub fn take_shutdown_event(&self) -> Option<bool> {
+        self.shutdown_event.take()
+    }
+
+    /// Returns the number of requests awaiting response.
+    pub fn in_flight(&self) -> usize {
+        let consumers = self
+            .iter_consumers()
+            .filter(|(_, c)| c.strong_count() > 0)
+            .count();
+        self.cond_map.borrow().len() + consumers
+    }
+
+    /// Returns `true` if the queue was closed with [`Self::shutdown`].
+    pub fn is_server_shutdown(&self) -> bool {
+        self.is_server_shutdown.get()
+    }
+
+    /// Same as [`Self::close`], but the requests awaiting response fail
+    /// with [`Error::ServerShutdown`].
+    pub fn shutdown(&self) {
+        self.is_server_shutdown.set(true);
+        self.close();
+    }
+
+    fn closed_error(&self) -> Error {
+        if self.is_server_shutdown.get() {
+            Error::ServerShutdown
+        } else {
+            io::Error::from(io::ErrorKind::ConnectionAborted).into()
+        }
+    }
+
     pub fn close(&self) {
         let _lock = self.notification_lock.lock();
-        self.is_active.set(false);
+        if !self.is_active.replace(false) {
+            return;
+        }
         for (_, cond_ref) in self.cond_map.borrow_mut().drain() {
             self.header_recv_result
-                .replace(Some(Err(
-                    io::Error::from(io::ErrorKind::ConnectionAborted).into()
-                )));
+                .replace(Some(Err(self.closed_error())));
             cond_ref.signal();
         }
         for consumer in self.iter_consumers().filter_map(|(_, c)| c.upgrade()) {
-            consumer.handle_disconnect();
+            if self.is_server_shutdown.get() {
+                consumer.handle_error(Error::ServerShutdown);
+            } else {
+                consumer.handle_disconnect();
+            }
         }
+        self.interceptors.fail_all(&self.closed_error());
     }
 }
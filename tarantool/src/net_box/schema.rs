@@ -2,7 +2,6 @@ use once_cell::unsync::Lazy;
 
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::net::SocketAddr;
 use std::rc::Rc;
 
 use crate::error::Error;
@@ -11,7 +10,7 @@ use crate::index::IteratorType;
 use crate::space::{SystemSpace, SYSTEM_ID_MAX};
 use crate::tuple::Tuple;
 
-use super::inner::ConnInner;
+use super::inner::{Address, ConnInner};
 use super::options::Options;
 use super::protocol::{decode_multiple_rows, encode_select};
 
@@ -24,7 +23,7 @@ pub struct ConnSchema {
 }
 
 impl ConnSchema {
-    pub fn acquire(addrs: &[SocketAddr]) -> Rc<ConnSchema> {
+    pub fn acquire(addrs: &[Address]) -> Rc<ConnSchema> {
         let addr = SCHEMA_CACHE.with(|cache| {
             let cache = cache.cache.borrow();
             addrs.iter().find_map(|addr| cache.get(addr).cloned())
@@ -44,7 +43,7 @@ impl ConnSchema {
         SCHEMA_CACHE.with(|cache| {
             let mut cache = cache.cache.borrow_mut();
             for addr in addrs {
-                cache.insert(*addr, schema.clone());
+                cache.insert(addr.clone(), schema.clone());
             }
         });
 
@@ -99,6 +98,14 @@ impl ConnSchema {
         self.space_ids.borrow().get(name).copied()
     }
 
+    pub fn lookup_space_name(&self, space_id: u32) -> Option<String> {
+        self.space_ids
+            .borrow()
+            .iter()
+            .find(|(_, &id)| id == space_id)
+            .map(|(name, _)| name.clone())
+    }
+
     pub fn lookup_index(&self, name: &str, space_id: u32) -> Option<u32> {
         self.index_ids
             .borrow()
@@ -157,7 +164,7 @@ impl ConnSchema {
 }
 
 struct ConnSchemaCache {
-    cache: RefCell<HashMap<SocketAddr, Rc<ConnSchema>>>,
+    cache: RefCell<HashMap<Address, Rc<ConnSchema>>>,
 }
 
 unsafe impl Sync for ConnSchemaCache {}
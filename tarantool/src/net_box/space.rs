@@ -7,6 +7,7 @@ use crate::tuple::{Encode, ToTupleBuffer, Tuple};
 use super::index::{RemoteIndex, RemoteIndexIterator};
 use super::inner::ConnInner;
 use super::options::Options;
+use super::promise::Promise;
 use super::protocol;
 
 /// Remote space
@@ -124,4 +125,127 @@ impl RemoteSpace {
     {
         self.primary_key().delete(key, options)
     }
+
+    /// Same as [`get`](Self::get), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use tarantool::fiber;
+    /// use tarantool::net_box::Conn;
+    ///
+    /// # fn get_conn() -> Conn { todo!() }
+    /// let conn: Conn = get_conn();
+    /// let space = conn.space("users").unwrap().unwrap();
+    /// let (alice, bob) = fiber::block_on(async {
+    ///     let alice = space.get_async(&("alice",)).unwrap();
+    ///     let bob = space.get_async(&("bob",)).unwrap();
+    ///     futures::join!(alice, bob)
+    /// });
+    /// ```
+    #[inline(always)]
+    pub fn get_async<K>(&self, key: &K) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.primary_key().get_async(key)
+    }
+
+    /// Same as [`select`](Self::select), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received. `options.timeout` is ignored, use
+    /// [`IntoTimeout`] on the promise instead.
+    ///
+    /// [`IntoTimeout`]: crate::fiber::r#async::timeout::IntoTimeout
+    #[inline(always)]
+    pub fn select_async<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        options: &Options,
+    ) -> Result<Promise<Vec<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.primary_key().select_async(iterator_type, key, options)
+    }
+
+    /// Same as [`insert`](Self::insert), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    pub fn insert_async<T>(&self, value: &T) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        T: ToTupleBuffer,
+    {
+        self.conn_inner.request_async_with_decoder(
+            protocol::Insert {
+                space_id: self.space_id,
+                value,
+            },
+            protocol::decode_first_row,
+        )
+    }
+
+    /// Same as [`replace`](Self::replace), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    pub fn replace_async<T>(&self, value: &T) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        T: ToTupleBuffer,
+    {
+        self.conn_inner.request_async_with_decoder(
+            protocol::Replace {
+                space_id: self.space_id,
+                value,
+            },
+            protocol::decode_first_row,
+        )
+    }
+
+    /// Same as [`update`](Self::update), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    #[inline(always)]
+    pub fn update_async<K, Op>(&self, key: &K, ops: &[Op]) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.primary_key().update_async(key, ops)
+    }
+
+    /// Same as [`upsert`](Self::upsert), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    #[inline(always)]
+    pub fn upsert_async<T, Op>(
+        &self,
+        value: &T,
+        ops: &[Op],
+    ) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        T: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.primary_key().upsert_async(value, ops)
+    }
+
+    /// Same as [`delete`](Self::delete), but doesn't yield.
+    ///
+    /// If enqueuing a request succeeded a [`Promise`] is returned which will be
+    /// kept once a response is received.
+    #[inline(always)]
+    pub fn delete_async<K>(&self, key: &K) -> Result<Promise<Option<Tuple>>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.primary_key().delete_async(key)
+    }
 }
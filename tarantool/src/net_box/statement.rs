@@ -0,0 +1,97 @@
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+
+use crate::error::Error;
+use crate::sql::SqlResponse;
+use crate::tuple::{ToTupleBuffer, Tuple};
+
+use super::inner::ConnInner;
+use super::options::Options;
+use super::protocol;
+
+/// Sql statement prepared on the remote server with [`Conn::prepare`].
+/// See [`sql`](crate::sql#prepared-statements) for the common semantics.
+///
+/// The statement can't be executed after the connection is re-established.
+/// The unprepare request is sent without waiting for the response when the
+/// last handle is dropped.
+///
+/// [`Conn::prepare`]: super::Conn::prepare
+pub struct PreparedStatement {
+    conn: Rc<ConnInner>,
+    id: u32,
+    /// Id of the connection the statement was prepared over.
+    connection_id: u64,
+    response: SqlResponse,
+}
+
+impl PreparedStatement {
+    /// Fails if the `response` to the prepare request has no statement id.
+    pub(crate) fn new(conn: Rc<ConnInner>, response: SqlResponse) -> Result<Self, Error> {
+        let id = response.stmt_id().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "statement id is missing in the prepare response",
+            )
+        })?;
+        let connection_id = conn.acquire_statement(id);
+        Ok(Self {
+            conn,
+            id,
+            connection_id,
+            response,
+        })
+    }
+
+    /// Returns the id of the statement on the server.
+    #[inline(always)]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Response to the prepare request containing the metadata of the result
+    /// columns & bind parameters of the statement.
+    #[inline(always)]
+    pub fn response(&self) -> &SqlResponse {
+        &self.response
+    }
+
+    /// Executes the statement with the given bind parameters.
+    ///
+    /// See also [`Conn::execute`](super::Conn::execute).
+    pub fn execute(
+        &self,
+        bind_params: &impl ToTupleBuffer,
+        options: &Options,
+    ) -> Result<SqlResponse, Error> {
+        self.conn.request(
+            protocol::request_producer(protocol::ExecutePrepared(self.id, bind_params)),
+            |buf, _| SqlResponse::decode(buf, None),
+            options,
+        )
+    }
+}
+
+impl fmt::Debug for PreparedStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedStatement")
+            .field("id", &self.id)
+            .field("response", &self.response)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PreparedStatement {
+    fn drop(&mut self) {
+        // The statement is deallocated by the server anyway once the session
+        // is closed
+        let is_last = self.conn.release_statement(self.id, self.connection_id);
+        if is_last && self.conn.is_connected() {
+            // The response is discarded as the promise is dropped right away
+            let _ = self
+                .conn
+                .request_async::<_, Tuple>(protocol::Unprepare(self.id));
+        }
+    }
+}
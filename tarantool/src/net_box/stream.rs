@@ -2,6 +2,7 @@ use std::cell::Cell;
 use std::io::{self, Read, Write};
 use std::os::unix::io::{IntoRawFd, RawFd};
 use std::rc::Rc;
+use std::time::Duration;
 
 use crate::coio::{read, write, CoIOStream};
 use crate::error::Error;
@@ -38,6 +39,7 @@ impl ConnStream {
         self.reader_guard.is_acquired.set(true);
         ConnStreamReader {
             fd: self.fd,
+            timeout: None,
             reader_guard: self.reader_guard.clone(),
         }
     }
@@ -75,12 +77,21 @@ impl Drop for ConnStream {
 
 pub struct ConnStreamReader {
     fd: RawFd,
+    timeout: Option<Duration>,
     reader_guard: Rc<ConnStreamGuard>,
 }
 
+impl ConnStreamReader {
+    /// Sets the timeout after which reads fail with
+    /// [`io::ErrorKind::TimedOut`]. `None` means no timeout.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+}
+
 impl Read for ConnStreamReader {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        read(self.fd, buf, None)
+        read(self.fd, buf, self.timeout)
     }
 }
 
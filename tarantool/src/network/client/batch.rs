@@ -0,0 +1,301 @@
+//! Pipelining of requests of the [`Client`](super::Client).
+//!
+//! See [`Batch`].
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+
+use futures::future;
+
+use super::{Client, Error};
+use crate::fiber::r#async::oneshot;
+use crate::network::protocol::api::Request;
+use crate::network::protocol::SyncIndex;
+
+/// Maximum number of requests of a [`Batch`] awaiting response at the same
+/// time if not specified with [`Batch::max_in_flight`].
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 128;
+
+/// A batch of requests pipelined through the connection of a [`Client`].
+/// Created with [`Client::batch`].
+///
+/// Requests are added with [`Batch::push`] and are only sent once the batch
+/// is executed. All the requests that fit into the in-flight window are
+/// written to the connection at once, after that a new request is sent every
+/// time a response is received, so that at most
+/// [`max_in_flight`](Batch::max_in_flight) requests are awaiting response at
+/// any moment. This way a large number of requests can be executed from
+/// a single fiber without flooding the connection.
+///
+/// Requests may be of different types, their responses are converted into
+/// `T` with [`Into`]. Results are either returned in the order the requests
+/// were added with [`Batch::execute`] or as they arrive with
+/// [`Batch::execute_unordered`].
+///
+/// If the client has a [`reconnect::Policy`](super::reconnect::Policy)
+/// which retries in-flight requests, the requests which were in flight when
+/// the connection was lost are sent again. This is never the case for a batch
+/// created with [`Stream::batch`](super::Stream): its requests fail with
+/// [`Error::ConnectionLost`] as the transaction of the stream is aborted.
+///
+/// # Example
+/// ```no_run
+/// # async {
+/// use tarantool::network::client::Client;
+/// use tarantool::network::protocol::api::{Call, Insert};
+/// use tarantool::tuple::Tuple;
+///
+/// let client = Client::connect("localhost", 3301).await.unwrap();
+/// let records: Vec<(u32, String)> = (0..10_000).map(|i| (i, i.to_string())).collect();
+///
+/// let mut batch = client.batch::<Option<Tuple>>().max_in_flight(256);
+/// for value in &records {
+///     batch.push(Insert { space_id: 512, value });
+/// }
+/// batch.push(Call { fn_name: "on_loaded", args: &() });
+/// for result in batch.execute().await {
+///     result.unwrap();
+/// }
+/// # };
+/// ```
+pub struct Batch<'a, T> {
+    client: Client,
+    requests: Vec<Box<dyn BatchRequest<T> + 'a>>,
+    max_in_flight: usize,
+}
+
+impl<'a, T> Batch<'a, T> {
+    #[inline]
+    pub(super) fn new(client: Client) -> Self {
+        Self {
+            client,
+            requests: Vec::new(),
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+        }
+    }
+
+    /// Sets the maximum number of requests awaiting response at the same
+    /// time. Values less than 1 are treated as 1.
+    ///
+    /// Default: [`DEFAULT_MAX_IN_FLIGHT`].
+    #[inline]
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Adds the `request` to the batch. Returns the index of the request,
+    /// which identifies its result in [`BatchResults::next`].
+    pub fn push<R>(&mut self, request: R) -> usize
+    where
+        R: Request + 'a,
+        R::Response: Into<T>,
+    {
+        self.requests.push(Box::new(request));
+        self.requests.len() - 1
+    }
+
+    /// Returns the number of requests in the batch.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns `true` if no requests were added to the batch.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Sends the requests and waits for all the responses.
+    /// This function yields.
+    ///
+    /// Returns the results in the order the requests were added. An error
+    /// of a single request doesn't affect the others.
+    pub async fn execute(self) -> Vec<Result<T, Error>> {
+        let mut results: Vec<_> = std::iter::repeat_with(|| None).take(self.len()).collect();
+        let mut unordered = self.execute_unordered();
+        while let Some((index, result)) = unordered.next().await {
+            results[index] = Some(result);
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("every request gets a result"))
+            .collect()
+    }
+
+    /// Starts executing the batch. The returned [`BatchResults`] sends the
+    /// requests and yields their results as the responses arrive.
+    ///
+    /// Requests are only sent while [`BatchResults::next`] is being awaited.
+    #[inline]
+    pub fn execute_unordered(self) -> BatchResults<'a, T> {
+        BatchResults {
+            client: self.client,
+            queue: (0..self.requests.len()).collect(),
+            requests: self.requests.into_iter().map(Some).collect(),
+            in_flight: Vec::new(),
+            max_in_flight: self.max_in_flight,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Batch<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Batch")
+            .field("len", &self.len())
+            .field("max_in_flight", &self.max_in_flight)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Results of a [`Batch`] executed with [`Batch::execute_unordered`].
+///
+/// Dropping it cancels the requests which were not yet sent, the responses to
+/// the ones in flight are discarded.
+pub struct BatchResults<'a, T> {
+    client: Client,
+    /// Requests by index, taken once the result is returned.
+    requests: Vec<Option<Box<dyn BatchRequest<T> + 'a>>>,
+    /// Indexes of the requests to be sent.
+    queue: VecDeque<usize>,
+    in_flight: Vec<InFlight>,
+    max_in_flight: usize,
+}
+
+struct InFlight {
+    index: usize,
+    sync: SyncIndex,
+    response: oneshot::Receiver<Result<(), Error>>,
+}
+
+impl<'a, T> BatchResults<'a, T> {
+    /// Returns the index of the next request a response to which was received
+    /// along with the result of the request or `None` if all the results were
+    /// returned. This function yields.
+    pub async fn next(&mut self) -> Option<(usize, Result<T, Error>)> {
+        loop {
+            while self.in_flight.len() < self.max_in_flight {
+                let index = match self.queue.front() {
+                    Some(&index) => index,
+                    None => break,
+                };
+                let request = self.requests[index].as_ref().expect("is set until done");
+                let started = match self.client.wait_ready(request.needs_features()).await {
+                    Ok(()) => request.enqueue(&self.client),
+                    Err(e) => Err(e),
+                };
+                self.queue.pop_front();
+                match started {
+                    Ok((sync, response)) => self.in_flight.push(InFlight {
+                        index,
+                        sync,
+                        response,
+                    }),
+                    Err(e) => {
+                        self.requests[index] = None;
+                        return Some((index, Err(e)));
+                    }
+                }
+            }
+
+            if self.in_flight.is_empty() {
+                return None;
+            }
+
+            let (position, result) = future::poll_fn(|cx| {
+                for (position, in_flight) in self.in_flight.iter_mut().enumerate() {
+                    if let Poll::Ready(result) = Pin::new(&mut in_flight.response).poll(cx) {
+                        return Poll::Ready((position, result));
+                    }
+                }
+                Poll::Pending
+            })
+            .await;
+            let InFlight { index, sync, .. } = self.in_flight.swap_remove(position);
+            match result.expect("channel should be open") {
+                Ok(()) => {
+                    let request = self.requests[index].take().expect("is set until done");
+                    return Some((index, request.take_response(&self.client, sync)));
+                }
+                Err(Error::ConnectionLost(_) | Error::ServerShutdown)
+                    if self.client.retries_in_flight() =>
+                {
+                    // Send the request again once the connection is re-established
+                    self.queue.push_back(index);
+                }
+                Err(e) => {
+                    self.requests[index] = None;
+                    return Some((index, Err(self.client.in_flight_error(e))));
+                }
+            }
+        }
+    }
+
+    /// Returns the number of requests the results of which are yet to be
+    /// returned.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.queue.len() + self.in_flight.len()
+    }
+}
+
+impl<T> Drop for BatchResults<'_, T> {
+    fn drop(&mut self) {
+        let mut client = self.client.inner.borrow_mut();
+        for InFlight { sync, .. } in self.in_flight.drain(..) {
+            client.interceptors.discard(sync.0);
+            if client.awaiting_response.remove(&sync).is_some() {
+                client.discarded_responses.insert(sync);
+            } else {
+                client.protocol.drop_response(sync);
+            }
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for BatchResults<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchResults")
+            .field("remaining", &self.remaining())
+            .field("in_flight", &self.in_flight.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Type erased [`Request`] with a response convertible into `T`.
+trait BatchRequest<T> {
+    fn needs_features(&self) -> bool;
+
+    fn enqueue(
+        &self,
+        client: &Client,
+    ) -> Result<(SyncIndex, oneshot::Receiver<Result<(), Error>>), Error>;
+
+    fn take_response(&self, client: &Client, sync: SyncIndex) -> Result<T, Error>;
+}
+
+impl<R, T> BatchRequest<T> for R
+where
+    R: Request,
+    R::Response: Into<T>,
+{
+    #[inline(always)]
+    fn needs_features(&self) -> bool {
+        self.required_feature().is_some()
+    }
+
+    #[inline(always)]
+    fn enqueue(
+        &self,
+        client: &Client,
+    ) -> Result<(SyncIndex, oneshot::Receiver<Result<(), Error>>), Error> {
+        client.enqueue_request(self)
+    }
+
+    fn take_response(&self, client: &Client, sync: SyncIndex) -> Result<T, Error> {
+        client.take_response(sync, self).map(Into::into)
+    }
+}
@@ -0,0 +1,160 @@
+use super::{Client, Error};
+use crate::index::{IteratorType, TuplePosition};
+use crate::network::protocol::api::{Delete, Select, SelectPage, Update, Upsert};
+use crate::tuple::{Encode, ToTupleBuffer, Tuple};
+
+/// Remote index (a group of key values and pointers).
+///
+/// Can be obtained with [`RemoteSpace::index`](super::RemoteSpace::index).
+#[derive(Clone, Debug)]
+pub struct RemoteIndex {
+    client: Client,
+    space_id: u32,
+    index_id: u32,
+}
+
+impl RemoteIndex {
+    pub(crate) fn new(client: Client, space_id: u32, index_id: u32) -> Self {
+        RemoteIndex {
+            client,
+            space_id,
+            index_id,
+        }
+    }
+
+    pub(crate) async fn find(
+        client: &Client,
+        space_id: u32,
+        name: &str,
+    ) -> Result<Option<Self>, Error> {
+        let index_id = client
+            .schema()
+            .await?
+            .index(space_id, name)
+            .map(|index| index.id);
+        Ok(index_id.map(|index_id| Self::new(client.clone(), space_id, index_id)))
+    }
+
+    /// Returns the id of this index on the remote server.
+    #[inline(always)]
+    pub fn id(&self) -> u32 {
+        self.index_id
+    }
+
+    /// The remote-call equivalent of the local call `Index::get(...)`
+    /// (see [details](crate::index::Index::get)).
+    pub async fn get<K>(&self, key: &K) -> Result<Option<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        Ok(self
+            .select(IteratorType::Eq, key, Some(1), 0)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// The remote-call equivalent of the local call `Index::select(...)`
+    /// (see [details](crate::index::Index::select)).
+    ///
+    /// Treats `limit` as unlimited if `None` is specified.
+    pub async fn select<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        limit: Option<u32>,
+        offset: u32,
+    ) -> Result<Vec<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.client
+            .send_versioned(&Select {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                limit: limit.unwrap_or(u32::MAX),
+                offset,
+                iterator_type,
+                key,
+            })
+            .await
+    }
+
+    /// The remote-call equivalent of the local call `Index::select_page(...)`
+    /// (see [details](crate::index::Index::select_page)).
+    ///
+    /// Fails with [`ProtocolError::UnsupportedFeature`] if the server doesn't
+    /// support keyset pagination, which requires Tarantool 2.11 or later.
+    ///
+    /// [`ProtocolError::UnsupportedFeature`]: crate::network::protocol::Error::UnsupportedFeature
+    pub async fn select_page<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        after: Option<&TuplePosition>,
+        limit: u32,
+    ) -> Result<(Vec<Tuple>, Option<TuplePosition>), Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.client
+            .send_versioned(&SelectPage {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                limit,
+                iterator_type,
+                key,
+                after,
+            })
+            .await
+    }
+
+    /// The remote-call equivalent of the local call `Index::update(...)`
+    /// (see [details](crate::index::Index::update)).
+    pub async fn update<K, Op>(&self, key: &K, ops: &[Op]) -> Result<Option<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.client
+            .send_versioned(&Update {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                key,
+                ops,
+            })
+            .await
+    }
+
+    /// The remote-call equivalent of the local call `Index::upsert(...)`
+    /// (see [details](crate::index::Index::upsert)).
+    pub async fn upsert<T, Op>(&self, value: &T, ops: &[Op]) -> Result<Option<Tuple>, Error>
+    where
+        T: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.client
+            .send_versioned(&Upsert {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                value,
+                ops,
+            })
+            .await
+    }
+
+    /// The remote-call equivalent of the local call `Index::delete(...)`
+    /// (see [details](crate::index::Index::delete)).
+    pub async fn delete<K>(&self, key: &K) -> Result<Option<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.client
+            .send_versioned(&Delete {
+                space_id: self.space_id,
+                index_id: self.index_id,
+                key,
+            })
+            .await
+    }
+}
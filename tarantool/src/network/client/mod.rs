@@ -24,29 +24,71 @@
 //! gets back a correct response. Reducing the number of active sockets lowers the overhead of system calls and increases
 //! the overall server performance.
 //!
+//! # Reconnecting
+//! By default the client is closed once the connection is lost and all the following requests
+//! fail with [`Error::ClosedWithErr`]. A client created with [`Client::connect_with_reconnect`]
+//! instead re-establishes the connection in the background according to a [`reconnect::Policy`].
+//!
 //! # Implementation
 //! Internally the client uses [`Protocol`] to get bytes that it needs to send
 //! and push bytes that it gets from the network.
 //!
 //! On creation the client spawns sender and receiver worker threads. Which in turn
-//! use coio based [`TcpStream`] as the transport layer.
+//! use coio based [`Transport`] (either a [`TcpStream`] or a [`UnixStream`]) as
+//! the transport layer. The receiver is also responsible for reconnecting.
+//!
+//! [`TcpStream`]: tcp::TcpStream
+//! [`UnixStream`]: unix::UnixStream
 
+pub mod batch;
+mod index;
+pub mod pool;
+mod pushes;
+pub mod reconnect;
+pub mod schema;
+mod space;
+mod statement;
+mod stream;
 pub mod tcp;
+pub mod transport;
+pub mod unix;
 
-use std::cell::RefCell;
-use std::collections::HashMap;
+pub use batch::{Batch, BatchResults};
+pub use index::RemoteIndex;
+pub use pool::ClientPool;
+pub use pushes::{CallMessage, Pushes};
+pub use space::RemoteSpace;
+pub use statement::PreparedStatement;
+pub use stream::Stream;
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::io::{Cursor, Error as IoError};
 use std::rc::Rc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use self::tcp::{Error as TcpError, TcpStream};
+use self::schema::Schema;
+use self::tcp::Error as TcpError;
+use self::transport::Transport;
 
-use super::protocol::api::{Call, Eval, Execute, Ping, Request};
-use super::protocol::{self, Error as ProtocolError, Protocol, SizeHint, SyncIndex};
+use super::protocol::api::{
+    Call, Delete, Eval, Execute, Insert, Ping, Prepare, Replace, Request, Select, Unprepare,
+    Unwatch, Update, Upsert, Watch, WithSchemaVersion, WithStreamId,
+};
+use super::protocol::{
+    self, Error as ProtocolError, Event, Feature, Protocol, ProtocolFeatures, SizeHint, SyncIndex,
+};
+use crate::error::TarantoolErrorCode;
 use crate::fiber;
+use crate::fiber::r#async::timeout::{self, IntoTimeout as _};
 use crate::fiber::r#async::IntoOnDrop as _;
 use crate::fiber::r#async::{oneshot, watch};
-use crate::tuple::{ToTupleBuffer, Tuple};
+use crate::index::IteratorType;
+use crate::network::interceptor::{Interceptor, Interceptors};
+use crate::network::uri::{self, ToUri, Uri};
+use crate::space::SystemSpace;
+use crate::sql::SqlResponse;
+use crate::tuple::{Encode, RawByteBuf, ToTupleBuffer, Tuple};
 
 use futures::io::{ReadHalf, WriteHalf};
 use futures::{AsyncReadExt, AsyncWriteExt};
@@ -56,12 +98,28 @@ use futures::{AsyncReadExt, AsyncWriteExt};
 pub enum Error {
     #[error("tcp stream error: {0}")]
     Tcp(#[from] TcpError),
+    /// Failed to connect to a Unix domain socket.
+    #[error("failed to connect to unix socket: {0}")]
+    UnixConnect(IoError),
     #[error("io error: {0}")]
     Io(#[from] IoError),
+    #[error("invalid uri: {0}")]
+    Uri(#[from] uri::Error),
     #[error("protocol error: {0}")]
     Protocol(#[from] ProtocolError),
     #[error("closed with error: {0}")]
     ClosedWithErr(String),
+    /// The connection was lost before the response was received.
+    ///
+    /// Only returned by clients with a [`reconnect::Policy`], which will try
+    /// to re-establish the connection, so the request can be sent again.
+    #[error("connection lost: {0}")]
+    ConnectionLost(String),
+    /// The server announced its shutdown. Is returned for the requests made
+    /// after that and for the ones which didn't receive a response within
+    /// the [shutdown timeout](Client::set_shutdown_timeout).
+    #[error("server is shutting down")]
+    ServerShutdown,
     #[error("{0}")]
     Other(String),
 }
@@ -69,8 +127,14 @@ pub enum Error {
 #[derive(Clone, Debug)]
 enum State {
     Alive,
+    /// The server announced its shutdown. No new requests are sent, while
+    /// the ones in flight are still awaiting response.
+    ShuttingDown,
+    Reconnecting,
     ClosedManually,
     ClosedWithError(String),
+    /// The connection was closed after the server announced its shutdown.
+    ClosedByShutdown,
 }
 
 impl State {
@@ -78,11 +142,27 @@ impl State {
         matches!(self, Self::Alive)
     }
 
+    /// Returns `true` if the connection is established, even if the server
+    /// is shutting down.
+    fn is_connected(&self) -> bool {
+        matches!(self, Self::Alive | Self::ShuttingDown)
+    }
+
     fn is_closed(&self) -> bool {
-        !self.is_alive()
+        matches!(
+            self,
+            Self::ClosedManually | Self::ClosedWithError(_) | Self::ClosedByShutdown
+        )
     }
 }
 
+/// Key broadcast by the server once it starts shutting down.
+const SHUTDOWN_KEY: &str = "box.shutdown";
+
+/// Time the in-flight requests are awaited after the server announces its
+/// shutdown if not specified with [`Client::set_shutdown_timeout`].
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
 type WorkerHandle = fiber::JoinHandle<'static, ()>;
 
 #[derive(Debug)]
@@ -91,19 +171,69 @@ struct ClientInner {
     awaiting_response: HashMap<SyncIndex, oneshot::Sender<Result<(), Error>>>,
     state: State,
     close_token: Option<tcp::CloseToken>,
+    /// Writing half of a newly established connection to be picked up by sender.
+    writer: Option<WriteHalf<Transport>>,
+    /// Incremented every time the connection is re-established.
+    connection_id: u64,
     worker_handles: Vec<WorkerHandle>,
     sender_waker: watch::Sender<()>,
+    /// Notified every time `state` changes.
+    state_waker: watch::Sender<()>,
+    schema: Option<Rc<Schema>>,
+    /// Id of the next stream created with [`Client::stream`].
+    next_stream_id: u64,
+    uri: Uri,
+    reconnect: Option<reconnect::Policy>,
+    /// Senders of the channels returned by [`Client::watch`] by key.
+    watchers: HashMap<String, watch::Sender<Option<RawByteBuf>>>,
+    /// Pushes of the requests sent with [`Client::call_with_pushes`].
+    pushes: HashMap<SyncIndex, pushes::PushQueue>,
+    /// Requests responses to which are not awaited and are dropped once
+    /// received.
+    discarded_responses: HashSet<SyncIndex>,
+    /// Number of [`PreparedStatement`] handles by statement id. Statements
+    /// belong to the session, so it is cleared once the connection is
+    /// re-established.
+    prepared_statements: HashMap<u32, usize>,
+    /// Added with [`Client::add_interceptor`].
+    interceptors: Rc<Interceptors>,
+    /// Set with [`Client::set_shutdown_timeout`].
+    shutdown_timeout: Duration,
+    /// Time until which the in-flight requests are awaited once the server
+    /// announced its shutdown.
+    shutdown_deadline: Option<Instant>,
 }
 
 impl ClientInner {
-    pub fn new(config: protocol::Config, sender_waker: watch::Sender<()>) -> Self {
+    pub fn new(
+        uri: Uri,
+        config: protocol::Config,
+        reconnect: Option<reconnect::Policy>,
+        sender_waker: watch::Sender<()>,
+    ) -> Self {
+        let (state_waker, _) = watch::channel(());
         Self {
             protocol: Protocol::with_config(config),
             awaiting_response: HashMap::new(),
             state: State::Alive,
             close_token: None,
+            writer: None,
+            connection_id: 0,
             worker_handles: Vec::new(),
             sender_waker,
+            state_waker,
+            schema: None,
+            // Stream id 0 means the request doesn't belong to any stream
+            next_stream_id: 1,
+            uri,
+            reconnect,
+            watchers: HashMap::new(),
+            pushes: HashMap::new(),
+            discarded_responses: HashSet::new(),
+            prepared_statements: HashMap::new(),
+            interceptors: Rc::default(),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            shutdown_deadline: None,
         }
     }
 }
@@ -128,7 +258,14 @@ fn wake_sender(client: &RefCell<ClientInner>) -> Result<(), watch::SendError<()>
 /// See [`super::client`] for examples.
 // WARNING: Attention should be payed not to borrow inner client across await and yield points.
 #[derive(Clone, Debug)]
-pub struct Client(Rc<RefCell<ClientInner>>);
+pub struct Client {
+    inner: Rc<RefCell<ClientInner>>,
+    /// Id of the stream requests are sent in, see [`Client::stream`].
+    stream_id: Option<u64>,
+    /// Id of the connection the stream sent its first request over. Shared
+    /// between the clones of the stream.
+    stream_connection: Option<Rc<Cell<Option<u64>>>>,
+}
 
 impl Client {
     /// Creates a new client and tries to establish connection
@@ -141,6 +278,28 @@ impl Client {
         Self::connect_with_config(url, port, Default::default()).await
     }
 
+    /// Creates a new client and tries to establish connection to `uri`,
+    /// which can be either `host:port` or `unix/:path` for connecting over
+    /// a Unix domain socket. See [`uri`] for the supported formats.
+    ///
+    /// # Errors
+    /// Error is returned if `uri` is invalid or an attempt to connect failed.
+    /// See [`Error`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async {
+    /// use tarantool::network::client::Client;
+    ///
+    /// let client = Client::connect_uri("unix/:/var/run/tarantool.sock", Default::default())
+    ///     .await
+    ///     .unwrap();
+    /// # };
+    /// ```
+    pub async fn connect_uri(uri: impl ToUri, config: protocol::Config) -> Result<Self, Error> {
+        Self::connect_inner(uri.to_uri()?, config, None).await
+    }
+
     /// Creates a new client and tries to establish connection
     /// to `url:port`
     ///
@@ -155,12 +314,69 @@ impl Client {
         port: u16,
         config: protocol::Config,
     ) -> Result<Self, Error> {
+        Self::connect_inner(
+            Uri::Tcp {
+                host: url.into(),
+                port,
+            },
+            config,
+            None,
+        )
+        .await
+    }
+
+    /// Creates a new client and tries to establish connection
+    /// to `url:port`
+    ///
+    /// Unlike [`Client::connect_with_config`] the returned client isn't closed
+    /// once the connection is lost. Instead it reconnects and re-authenticates
+    /// according to the `policy`. See [`reconnect::Policy`] for details.
+    ///
+    /// # Errors
+    /// Error is returned if the first attempt to connect failed.
+    /// See [`Error`].
+    pub async fn connect_with_reconnect(
+        url: &str,
+        port: u16,
+        config: protocol::Config,
+        policy: reconnect::Policy,
+    ) -> Result<Self, Error> {
+        Self::connect_inner(
+            Uri::Tcp {
+                host: url.into(),
+                port,
+            },
+            config,
+            Some(policy),
+        )
+        .await
+    }
+
+    /// Creates a new client and tries to establish connection to `uri`
+    /// with reconnects according to the `policy`.
+    ///
+    /// See [`Client::connect_uri`] and [`Client::connect_with_reconnect`]
+    /// for details.
+    pub async fn connect_uri_with_reconnect(
+        uri: impl ToUri,
+        config: protocol::Config,
+        policy: reconnect::Policy,
+    ) -> Result<Self, Error> {
+        Self::connect_inner(uri.to_uri()?, config, Some(policy)).await
+    }
+
+    async fn connect_inner(
+        uri: Uri,
+        config: protocol::Config,
+        reconnect: Option<reconnect::Policy>,
+    ) -> Result<Self, Error> {
+        let stream = Transport::connect(&uri).await?;
         let (sender_waker_tx, sender_waker_rx) = watch::channel(());
-        let mut client = ClientInner::new(config, sender_waker_tx);
-        let stream = TcpStream::connect(url, port).await?;
+        let mut client = ClientInner::new(uri, config, reconnect, sender_waker_tx);
         client.close_token = Some(stream.close_token());
 
         let (reader, writer) = stream.split();
+        client.writer = Some(writer);
         let client = Rc::new(RefCell::new(client));
 
         // start receiver in a separate fiber
@@ -172,20 +388,292 @@ impl Client {
 
         // start sender in a separate fiber
         let sender_handle = fiber::Builder::new()
-            .func_async(sender(client.clone(), writer, sender_waker_rx))
+            .func_async(sender(client.clone(), sender_waker_rx))
             .name("network-client-sender")
             .start()
             .unwrap();
         client.borrow_mut().worker_handles = vec![receiver_handle, sender_handle];
-        Ok(Self(client))
+        Ok(Self {
+            inner: client,
+            stream_id: None,
+            stream_connection: None,
+        })
+    }
+
+    /// Waits until the client is connected, which only takes time if the client
+    /// is reconnecting. This function may yield.
+    async fn wait_connected(&self) -> Result<(), Error> {
+        let mut state_changed = self.inner.borrow().state_waker.subscribe();
+        loop {
+            match self.inner.borrow().state.clone() {
+                State::Alive => return Ok(()),
+                State::Reconnecting => {}
+                State::ShuttingDown => {
+                    if self.inner.borrow().reconnect.is_none() {
+                        return Err(Error::ServerShutdown);
+                    }
+                    // Requests are sent once the client reconnects
+                }
+                State::ClosedManually => {
+                    unreachable!("All client handles are dropped at this point")
+                }
+                State::ClosedWithError(err) => return Err(Error::ClosedWithErr(err)),
+                State::ClosedByShutdown => return Err(Error::ServerShutdown),
+            }
+            state_changed
+                .changed()
+                .await
+                .expect("sender is held by the client");
+        }
+    }
+
+    /// Returns the protocol features negotiated with the server. This function
+    /// yields if the client hasn't yet received the server's response to the
+    /// id request of the current connection.
+    ///
+    /// Servers older than 2.10 don't support the id request, in which case
+    /// [`ProtocolFeatures::default`] is returned.
+    pub async fn protocol_features(&self) -> Result<ProtocolFeatures, Error> {
+        let mut state_changed = self.inner.borrow().state_waker.subscribe();
+        loop {
+            self.wait_connected().await?;
+            if let Some(features) = self.inner.borrow().protocol.features() {
+                return Ok(features);
+            }
+            state_changed
+                .changed()
+                .await
+                .expect("sender is held by the client");
+        }
+    }
+
+    /// Adds an [`Interceptor`] which is called for every request sent by this
+    /// client, its clones and its streams.
+    ///
+    /// Requests sent before the interceptor was added are not reported to it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async {
+    /// use std::rc::Rc;
+    /// use tarantool::network::client::Client;
+    /// use tarantool::network::interceptor::{Interceptor, RequestInfo, ResponseInfo};
+    ///
+    /// struct SlowRequests;
+    ///
+    /// impl Interceptor for SlowRequests {
+    ///     fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+    ///         if response.duration.as_secs() > 1 {
+    ///             log::warn!("slow {} request: {:?}", request.kind, request.name);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let client = Client::connect("localhost", 3301).await.unwrap();
+    /// client.add_interceptor(Rc::new(SlowRequests));
+    /// # };
+    /// ```
+    pub fn add_interceptor(&self, interceptor: Rc<dyn Interceptor>) {
+        self.inner.borrow().interceptors.add(interceptor);
+    }
+
+    /// Sets the time the requests in flight are awaited after the server
+    /// announces its shutdown, after which they fail with
+    /// [`Error::ServerShutdown`] and the connection is closed.
+    ///
+    /// Once a server supporting watchers (Tarantool 2.10 or later) starts
+    /// shutting down, the client stops sending new requests. They fail with
+    /// [`Error::ServerShutdown`] or, if the client has
+    /// a [`reconnect::Policy`], wait for the connection to be re-established.
+    /// The connection is closed once the responses to all the requests in
+    /// flight are received or the timeout expires.
+    ///
+    /// Default: [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    pub fn set_shutdown_timeout(&self, timeout: Duration) {
+        self.inner.borrow_mut().shutdown_timeout = timeout;
+    }
+
+    #[inline]
+    fn interceptors(&self) -> Rc<Interceptors> {
+        self.inner.borrow().interceptors.clone()
+    }
+
+    /// Returns the number of requests awaiting response.
+    pub(crate) fn pending_requests(&self) -> usize {
+        self.inner.borrow().awaiting_response.len()
+    }
+
+    /// Returns `true` if requests which were in flight when the connection
+    /// was lost should be sent again.
+    ///
+    /// Requests of a stream are never sent again, as the server rolls back
+    /// the transaction of the stream once the connection is lost.
+    fn retries_in_flight(&self) -> bool {
+        if self.stream_id.is_some() {
+            return false;
+        }
+        self.inner
+            .borrow()
+            .reconnect
+            .as_ref()
+            .map_or(false, |policy| {
+                policy.in_flight == reconnect::InFlight::Retry
+            })
+    }
+
+    /// Send [`Request`] once the client is connected and subscribe to its
+    /// response. The returned channel receives `Ok` once the response can be
+    /// taken from the protocol. This function yields.
+    async fn start_request<R: Request>(
+        &self,
+        request: &R,
+    ) -> Result<(SyncIndex, oneshot::Receiver<Result<(), Error>>), Error> {
+        self.wait_ready(request.required_feature().is_some())
+            .await?;
+        self.enqueue_request(request)
+    }
+
+    /// Waits until the client is connected and, if the request to be sent
+    /// `needs_features` or belongs to a stream, until the protocol features
+    /// are negotiated. This function yields only if the client is reconnecting
+    /// or the features are not yet known.
+    async fn wait_ready(&self, needs_features: bool) -> Result<(), Error> {
+        self.wait_connected().await?;
+        if needs_features || self.stream_id.is_some() {
+            // Make sure the protocol can reject the request if the server
+            // doesn't support it
+            self.protocol_features().await?;
+        }
+        Ok(())
+    }
+
+    /// Send [`Request`] and subscribe to its response without waiting for
+    /// the client to be ready, see [`Client::wait_ready`].
+    /// This function doesn't yield.
+    fn enqueue_request<R: Request>(
+        &self,
+        request: &R,
+    ) -> Result<(SyncIndex, oneshot::Receiver<Result<(), Error>>), Error> {
+        let sync = {
+            let mut client = self.inner.borrow_mut();
+            if let Some(stream_connection) = &self.stream_connection {
+                match stream_connection.get() {
+                    None => stream_connection.set(Some(client.connection_id)),
+                    Some(connection_id) if connection_id != client.connection_id => {
+                        return Err(Error::ConnectionLost(
+                            "connection was re-established, stream transaction aborted".into(),
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+            match self.stream_id {
+                Some(stream_id) => client
+                    .protocol
+                    .send_request(&WithStreamId { request, stream_id })?,
+                None => client.protocol.send_request(request)?,
+            }
+        };
+        let (tx, rx) = oneshot::channel();
+        self.inner.borrow_mut().awaiting_response.insert(sync, tx);
+        self.intercept_request(request, sync);
+        wake_sender(&self.inner).unwrap();
+        Ok((sync, rx))
+    }
+
+    /// Reports the `request` sent with `sync` to the interceptors.
+    fn intercept_request<R: Request>(&self, request: &R, sync: SyncIndex) {
+        let interceptors = self.interceptors();
+        if interceptors.is_empty() {
+            return;
+        }
+        // The request is encoded once again, as the protocol doesn't expose
+        // its outgoing buffer
+        let mut data = Vec::new();
+        let encoded = match self.stream_id {
+            Some(stream_id) => WithStreamId { request, stream_id }.encode(&mut data, sync),
+            None => request.encode(&mut data, sync),
+        };
+        if encoded.is_err() {
+            return;
+        }
+        interceptors.before_request(&data, |space_id| {
+            let client = self.inner.borrow();
+            let space = client.schema.as_ref()?.space_by_id(space_id)?;
+            Some(space.name.clone())
+        });
+    }
+
+    /// Takes the response to the request with `sync` from the protocol and
+    /// reports it to the interceptors.
+    fn take_response<R: Request>(
+        &self,
+        sync: SyncIndex,
+        request: &R,
+    ) -> Result<R::Response, Error> {
+        let result = self
+            .inner
+            .borrow_mut()
+            .protocol
+            .take_response(sync, request)
+            .expect("is present at this point")
+            .map_err(Error::from);
+        let error = result.as_ref().err().map(|e| e as _);
+        self.interceptors().after_response(sync.0, error);
+        result
+    }
+
+    /// Send [`Request`] without waiting for the response, which is dropped
+    /// once received. Does nothing if the client is not connected.
+    /// This function doesn't yield.
+    fn send_detached<R: Request>(&self, request: &R) -> Result<(), Error> {
+        {
+            let mut client = self.inner.borrow_mut();
+            if !client.state.is_alive() {
+                return Ok(());
+            }
+            let sync = client.protocol.send_request(request)?;
+            client.discarded_responses.insert(sync);
+        }
+        wake_sender(&self.inner).unwrap();
+        Ok(())
+    }
+
+    /// Registers a [`PreparedStatement`] handle to the statement with
+    /// `stmt_id`. Returns the id of the connection in the session of which
+    /// the statement was prepared.
+    fn acquire_statement(&self, stmt_id: u32) -> u64 {
+        let mut client = self.inner.borrow_mut();
+        *client.prepared_statements.entry(stmt_id).or_default() += 1;
+        client.connection_id
     }
 
-    fn check_state(&self) -> Result<(), Error> {
-        match self.0.borrow().state.clone() {
-            State::Alive => Ok(()),
-            State::ClosedManually => unreachable!("All client handles are dropped at this point"),
-            State::ClosedWithError(err) => Err(Error::ClosedWithErr(err)),
+    /// Unregisters a [`PreparedStatement`] handle acquired with
+    /// [`Client::acquire_statement`]. The statement is deallocated on
+    /// the server once its last handle is dropped, unless the connection
+    /// was re-established since it was prepared.
+    /// This function doesn't yield.
+    fn release_statement(&self, stmt_id: u32, connection_id: u64) {
+        {
+            let mut client = self.inner.borrow_mut();
+            if client.connection_id != connection_id {
+                // The statement was deallocated along with the old session
+                return;
+            }
+            match client.prepared_statements.get_mut(&stmt_id) {
+                Some(count) if *count > 1 => {
+                    *count -= 1;
+                    return;
+                }
+                Some(_) => {
+                    client.prepared_statements.remove(&stmt_id);
+                }
+                None => return,
+            }
         }
+        // Errors are ignored as the statement is deallocated by the server
+        // anyway once the session is closed
+        let _ = self.send_detached(&Unprepare { stmt_id });
     }
 
     /// Send [`Request`] and wait for response.
@@ -195,25 +683,209 @@ impl Client {
     /// In case of `ClosedWithErr` it is suggested to recreate the connection.
     /// Other errors are self-descriptive.
     async fn send<R: Request>(&self, request: &R) -> Result<R::Response, Error> {
-        self.check_state()?;
-        let sync = self.0.borrow_mut().protocol.send_request(request)?;
-        let (tx, rx) = oneshot::channel();
-        self.0.borrow_mut().awaiting_response.insert(sync, tx);
-        wake_sender(&self.0).unwrap();
-        // Cleanup `awaiting_response` entry in case of `send` future cancelation
-        // at this `.await`.
-        // `send` can be canceled for example with `Timeout`.
-        rx.on_drop(|| {
-            let _ = self.0.borrow_mut().awaiting_response.remove(&sync);
-        })
-        .await
-        .expect("Channel should be open")?;
+        loop {
+            let (sync, rx) = self.start_request(request).await?;
+            // Cleanup `awaiting_response` entry in case of `send` future cancelation
+            // at this `.await`.
+            // `send` can be canceled for example with `Timeout`.
+            let result = rx
+                .on_drop(|| {
+                    let interceptors = {
+                        let mut client = self.inner.borrow_mut();
+                        let _ = client.awaiting_response.remove(&sync);
+                        client.interceptors.clone()
+                    };
+                    interceptors.discard(sync.0);
+                })
+                .await
+                .expect("Channel should be open");
+            match result {
+                Ok(()) => return self.take_response(sync, request),
+                Err(Error::ConnectionLost(_) | Error::ServerShutdown)
+                    if self.retries_in_flight() =>
+                {
+                    // Send the request again once the connection is re-established
+                }
+                Err(err) => return Err(self.in_flight_error(err)),
+            }
+        }
+    }
+
+    /// Converts the error of a request which was in flight when the connection
+    /// was lost. Such requests of a stream fail with [`Error::ConnectionLost`]
+    /// as their transaction is aborted, see [`Client::retries_in_flight`].
+    pub(crate) fn in_flight_error(&self, err: Error) -> Error {
+        match err {
+            Error::ConnectionLost(e) if self.stream_id.is_some() => {
+                Error::ConnectionLost(format!("{}, stream transaction aborted", e))
+            }
+            Error::ServerShutdown if self.stream_id.is_some() => {
+                Error::ConnectionLost("server is shutting down, stream transaction aborted".into())
+            }
+            err => err,
+        }
+    }
+
+    /// Send [`Request`] along with the version of the cached schema and wait
+    /// for response. This function yields.
+    ///
+    /// Should be used for requests containing space or index ids taken
+    /// from the schema cache. If the remote schema has changed since it was
+    /// cached, the schema is reloaded and the request is sent again.
+    async fn send_versioned<R: Request>(&self, request: &R) -> Result<R::Response, Error> {
+        loop {
+            let schema_version = self.schema().await?.version();
+            let result = self
+                .send(&WithSchemaVersion {
+                    request,
+                    schema_version,
+                })
+                .await;
+            match result {
+                Err(Error::Protocol(ProtocolError::Response(e)))
+                    if e.code() == TarantoolErrorCode::WrongSchemaVersion as u32 =>
+                {
+                    self.reload_schema().await?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns the cached schema of the remote server.
+    ///
+    /// The schema is fetched on the first call and is fetched again
+    /// every time the client detects that the remote schema version has
+    /// changed since the schema was cached. This function yields in this case.
+    pub async fn schema(&self) -> Result<Rc<Schema>, Error> {
+        let is_outdated = {
+            let client = self.inner.borrow();
+            match (&client.schema, client.protocol.schema_version()) {
+                (Some(schema), Some(actual)) => schema.version() < actual,
+                _ => true,
+            }
+        };
+        if is_outdated {
+            self.reload_schema().await?;
+        }
         Ok(self
-            .0
-            .borrow_mut()
+            .inner
+            .borrow()
+            .schema
+            .clone()
+            .expect("is set at this point"))
+    }
+
+    /// Fetches the schema of the remote server dropping the cached one.
+    /// This function yields.
+    ///
+    /// See also [`Client::schema`].
+    pub async fn reload_schema(&self) -> Result<(), Error> {
+        if self.inner.borrow().protocol.schema_version().is_none() {
+            // Learn the current schema version
+            self.ping().await?;
+        }
+        // The schema version may only grow, so if it changes while the schema is
+        // being fetched, the cached schema will be considered outdated.
+        let version = self
+            .inner
+            .borrow()
             .protocol
-            .take_response(sync, request)
-            .expect("Is present at this point")?)
+            .schema_version()
+            .expect("is set at this point");
+        let spaces = self
+            .select(
+                SystemSpace::VSpace as u32,
+                0,
+                IteratorType::All,
+                &(),
+                None,
+                0,
+            )
+            .await?;
+        let indexes = self
+            .select(
+                SystemSpace::VIndex as u32,
+                0,
+                IteratorType::All,
+                &(),
+                None,
+                0,
+            )
+            .await?;
+        let schema = Schema::from_rows(version, spaces, indexes).map_err(ProtocolError::from)?;
+        self.inner.borrow_mut().schema = Some(Rc::new(schema));
+        Ok(())
+    }
+
+    /// Creates a new [`Stream`] using the connection of this client.
+    ///
+    /// Requests sent through the stream are executed by the server
+    /// sequentially and can be combined into a transaction.
+    pub fn stream(&self) -> Stream {
+        let mut client = self.inner.borrow_mut();
+        let stream_id = client.next_stream_id;
+        client.next_stream_id += 1;
+        Stream::new(Self {
+            inner: self.inner.clone(),
+            stream_id: Some(stream_id),
+            stream_connection: Some(Rc::new(Cell::new(None))),
+        })
+    }
+
+    /// Creates a [`Batch`] of requests to be pipelined through the connection
+    /// of this client.
+    ///
+    /// See [`Batch`] for details.
+    pub fn batch<'a, T>(&self) -> Batch<'a, T> {
+        Batch::new(self.clone())
+    }
+
+    /// Subscribes to the updates of `key` broadcast by the server with
+    /// `box.broadcast`.
+    ///
+    /// Returns a channel which receives the msgpack encoded value of the key
+    /// right after the subscription is made and then every time it changes.
+    /// The value is `None` until the first update is received. The server
+    /// sends `nil` if the key was never broadcast.
+    ///
+    /// The subscription is renewed after reconnects (see
+    /// [`Client::connect_with_reconnect`]) and is cancelled once all the
+    /// receivers of the channel are dropped. Watching the same key multiple
+    /// times returns receivers of the same channel, in which case
+    /// the latest received value can be accessed with
+    /// [`watch::Receiver::get_cloned`].
+    ///
+    /// Requires Tarantool 2.10 or later.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async {
+    /// use tarantool::network::client::Client;
+    ///
+    /// let client = Client::connect("localhost", 3301).await.unwrap();
+    /// let mut leader = client.watch("leader").await.unwrap();
+    /// while leader.changed().await.is_ok() {
+    ///     let data = leader.get_cloned().expect("is set after an update");
+    ///     let name: Option<String> = rmp_serde::from_slice(&data).unwrap();
+    ///     println!("new leader: {:?}", name);
+    /// }
+    /// # };
+    /// ```
+    pub async fn watch(&self, key: &str) -> Result<watch::Receiver<Option<RawByteBuf>>, Error> {
+        // Make sure the protocol can reject the request if the server doesn't
+        // support watchers
+        self.protocol_features().await?;
+        let mut client = self.inner.borrow_mut();
+        if let Some(sender) = client.watchers.get(key) {
+            return Ok(sender.subscribe());
+        }
+        client.protocol.send_request(&Watch { key })?;
+        let (sender, receiver) = watch::channel(None);
+        client.watchers.insert(key.to_owned(), sender);
+        drop(client);
+        wake_sender(&self.inner).unwrap();
+        Ok(receiver)
     }
 
     /// Execute a PING command.
@@ -234,6 +906,42 @@ impl Client {
         self.send(&Call { fn_name, args }).await
     }
 
+    /// Call a remote stored procedure which reports intermediate results with
+    /// `box.session.push`. This function yields until the request is sent.
+    ///
+    /// The returned [`Pushes`] yields the pushed values followed by the value
+    /// returned by the procedure.
+    ///
+    /// Unlike other requests, the call isn't sent again if the connection is
+    /// lost, even if the client's [`reconnect::Policy`] retries requests.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async {
+    /// use tarantool::network::client::{CallMessage, Client};
+    ///
+    /// let client = Client::connect("localhost", 3301).await.unwrap();
+    /// let mut messages = client.call_with_pushes("long_job", &()).await.unwrap();
+    /// while let Some(message) = messages.next().await.unwrap() {
+    ///     match message {
+    ///         CallMessage::Push(data) => {
+    ///             let progress: f64 = rmp_serde::from_slice(&data).unwrap();
+    ///             println!("progress: {}", progress);
+    ///         }
+    ///         CallMessage::Response(result) => println!("done: {:?}", result),
+    ///     }
+    /// }
+    /// # };
+    /// ```
+    pub async fn call_with_pushes<T: ToTupleBuffer>(
+        &self,
+        fn_name: &str,
+        args: &T,
+    ) -> Result<Pushes, Error> {
+        let (sync, response) = self.start_request(&Call { fn_name, args }).await?;
+        Ok(Pushes::new(self.clone(), sync, response))
+    }
+
     /// Evaluates and executes the expression in Lua-string, which may be any statement or series of statements.
     ///
     /// An execute privilege is required; if the user does not have it, an administrator may grant it with
@@ -250,12 +958,16 @@ impl Client {
     }
 
     /// Execute sql query remotely.
+    ///
+    /// Returns the resulting rows along with the column metadata and the
+    /// information about the changes made by the statement. At most `limit`
+    /// rows are kept if it is set.
     pub async fn execute<T: ToTupleBuffer>(
         &self,
         sql: &str,
         bind_params: &T,
         limit: Option<usize>,
-    ) -> Result<Vec<Tuple>, Error> {
+    ) -> Result<SqlResponse, Error> {
         self.send(&Execute {
             sql,
             bind_params,
@@ -263,13 +975,138 @@ impl Client {
         })
         .await
     }
+
+    /// Prepares the sql statement on the remote server for repeated
+    /// execution with [`PreparedStatement::execute`].
+    ///
+    /// The statement is deallocated on the server once the returned
+    /// [`PreparedStatement`] is dropped.
+    pub async fn prepare(&self, sql: &str) -> Result<PreparedStatement, Error> {
+        let response = self.send(&Prepare { sql }).await?;
+        PreparedStatement::new(self.clone(), response)
+    }
+
+    /// Search space by name on remote server.
+    ///
+    /// Returns `None` if there is no space with such name
+    /// or the user has no access to it.
+    ///
+    /// The space is looked up in the cached schema, see [`Client::schema`].
+    pub async fn space(&self, name: &str) -> Result<Option<RemoteSpace>, Error> {
+        let space_id = self.schema().await?.space(name).map(|space| space.id);
+        Ok(space_id.map(|space_id| RemoteSpace::new(self.clone(), space_id)))
+    }
+
+    /// The remote-call equivalent of the local call `Index::select(...)`
+    /// (see [details](crate::index::Index::select)).
+    ///
+    /// Treats `limit` as unlimited if `None` is specified.
+    pub async fn select<K: ToTupleBuffer>(
+        &self,
+        space_id: u32,
+        index_id: u32,
+        iterator_type: IteratorType,
+        key: &K,
+        limit: Option<u32>,
+        offset: u32,
+    ) -> Result<Vec<Tuple>, Error> {
+        self.send(&Select {
+            space_id,
+            index_id,
+            limit: limit.unwrap_or(u32::MAX),
+            offset,
+            iterator_type,
+            key,
+        })
+        .await
+    }
+
+    /// The remote-call equivalent of the local call `Space::insert(...)`
+    /// (see [details](crate::space::Space::insert)).
+    pub async fn insert<T: ToTupleBuffer>(
+        &self,
+        space_id: u32,
+        value: &T,
+    ) -> Result<Option<Tuple>, Error> {
+        self.send(&Insert { space_id, value }).await
+    }
+
+    /// The remote-call equivalent of the local call `Space::replace(...)`
+    /// (see [details](crate::space::Space::replace)).
+    pub async fn replace<T: ToTupleBuffer>(
+        &self,
+        space_id: u32,
+        value: &T,
+    ) -> Result<Option<Tuple>, Error> {
+        self.send(&Replace { space_id, value }).await
+    }
+
+    /// The remote-call equivalent of the local call `Index::update(...)`
+    /// (see [details](crate::index::Index::update)).
+    pub async fn update<K, Op>(
+        &self,
+        space_id: u32,
+        index_id: u32,
+        key: &K,
+        ops: &[Op],
+    ) -> Result<Option<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.send(&Update {
+            space_id,
+            index_id,
+            key,
+            ops,
+        })
+        .await
+    }
+
+    /// The remote-call equivalent of the local call `Index::upsert(...)`
+    /// (see [details](crate::index::Index::upsert)).
+    pub async fn upsert<T, Op>(
+        &self,
+        space_id: u32,
+        index_id: u32,
+        value: &T,
+        ops: &[Op],
+    ) -> Result<Option<Tuple>, Error>
+    where
+        T: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.send(&Upsert {
+            space_id,
+            index_id,
+            value,
+            ops,
+        })
+        .await
+    }
+
+    /// The remote-call equivalent of the local call `Index::delete(...)`
+    /// (see [details](crate::index::Index::delete)).
+    pub async fn delete<K: ToTupleBuffer>(
+        &self,
+        space_id: u32,
+        index_id: u32,
+        key: &K,
+    ) -> Result<Option<Tuple>, Error> {
+        self.send(&Delete {
+            space_id,
+            index_id,
+            key,
+        })
+        .await
+    }
 }
 
 impl Drop for Client {
     fn drop(&mut self) {
         // 3 means this client and 2 fibers: receiver and sender
-        if Rc::strong_count(&self.0) <= 3 {
-            let mut client = self.0.borrow_mut();
+        if Rc::strong_count(&self.inner) <= 3 {
+            let mut client = self.inner.borrow_mut();
             // Stop fibers
             client.state = State::ClosedManually;
 
@@ -277,6 +1114,8 @@ impl Drop for Client {
             let handles: Vec<_> = client.worker_handles.drain(..).collect();
             // Wake sender so it can exit loop
             client.sender_waker.send(()).unwrap();
+            // Wake receiver in case it is waiting to reconnect
+            let _ = client.state_waker.send(());
 
             // Drop ref before executing code that switches fibers.
             drop(client);
@@ -292,106 +1131,368 @@ impl Drop for Client {
     }
 }
 
-macro_rules! handle_result {
-    ($client:expr, $e:expr) => {
-        match $e {
-            Ok(value) => value,
-            Err(err) => {
-                let err: Error = err.into();
-                let str_err = err.to_string();
-                $client.state = State::ClosedWithError(err.to_string());
-                // Notify all subscribers on closing
-                let subscriptions: HashMap<_, _> = $client.awaiting_response.drain().collect();
-                for (_, subscription) in subscriptions {
-                    // We don't care about errors at this point
-                    let _ = subscription.send(Err(Error::ClosedWithErr(str_err.clone())));
-                }
-                return;
-            }
+/// Handles an error of the connection with id `connection_id`.
+///
+/// Fails the requests awaiting response and either closes the client or lets
+/// the receiver reconnect depending on whether the client has a reconnect policy.
+/// Does nothing if the connection was already handled as lost.
+fn connection_lost(client: &RefCell<ClientInner>, connection_id: u64, err: Error) {
+    let mut client = client.borrow_mut();
+    if !client.state.is_connected() || client.connection_id != connection_id {
+        return;
+    }
+    // Once the shutdown is announced, the server closes the connection
+    // on its own, so any error is the consequence of the shutdown
+    let shutdown =
+        matches!(err, Error::ServerShutdown) || matches!(client.state, State::ShuttingDown);
+    let err = err.to_string();
+    let reconnecting = client.reconnect.is_some();
+    client.state = if reconnecting {
+        State::Reconnecting
+    } else if shutdown {
+        State::ClosedByShutdown
+    } else {
+        State::ClosedWithError(err.clone())
+    };
+    client.shutdown_deadline = None;
+    if !reconnecting {
+        // Close the channels returned by `Client::watch`
+        client.watchers.clear();
+    }
+    client.discarded_responses.clear();
+    let make_error = || {
+        if shutdown {
+            Error::ServerShutdown
+        } else if reconnecting {
+            Error::ConnectionLost(err.clone())
+        } else {
+            Error::ClosedWithErr(err.clone())
         }
     };
+    // Notify all subscribers
+    for (_, subscription) in client.awaiting_response.drain() {
+        // We don't care about errors at this point
+        let _ = subscription.send(Err(make_error()));
+    }
+    if let Some(close_token) = client.close_token.take() {
+        // Wake the other worker if it is blocked on the stream
+        let _ = close_token.shutdown();
+    }
+    let _ = client.sender_waker.send(());
+    let _ = client.state_waker.send(());
+    let interceptors = client.interceptors.clone();
+    drop(client);
+    interceptors.fail_all(&make_error());
 }
 
 /// Sender work loop. Yields on each iteration and during awaits.
-async fn sender(
-    client: Rc<RefCell<ClientInner>>,
-    mut writer: WriteHalf<TcpStream>,
-    mut waker: watch::Receiver<()>,
-) {
+async fn sender(client: Rc<RefCell<ClientInner>>, mut waker: watch::Receiver<()>) {
+    // Writing half of the current connection and the id of the connection
+    let mut writer = None;
     loop {
-        if client.borrow().state.is_closed() {
-            return;
-        }
-        // TODO: Set max drain
-        let data: Vec<_> = client
-            .borrow_mut()
-            .protocol
-            .drain_outgoing_data(None)
-            .collect();
+        let data: Vec<_> = {
+            let mut client = client.borrow_mut();
+            if client.state.is_closed() {
+                return;
+            }
+            if let Some(new_writer) = client.writer.take() {
+                writer = Some((client.connection_id, new_writer));
+            }
+            if client.state.is_connected() {
+                // TODO: Set max drain
+                client.protocol.drain_outgoing_data(None).collect()
+            } else {
+                // Outgoing data is sent once the connection is re-established
+                Vec::new()
+            }
+        };
         if data.is_empty() {
             // Wait for explicit wakeup, it should happen when there is new outgoing data
             waker.changed().await.expect("channel should be open");
         } else {
-            let result = writer.write_all(&data).await;
-            handle_result!(client.borrow_mut(), result);
+            let (connection_id, writer) = writer.as_mut().expect("is set while client is alive");
+            if let Err(err) = writer.write_all(&data).await {
+                connection_lost(&client, *connection_id, err.into());
+            }
         }
     }
 }
 
 /// Receiver work loop. Yields on each iteration and during awaits.
-async fn receiver(client: Rc<RefCell<ClientInner>>, mut reader: ReadHalf<TcpStream>) {
-    let mut hint = client.borrow().protocol.read_size_hint();
+///
+/// Re-establishes the connection once it is lost if the client has
+/// a reconnect policy.
+async fn receiver(client: Rc<RefCell<ClientInner>>, mut reader: ReadHalf<Transport>) {
+    // Number of consecutive reconnect attempts which didn't result in
+    // an authenticated connection
+    let mut failed_attempts = 0;
     loop {
-        if client.borrow().state.is_closed() {
+        let connection_id = client.borrow().connection_id;
+        if let Err(err) = receive(&client, &mut reader, &mut failed_attempts).await {
+            connection_lost(&client, connection_id, err);
+        }
+        if !matches!(client.borrow().state, State::Reconnecting) {
             return;
         }
+        match reconnect(&client, &mut failed_attempts).await {
+            Some(new_reader) => reader = new_reader,
+            None => return,
+        }
+    }
+}
+
+/// Reads and processes incoming messages until the client stops being alive.
+///
+/// Returns [`Error::ServerShutdown`] once the server announced its shutdown
+/// and the requests in flight either received their responses or timed out.
+async fn receive(
+    client: &RefCell<ClientInner>,
+    reader: &mut ReadHalf<Transport>,
+    failed_attempts: &mut u32,
+) -> Result<(), Error> {
+    let mut hint = client.borrow().protocol.read_size_hint();
+    loop {
+        {
+            let client = client.borrow();
+            match client.state {
+                State::Alive => {}
+                State::ShuttingDown if client.awaiting_response.is_empty() => {
+                    return Err(Error::ServerShutdown);
+                }
+                State::ShuttingDown => {}
+                _ => return Ok(()),
+            }
+        }
         match hint {
             SizeHint::Hint(size) => {
                 let mut buf = vec![0; size];
-                handle_result!(client.borrow_mut(), reader.read_exact(&mut buf).await);
+                read_exact(client, reader, &mut buf).await?;
+                let had_features = client.borrow().protocol.features().is_some();
                 let result = client
                     .borrow_mut()
                     .protocol
-                    .process_incoming(&mut Cursor::new(buf));
+                    .process_incoming(&mut Cursor::new(buf))?;
                 hint = client.borrow().protocol.read_size_hint();
-                let result = handle_result!(client.borrow_mut(), result);
+                if client.borrow().protocol.is_ready() {
+                    *failed_attempts = 0;
+                }
+                if !had_features && client.borrow().protocol.features().is_some() {
+                    watch_shutdown(client)?;
+                    // Wake the requests waiting for features to be negotiated
+                    let _ = client.borrow().state_waker.send(());
+                }
                 if let Some(sync) = result {
-                    let subscription = client.borrow_mut().awaiting_response.remove(&sync);
-                    if let Some(subscription) = subscription {
+                    client.borrow().interceptors.response_received(sync.0, size);
+                    let mut client = client.borrow_mut();
+                    if let Some(subscription) = client.awaiting_response.remove(&sync) {
                         subscription
                             .send(Ok(()))
                             .expect("cannot be closed at this point");
                     } else {
-                        log::warn!("received unwaited message for {sync:?}");
+                        if !client.discarded_responses.remove(&sync) {
+                            log::warn!("received unwaited message for {sync:?}");
+                        }
+                        client.protocol.drop_response(sync);
+                    }
+                }
+                loop {
+                    let push = client.borrow_mut().protocol.take_push();
+                    let push = match push {
+                        Some(push) => push,
+                        None => break,
+                    };
+                    // Pushes of the requests which are not sent with
+                    // `Client::call_with_pushes` are discarded
+                    if let Some(queue) = client.borrow_mut().pushes.get_mut(&push.sync) {
+                        queue.push(push.data);
+                    }
+                }
+                loop {
+                    let event = client.borrow_mut().protocol.take_event();
+                    match event {
+                        Some(event) => handle_event(client, event)?,
+                        None => break,
                     }
                 }
-                wake_sender(&client).unwrap();
+                wake_sender(client).unwrap();
             }
             SizeHint::FirstU32 => {
                 // Read 5 bytes, 1st is a marker
                 let mut buf = vec![0; 5];
-                handle_result!(client.borrow_mut(), reader.read_exact(&mut buf).await);
-                let result = rmp::decode::read_u32(&mut Cursor::new(buf));
-                let mut client_ref = client.borrow_mut();
-                let new_hint = handle_result!(client_ref, result.map_err(ProtocolError::from));
+                read_exact(client, reader, &mut buf).await?;
+                let new_hint =
+                    rmp::decode::read_u32(&mut Cursor::new(buf)).map_err(ProtocolError::from)?;
                 if new_hint > 0 {
                     hint = SizeHint::Hint(new_hint as usize)
                 } else {
-                    handle_result!(
-                        client_ref,
-                        Err(Error::Other("unexpected zero message length".to_owned()))
-                    )
+                    return Err(Error::Other("unexpected zero message length".to_owned()));
                 }
             }
         }
     }
 }
 
-#[cfg(feature = "internal_test")]
-mod tests {
-    use super::*;
-    use crate::fiber::r#async::timeout::IntoTimeout as _;
-    use crate::space::Space;
+/// Reads exactly `buf.len()` bytes. Fails with [`Error::ServerShutdown`] if
+/// the server is shutting down and the shutdown timeout expires.
+async fn read_exact(
+    client: &RefCell<ClientInner>,
+    reader: &mut ReadHalf<Transport>,
+    buf: &mut [u8],
+) -> Result<(), Error> {
+    let deadline = client.borrow().shutdown_deadline;
+    match deadline {
+        Some(deadline) => {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match reader.read_exact(buf).timeout(timeout).await {
+                Ok(()) => Ok(()),
+                Err(timeout::Error::Expired) => Err(Error::ServerShutdown),
+                Err(timeout::Error::Failed(e)) => Err(e.into()),
+            }
+        }
+        None => Ok(reader.read_exact(buf).await?),
+    }
+}
+
+/// Subscribes to the server shutdown notifications, if the server supports
+/// them. Is called every time the connection is established.
+fn watch_shutdown(client: &RefCell<ClientInner>) -> Result<(), Error> {
+    let mut client = client.borrow_mut();
+    let supported = client
+        .protocol
+        .features()
+        .map_or(false, |f| f.supports(Feature::Watchers));
+    // A user subscription is renewed on reconnect already
+    if supported && !client.watchers.contains_key(SHUTDOWN_KEY) {
+        client.protocol.send_request(&Watch { key: SHUTDOWN_KEY })?;
+    }
+    Ok(())
+}
+
+/// Delivers the `event` to the receivers of the corresponding [`Client::watch`]
+/// channel and acknowledges it so that the server sends the next one.
+/// Cancels the subscription if all the receivers were dropped.
+///
+/// Starts the graceful shutdown once the server broadcasts `box.shutdown`.
+fn handle_event(client: &RefCell<ClientInner>, event: Event) -> Result<(), Error> {
+    let Event { key, data } = event;
+    let mut client = client.borrow_mut();
+    let client = &mut *client;
+    // The client is subscribed to the shutdown notifications on its own
+    let is_shutdown = key == SHUTDOWN_KEY;
+    match client.watchers.get(&key) {
+        Some(sender) if !sender.is_closed() => {
+            // Receivers can't hold a reference to the value at this point
+            let _ = sender.send(Some(data.clone()));
+            client.protocol.send_request(&Watch { key: &key })?;
+        }
+        Some(_) if is_shutdown => {
+            client.watchers.remove(&key);
+            client.protocol.send_request(&Watch { key: &key })?;
+        }
+        Some(_) => {
+            client.watchers.remove(&key);
+            client.protocol.send_request(&Unwatch { key: &key })?;
+        }
+        None if is_shutdown => {
+            client.protocol.send_request(&Watch { key: &key })?;
+        }
+        None => {
+            // The subscription has already been cancelled
+        }
+    }
+    // `box.shutdown` is set to `true` once the server starts shutting down
+    if is_shutdown && data.0 == [0xc3] && client.state.is_alive() {
+        client.state = State::ShuttingDown;
+        client.shutdown_deadline = Some(Instant::now() + client.shutdown_timeout);
+        // Wake the requests waiting for the connection
+        let _ = client.state_waker.send(());
+    }
+    Ok(())
+}
+
+/// Re-establishes the lost connection according to the client's reconnect policy.
+///
+/// Returns reading half of the new connection or `None` if the client was
+/// closed or the policy gave up.
+async fn reconnect(
+    client: &RefCell<ClientInner>,
+    failed_attempts: &mut u32,
+) -> Option<ReadHalf<Transport>> {
+    let (policy, uri, mut state_changed) = {
+        let client = client.borrow();
+        let policy = client
+            .reconnect
+            .clone()
+            .expect("client can only be reconnecting if it has a policy");
+        let state_changed = client.state_waker.subscribe();
+        (policy, client.uri.clone(), state_changed)
+    };
+    let mut last_error = None;
+    loop {
+        if matches!(policy.max_attempts, Some(max) if *failed_attempts >= max) {
+            let mut err = format!("failed to reconnect after {} attempts", failed_attempts);
+            if let Some(last_error) = last_error {
+                err = format!("{}: {}", err, last_error);
+            }
+            let mut client = client.borrow_mut();
+            client.state = State::ClosedWithError(err);
+            client.watchers.clear();
+            let _ = client.sender_waker.send(());
+            let _ = client.state_waker.send(());
+            return None;
+        }
+
+        // Wait for the delay, but wake up early if the client gets closed
+        let delay = policy.delay(*failed_attempts);
+        *failed_attempts += 1;
+        let _ = state_changed.changed().timeout(delay).await;
+        if client.borrow().state.is_closed() {
+            return None;
+        }
+
+        match Transport::connect(&uri).await {
+            Ok(stream) => {
+                let mut client = client.borrow_mut();
+                if client.state.is_closed() {
+                    return None;
+                }
+                client.close_token = Some(stream.close_token());
+                let (reader, writer) = stream.split();
+                client.writer = Some(writer);
+                client.connection_id += 1;
+                client.prepared_statements.clear();
+                client.protocol.reset();
+                let client = &mut *client;
+                // Renew the subscriptions, requests are sent once the
+                // connection is authenticated
+                client.watchers.retain(|_, sender| !sender.is_closed());
+                for key in client.watchers.keys() {
+                    if let Err(err) = client.protocol.send_request(&Watch { key }) {
+                        log::warn!("failed to renew subscription to {:?}: {}", key, err);
+                    }
+                }
+                // Remote schema could have changed while the client was disconnected
+                client.schema = None;
+                client.state = State::Alive;
+                let _ = client.sender_waker.send(());
+                let _ = client.state_waker.send(());
+                return Some(reader);
+            }
+            Err(err) => {
+                log::warn!("failed to reconnect to {}: {}", uri, err);
+                last_error = Some(err);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber::r#async::timeout;
+    use crate::index::IndexType;
+    use crate::network::interceptor::{RequestInfo, RequestKind, ResponseInfo};
+    use crate::space::{FieldType, Space};
     use crate::test::util::TARANTOOL_LISTEN;
 
     async fn test_client() -> Client {
@@ -400,6 +1501,7 @@ mod tests {
             TARANTOOL_LISTEN,
             protocol::Config {
                 creds: Some(("test_user".to_owned(), "password".to_owned())),
+                ..Default::default()
             },
         )
         .timeout(Duration::from_secs(3))
@@ -416,6 +1518,32 @@ mod tests {
         });
     }
 
+    async fn test_client_with_reconnect(in_flight: reconnect::InFlight) -> Client {
+        Client::connect_with_reconnect(
+            "localhost",
+            TARANTOOL_LISTEN,
+            protocol::Config {
+                creds: Some(("test_user".to_owned(), "password".to_owned())),
+                ..Default::default()
+            },
+            reconnect::Policy {
+                backoff: reconnect::Backoff::Fixed(Duration::from_millis(10)),
+                max_attempts: Some(3),
+                in_flight,
+                ..Default::default()
+            },
+        )
+        .timeout(Duration::from_secs(3))
+        .await
+        .unwrap()
+    }
+
+    /// Simulates a network failure.
+    fn break_connection(client: &Client) {
+        let client = client.inner.borrow();
+        client.close_token.as_ref().unwrap().shutdown().unwrap();
+    }
+
     #[crate::test(tarantool = "crate")]
     fn connect_failure() {
         fiber::block_on(async {
@@ -425,6 +1553,69 @@ mod tests {
         });
     }
 
+    #[crate::test(tarantool = "crate")]
+    fn connect_uri() {
+        use crate::test::util::unix_socket_proxy;
+
+        let path = std::env::temp_dir().join(format!("network-client-{}.sock", std::process::id()));
+        unix_socket_proxy(&path, TARANTOOL_LISTEN).unwrap();
+        fiber::block_on(async {
+            let config = protocol::Config {
+                creds: Some(("test_user".to_owned(), "password".to_owned())),
+                ..Default::default()
+            };
+            let addr = format!("unix/:{}", path.display());
+            let client = Client::connect_uri(&addr, config.clone())
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+
+            let addr = format!("localhost:{}", TARANTOOL_LISTEN);
+            let client = Client::connect_uri(&addr, config.clone())
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+
+            let err = Client::connect_uri("unix/:/nonexistent/tarantool.sock", config.clone())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::UnixConnect(_)));
+
+            let err = Client::connect_uri("localhost", config).await.unwrap_err();
+            assert!(matches!(err, Error::Uri(uri::Error::MissingPort(_))));
+        });
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn auth_method_unsupported() {
+        use crate::auth::AuthMethod;
+
+        fiber::block_on(async {
+            let client = Client::connect_with_config(
+                "localhost",
+                TARANTOOL_LISTEN,
+                protocol::Config {
+                    creds: Some(("test_user".to_owned(), "password".to_owned())),
+                    auth_method: AuthMethod::PapSha256,
+                },
+            )
+            .timeout(Duration::from_secs(3))
+            .await
+            .unwrap();
+            let err = client
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err()
+                .to_string();
+            let expected = ProtocolError::UnsupportedAuthMethod(AuthMethod::PapSha256).to_string();
+            assert!(err.contains(&expected), "{}", err);
+        });
+    }
+
     #[crate::test(tarantool = "crate")]
     fn ping() {
         fiber::block_on(async {
@@ -449,6 +1640,104 @@ mod tests {
         fiber_b.join();
     }
 
+    #[crate::test(tarantool = "crate")]
+    fn connection_lost() {
+        fiber::block_on(async {
+            let client = test_client().await;
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+
+            break_connection(&client);
+            let err = client
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                dbg!(err),
+                timeout::Error::Failed(Error::ClosedWithErr(_))
+            ));
+            let err = client
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                dbg!(err),
+                timeout::Error::Failed(Error::ClosedWithErr(_))
+            ));
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn reconnect_fail_in_flight() {
+        fiber::block_on(async {
+            let client = test_client_with_reconnect(reconnect::InFlight::Fail).await;
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+
+            break_connection(&client);
+            let err = client
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                dbg!(err),
+                timeout::Error::Failed(Error::ConnectionLost(_))
+            ));
+
+            // Requests wait for the client to reconnect and re-authenticate
+            let result = client
+                .eval("return box.session.user()", &())
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(
+                result.unwrap().decode::<(String,)>().unwrap(),
+                ("test_user".to_owned(),)
+            );
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn reconnect_retry_in_flight() {
+        fiber::block_on(async {
+            let client = test_client_with_reconnect(reconnect::InFlight::Retry).await;
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+
+            for _ in 0..3 {
+                break_connection(&client);
+                client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+            }
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn reconnect_give_up() {
+        fiber::block_on(async {
+            let client = test_client_with_reconnect(reconnect::InFlight::Retry).await;
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+
+            // Can be any other unused port
+            client.inner.borrow_mut().uri = Uri::Tcp {
+                host: "localhost".into(),
+                port: 3300,
+            };
+            break_connection(&client);
+            let err = client
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                dbg!(&err),
+                timeout::Error::Failed(Error::ClosedWithErr(_))
+            ));
+            assert!(err
+                .to_string()
+                .contains("failed to reconnect after 3 attempts"));
+        });
+    }
+
     #[crate::test(tarantool = "crate")]
     fn execute() {
         Space::find("test_s1")
@@ -468,7 +1757,7 @@ mod tests {
                 .timeout(Duration::from_secs(3))
                 .await
                 .unwrap();
-            assert!(result.len() >= 2);
+            assert!(result.rows().len() >= 2);
 
             let result = client
                 .execute(r#"SELECT * FROM "test_s1" WHERE "id" = ?"#, &(6002,), None)
@@ -476,11 +1765,185 @@ mod tests {
                 .await
                 .unwrap();
 
-            assert_eq!(result.len(), 1);
+            assert_eq!(result.rows().len(), 1);
             assert_eq!(
-                result.get(0).unwrap().decode::<(u64, String)>().unwrap(),
+                result.rows()[0].decode::<(u64, String)>().unwrap(),
                 (6002, "6002".to_string())
             );
+            let columns: Vec<_> = result
+                .metadata()
+                .iter()
+                .map(|c| (c.name.as_str(), c.field_type.as_str()))
+                .collect();
+            assert_eq!(columns, [("id", "unsigned"), ("text", "string")]);
+            assert!(result.info().is_none());
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn execute_sql_response() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Row {
+            text: String,
+            id: u64,
+        }
+
+        fiber::block_on(async {
+            let client = test_client().await;
+
+            let result = client
+                .execute(
+                    r#"INSERT INTO "test_s1" VALUES (6401, '6401'), (6402, '6402')"#,
+                    &(),
+                    None,
+                )
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(result.row_count(), Some(2));
+            assert!(result.autoincrement_ids().is_empty());
+            assert!(result.metadata().is_empty());
+            assert!(result.rows().is_empty());
+
+            // Rows are matched with the struct fields by the column names
+            let result = client
+                .execute(
+                    r#"SELECT "text", "id" FROM "test_s1" WHERE "id" BETWEEN ? AND ? ORDER BY "id""#,
+                    &(6401, 6402),
+                    None,
+                )
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(
+                result.decode_rows::<Row>().unwrap(),
+                [
+                    Row {
+                        text: "6401".into(),
+                        id: 6401
+                    },
+                    Row {
+                        text: "6402".into(),
+                        id: 6402
+                    },
+                ]
+            );
+
+            let result = client
+                .execute(
+                    r#"SELECT * FROM "test_s1" WHERE "id" BETWEEN ? AND ?"#,
+                    &(6401, 6402),
+                    Some(1),
+                )
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(result.rows().len(), 1);
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn prepare() {
+        fn stmt_count() -> usize {
+            crate::lua_state()
+                .eval("return box.info.sql().cache.stmt_count")
+                .unwrap()
+        }
+
+        Space::find("test_s1")
+            .unwrap()
+            .insert(&(6501, "6501"))
+            .unwrap();
+        Space::find("test_s1")
+            .unwrap()
+            .insert(&(6502, "6502"))
+            .unwrap();
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let initial_count = stmt_count();
+
+            let stmt = client
+                .prepare(r#"SELECT "text" FROM "test_s1" WHERE "id" = ?"#)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(stmt_count(), initial_count + 1);
+            assert_eq!(stmt.response().bind_count(), Some(1));
+            assert_eq!(stmt.response().bind_metadata()[0].name, "?");
+            assert_eq!(stmt.response().metadata().len(), 1);
+            assert_eq!(stmt.response().metadata()[0].name, "text");
+
+            for id in [6501, 6502] {
+                let result = stmt
+                    .execute(&(id,), None)
+                    .timeout(Duration::from_secs(3))
+                    .await
+                    .unwrap();
+                assert_eq!(result.rows().len(), 1);
+                assert_eq!(
+                    result.rows()[0].decode::<(String,)>().unwrap(),
+                    (id.to_string(),)
+                );
+            }
+
+            // Statement is deallocated on the server once dropped
+            drop(stmt);
+            fiber::sleep(Duration::from_millis(100));
+            assert_eq!(stmt_count(), initial_count);
+            assert!(client.inner.borrow().discarded_responses.is_empty());
+
+            let err = client
+                .prepare("SELECT * FROM unexistent_space")
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                timeout::Error::Failed(Error::Protocol(ProtocolError::Response(_)))
+            ));
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn prepare_twice() {
+        fn stmt_count() -> usize {
+            crate::lua_state()
+                .eval("return box.info.sql().cache.stmt_count")
+                .unwrap()
+        }
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let initial_count = stmt_count();
+
+            let sql = r#"SELECT "text" FROM "test_s1" WHERE "id" = ?"#;
+            let stmt_1 = client
+                .prepare(sql)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            let stmt_2 = client
+                .prepare(sql)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(stmt_1.id(), stmt_2.id());
+            assert_eq!(stmt_count(), initial_count + 1);
+
+            // The statement is still prepared while it has handles
+            drop(stmt_1);
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+            assert_eq!(stmt_count(), initial_count + 1);
+            stmt_2
+                .execute(&(6501,), None)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+
+            drop(stmt_2);
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+            assert_eq!(stmt_count(), initial_count);
         });
     }
 
@@ -498,6 +1961,59 @@ mod tests {
         });
     }
 
+    #[crate::test(tarantool = "crate")]
+    fn call_with_pushes() {
+        fiber::block_on(async {
+            let client = test_client().await;
+
+            let mut messages = client
+                .call_with_pushes("test_pushes", &(3,))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            for i in 1..=3 {
+                let message = messages
+                    .next()
+                    .timeout(Duration::from_secs(3))
+                    .await
+                    .unwrap();
+                match message {
+                    Some(CallMessage::Push(data)) => {
+                        assert_eq!(rmp_serde::from_slice::<i32>(&data).unwrap(), i);
+                    }
+                    other => panic!("unexpected message: {:?}", other),
+                }
+            }
+            let message = messages
+                .next()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            match message {
+                Some(CallMessage::Response(result)) => {
+                    assert_eq!(result.unwrap().decode::<(i32,)>().unwrap(), (3,));
+                }
+                other => panic!("unexpected message: {:?}", other),
+            }
+            assert!(messages.next().await.unwrap().is_none());
+
+            // Pushes are ignored by regular calls
+            let result = client
+                .call("test_pushes", &(2,))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(result.unwrap().decode::<(i32,)>().unwrap(), (2,));
+            assert!(client.inner.borrow().pushes.is_empty());
+
+            // Messages are discarded once dropped
+            let messages = client.call_with_pushes("test_pushes", &(2,)).await.unwrap();
+            drop(messages);
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+            assert!(client.inner.borrow().pushes.is_empty());
+        });
+    }
+
     #[crate::test(tarantool = "crate")]
     fn invalid_call() {
         fiber::block_on(async {
@@ -513,6 +2029,738 @@ mod tests {
         });
     }
 
+    #[derive(Default)]
+    struct Recorder {
+        requests: RefCell<Vec<RequestInfo>>,
+        responses: RefCell<Vec<(RequestInfo, usize, Option<String>)>>,
+    }
+
+    impl Interceptor for Recorder {
+        fn before_request(&self, request: &RequestInfo) {
+            self.requests.borrow_mut().push(request.clone());
+        }
+
+        fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+            self.responses.borrow_mut().push((
+                request.clone(),
+                response.size,
+                response.error.map(ToString::to_string),
+            ));
+        }
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn interceptor() {
+        fiber::block_on(async {
+            let client = test_client().await;
+            let space = client.space("test_s1").await.unwrap().unwrap();
+            let recorder = Rc::new(Recorder::default());
+            client.add_interceptor(recorder.clone());
+
+            client
+                .call("test_stored_proc", &(1, 2))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            client
+                .call("unexistent_proc", &())
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            space
+                .get(&(6199,))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+
+            let requests = recorder.requests.borrow().clone();
+            let responses = recorder.responses.borrow().clone();
+            assert_eq!(requests.len(), 3);
+            assert_eq!(responses.len(), 3);
+            for (request, (response_to, size, _)) in requests.iter().zip(&responses) {
+                assert_eq!(request, response_to);
+                assert!(request.size > 0);
+                assert!(*size > 0);
+            }
+
+            assert_eq!(requests[0].kind, RequestKind::Call);
+            assert_eq!(requests[0].name.as_deref(), Some("test_stored_proc"));
+            assert_eq!(requests[0].space_id, None);
+            assert_eq!(responses[0].2, None);
+
+            assert_eq!(requests[1].name.as_deref(), Some("unexistent_proc"));
+            assert!(requests[1].sync > requests[0].sync);
+            assert_eq!(
+                responses[1].2.as_deref(),
+                Some("protocol error: service responded with error: Procedure 'unexistent_proc' is not defined")
+            );
+
+            assert_eq!(requests[2].kind, RequestKind::Select);
+            assert_eq!(requests[2].space_id, Some(space.id()));
+            assert_eq!(requests[2].name.as_deref(), Some("test_s1"));
+            assert_eq!(responses[2].2, None);
+
+            // Failed requests are reported once the connection is lost
+            let (sync, _response) = client.start_request(&Ping).await.unwrap();
+            break_connection(&client);
+            client.ping().await.unwrap_err();
+            let responses = recorder.responses.borrow();
+            let (_, size, error) = responses
+                .iter()
+                .find(|(request, ..)| request.sync == sync.0)
+                .unwrap();
+            assert_eq!(*size, 0);
+            assert!(error.as_ref().unwrap().starts_with("closed with error"));
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn extended_error() {
+        fiber::block_on(async {
+            let client = test_client().await;
+
+            let err = client
+                .eval(
+                    "local e = box.error.new{type = 'MyError', reason = 'boom'}
+                    e:set_prev(box.error.new(box.error.ILLEGAL_PARAMS, 'bad'))
+                    error(e)",
+                    &(),
+                )
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            let err = match err {
+                timeout::Error::Failed(Error::Protocol(ProtocolError::Response(e))) => e,
+                e => panic!("unexpected error: {}", e),
+            };
+            assert_eq!(err.message(), "boom");
+            let extended = err.extended().unwrap();
+            assert_eq!(extended.error_type, "CustomError");
+            assert_eq!(extended.message, "boom");
+            assert_eq!(extended.fields["custom_type"], rmpv::Value::from("MyError"));
+            assert!(extended.line > 0);
+            let cause = extended.cause().unwrap();
+            assert_eq!(cause.error_type, "ClientError");
+            assert_eq!(cause.code, TarantoolErrorCode::IllegalParams as u32);
+            assert_eq!(cause.message, "Illegal parameters, bad");
+            assert!(cause.cause().is_none());
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn select() {
+        fiber::block_on(async {
+            let client = test_client().await;
+            let space = client.space("test_s2").await.unwrap().unwrap();
+
+            let result = space
+                .select(IteratorType::LE, &(2,), None, 0)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            let ids: Vec<u32> = result
+                .iter()
+                .map(|t| t.field::<u32>(0).unwrap().unwrap())
+                .collect();
+            assert_eq!(ids, vec![2, 1]);
+
+            let result = space
+                .select(IteratorType::All, &(), Some(3), 5)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            let ids: Vec<u32> = result
+                .iter()
+                .map(|t| t.field::<u32>(0).unwrap().unwrap())
+                .collect();
+            assert_eq!(ids, vec![6, 7, 8]);
+
+            let index = space.index("idx_1").await.unwrap().unwrap();
+            let tuple = index
+                .get(&("key_16",))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(tuple.field::<u32>(0).unwrap(), Some(16));
+
+            assert!(space.index("unexistent_idx").await.unwrap().is_none());
+            assert!(client.space("unexistent_space").await.unwrap().is_none());
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn select_page() {
+        use crate::network::protocol::Feature;
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let index = client
+                .space("test_s2")
+                .await
+                .unwrap()
+                .unwrap()
+                .primary_key();
+
+            let features = client.protocol_features().await.unwrap();
+            if !features.supports(Feature::Pagination) {
+                let err = index
+                    .select_page(IteratorType::All, &(), None, 7)
+                    .timeout(Duration::from_secs(3))
+                    .await
+                    .unwrap_err();
+                assert!(matches!(
+                    err,
+                    timeout::Error::Failed(Error::Protocol(ProtocolError::UnsupportedFeature(
+                        Feature::Pagination
+                    )))
+                ));
+                return;
+            }
+
+            let mut ids = vec![];
+            let mut after = None;
+            loop {
+                let (tuples, position) = index
+                    .select_page(IteratorType::GE, &(3,), after.as_ref(), 7)
+                    .timeout(Duration::from_secs(3))
+                    .await
+                    .unwrap();
+                if tuples.is_empty() {
+                    break;
+                }
+                assert!(tuples.len() <= 7);
+                ids.extend(tuples.iter().map(|t| t.field::<u32>(0).unwrap().unwrap()));
+                after = position;
+            }
+            assert_eq!(ids, (3..=20).collect::<Vec<_>>());
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn insert_replace_delete() {
+        let local_space = Space::find("test_s1").unwrap();
+        fiber::block_on(async {
+            let client = test_client().await;
+            let space = client.space("test_s1").await.unwrap().unwrap();
+
+            let result = space
+                .insert(&(6101, "foo"))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(
+                result.unwrap().decode::<(u32, String)>().unwrap(),
+                (6101, "foo".into())
+            );
+            let err = space
+                .insert(&(6101, "foo"))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("Duplicate key exists"));
+
+            space
+                .replace(&(6101, "bar"))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            let tuple = local_space.get(&(6101,)).unwrap().unwrap();
+            assert_eq!(
+                tuple.decode::<(u32, String)>().unwrap(),
+                (6101, "bar".into())
+            );
+
+            let result = space
+                .delete(&(6101,))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(
+                result.unwrap().decode::<(u32, String)>().unwrap(),
+                (6101, "bar".into())
+            );
+            assert!(local_space.get(&(6101,)).unwrap().is_none());
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn batch() {
+        use crate::network::protocol::api::{Insert, Select};
+
+        #[derive(Debug)]
+        enum Response {
+            Row(Option<Tuple>),
+            Rows(Vec<Tuple>),
+        }
+
+        impl From<Option<Tuple>> for Response {
+            fn from(row: Option<Tuple>) -> Self {
+                Self::Row(row)
+            }
+        }
+
+        impl From<Vec<Tuple>> for Response {
+            fn from(rows: Vec<Tuple>) -> Self {
+                Self::Rows(rows)
+            }
+        }
+
+        let local_space = Space::find("test_s1").unwrap();
+        let space_id = local_space.id();
+        let records: Vec<_> = (6301..6401).map(|id| (id, id.to_string())).collect();
+        fiber::block_on(async {
+            let client = test_client().await;
+
+            let mut batch = client.batch::<Response>().max_in_flight(8);
+            for value in &records {
+                batch.push(Insert { space_id, value });
+            }
+            let duplicate = batch.push(Insert {
+                space_id,
+                value: &records[0],
+            });
+            let select = batch.push(Select {
+                space_id,
+                index_id: 0,
+                limit: 200,
+                offset: 0,
+                iterator_type: IteratorType::GE,
+                key: &(6301,),
+            });
+            assert_eq!(batch.len(), 102);
+
+            let mut results = batch.execute_unordered();
+            let mut inserted = 0;
+            while let Some((index, result)) = results.next().await {
+                assert!(client.pending_requests() <= 8);
+                if index == duplicate {
+                    assert!(result
+                        .unwrap_err()
+                        .to_string()
+                        .contains("Duplicate key exists"));
+                } else if index == select {
+                    // The select is only sent once most of the inserts are done
+                    assert!(matches!(result.unwrap(), Response::Rows(rows) if rows.len() > 90));
+                } else {
+                    let row = match result.unwrap() {
+                        Response::Row(row) => row.unwrap(),
+                        other => panic!("unexpected response: {:?}", other),
+                    };
+                    assert_eq!(row.decode::<(u32, String)>().unwrap(), records[index]);
+                    inserted += 1;
+                }
+            }
+            assert_eq!(inserted, 100);
+            assert_eq!(results.remaining(), 0);
+            drop(results);
+
+            let keys: Vec<_> = records.iter().map(|(id, _)| (*id,)).collect();
+            let mut batch = client.batch::<Option<Tuple>>();
+            for key in &keys {
+                batch.push(Delete {
+                    space_id,
+                    index_id: 0,
+                    key,
+                });
+            }
+            let results = batch.execute().await;
+            for (result, record) in results.into_iter().zip(&records) {
+                let row = result.unwrap().unwrap();
+                assert_eq!(&row.decode::<(u32, String)>().unwrap(), record);
+            }
+        });
+        assert!(local_space.get(&(6301,)).unwrap().is_none());
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn batch_reconnect() {
+        fiber::block_on(async {
+            let client = test_client_with_reconnect(reconnect::InFlight::Retry).await;
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+
+            let mut batch = client.batch::<()>().max_in_flight(4);
+            for _ in 0..20 {
+                batch.push(Ping);
+            }
+            let mut results = batch.execute_unordered();
+            let (_, result) = results.next().await.unwrap();
+            result.unwrap();
+
+            // Requests in flight are sent again after reconnect
+            break_connection(&client);
+            let mut count = 1;
+            while let Some((_, result)) = results.next().await {
+                result.unwrap();
+                count += 1;
+            }
+            assert_eq!(count, 20);
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn update_upsert() {
+        let local_space = Space::find("test_s1").unwrap();
+        local_space.insert(&(6201, "original")).unwrap();
+        fiber::block_on(async {
+            let client = test_client().await;
+            let space = client.space("test_s1").await.unwrap().unwrap();
+
+            let result = space
+                .update(&(6201,), &[("=", 1, "updated")])
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(
+                result.unwrap().decode::<(u32, String)>().unwrap(),
+                (6201, "updated".into())
+            );
+
+            // Tuple exists - operations are applied
+            space
+                .upsert(&(6201, "new"), &[("=", 1, "upserted")])
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            // Tuple doesn't exist - it is inserted
+            space
+                .upsert(&(6202, "new"), &[("=", 1, "upserted")])
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+        });
+        let tuple = local_space.get(&(6201,)).unwrap().unwrap();
+        assert_eq!(
+            tuple.decode::<(u32, String)>().unwrap(),
+            (6201, "upserted".into())
+        );
+        let tuple = local_space.get(&(6202,)).unwrap().unwrap();
+        assert_eq!(
+            tuple.decode::<(u32, String)>().unwrap(),
+            (6202, "new".into())
+        );
+        local_space.delete(&(6201,)).unwrap();
+        local_space.delete(&(6202,)).unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn schema() {
+        let local_space = Space::find("test_s2").unwrap();
+        fiber::block_on(async {
+            let client = test_client().await;
+            let schema = client
+                .schema()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+
+            let space = schema.space("test_s2").unwrap();
+            assert_eq!(space.id, local_space.id());
+            assert_eq!(space.format.len(), 5);
+            assert_eq!(space.format[0].name, "id");
+            assert_eq!(space.format[0].field_type, FieldType::Unsigned);
+            assert_eq!(space.field_index("value"), Some(2));
+            assert!(schema.space_by_id(space.id).is_some());
+
+            let index = schema.index(space.id, "idx_1").unwrap();
+            assert_eq!(index.id, 1);
+            assert_eq!(index.index_type, IndexType::Hash);
+            assert!(index.unique);
+            let index = schema.index(space.id, "idx_3").unwrap();
+            assert!(!index.unique);
+
+            assert!(schema.space("_vspace").is_some());
+            assert!(schema.space("unexistent_space").is_none());
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn schema_reload() {
+        fiber::block_on(async {
+            let client = test_client().await;
+            let version = client.schema().await.unwrap().version();
+            assert!(client.space("test_client_tmp").await.unwrap().is_none());
+
+            // Schema change is noticed from the response header
+            client
+                .eval("box.schema.space.create('test_client_tmp')", &())
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            let space = client.space("test_client_tmp").await.unwrap().unwrap();
+            let new_version = client.schema().await.unwrap().version();
+            assert!(new_version > version);
+            let metadata = space.metadata().await.unwrap().unwrap();
+            assert_eq!(metadata.name, "test_client_tmp");
+            assert!(metadata.format.is_empty());
+
+            // Schema change is noticed from `WrongSchemaVersion` error
+            let space = client.space("test_s1").await.unwrap().unwrap();
+            crate::lua_state()
+                .exec("box.space.test_client_tmp:drop()")
+                .unwrap();
+            space
+                .replace(&(6301, "versioned"))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert!(client.schema().await.unwrap().version() > new_version);
+            assert!(client.space("test_client_tmp").await.unwrap().is_none());
+        });
+        Space::find("test_s1").unwrap().delete(&(6301,)).unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn stream_transaction() {
+        use crate::network::protocol::Feature;
+        use crate::transaction::IsolationLevel;
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let features = client.protocol_features().await.unwrap();
+            if !features.supports(Feature::Transactions) {
+                return;
+            }
+            let stream = client.stream();
+            let other_stream = client.stream();
+            assert_ne!(stream.id(), other_stream.id());
+
+            let is_in_txn = |stream: Stream| async move {
+                let result = stream
+                    .eval("return box.is_in_txn()", &())
+                    .timeout(Duration::from_secs(3))
+                    .await
+                    .unwrap();
+                result.unwrap().decode::<(bool,)>().unwrap().0
+            };
+
+            stream
+                .begin(IsolationLevel::Default, Some(Duration::from_secs(10)))
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert!(is_in_txn(stream.clone()).await);
+            assert!(!is_in_txn(other_stream.clone()).await);
+            stream
+                .rollback()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert!(!is_in_txn(stream.clone()).await);
+
+            stream
+                .begin(IsolationLevel::BestEffort, None)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert!(is_in_txn(stream.clone()).await);
+            stream
+                .commit()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert!(!is_in_txn(stream).await);
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn protocol_features() {
+        use crate::network::protocol::Feature;
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let features = client
+                .protocol_features()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            // The features are negotiated since 2.10
+            let id_supported: bool = crate::lua_state()
+                .eval(
+                    "local major, minor = _TARANTOOL:match('^(%d+)%.(%d+)')
+                    return tonumber(major) * 100 + tonumber(minor) >= 210",
+                )
+                .unwrap();
+            if id_supported {
+                assert!(features.version() > 0);
+                assert!(features.supports(Feature::Streams));
+                assert!(features.supports(Feature::Transactions));
+                assert!(features.supports(Feature::Watchers));
+            } else {
+                assert_eq!(features, protocol::ProtocolFeatures::default());
+            }
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn unsupported_feature() {
+        use crate::network::protocol::Feature;
+        use crate::transaction::IsolationLevel;
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let features = client.protocol_features().await.unwrap();
+            if features.supports(Feature::Streams) {
+                return;
+            }
+            let stream = client.stream();
+            let err = stream
+                .begin(IsolationLevel::Default, None)
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                timeout::Error::Failed(Error::Protocol(ProtocolError::UnsupportedFeature(
+                    Feature::Transactions
+                )))
+            ));
+            let err = stream
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                timeout::Error::Failed(Error::Protocol(ProtocolError::UnsupportedFeature(
+                    Feature::Streams
+                )))
+            ));
+            // The connection is still usable
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn watch_unsupported() {
+        use crate::network::protocol::Feature;
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let features = client.protocol_features().await.unwrap();
+            if features.supports(Feature::Watchers) {
+                return;
+            }
+            let err = client
+                .watch("foo")
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                timeout::Error::Failed(Error::Protocol(ProtocolError::UnsupportedFeature(
+                    Feature::Watchers
+                )))
+            ));
+        });
+    }
+
+    async fn next_event<T: serde::de::DeserializeOwned>(
+        rx: &mut watch::Receiver<Option<RawByteBuf>>,
+    ) -> T {
+        rx.changed().timeout(Duration::from_secs(3)).await.unwrap();
+        let data = rx.get_cloned().unwrap();
+        rmp_serde::from_slice(&data).unwrap()
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn watch() {
+        use crate::network::protocol::Feature;
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let features = client.protocol_features().await.unwrap();
+            if !features.supports(Feature::Watchers) {
+                return;
+            }
+            let mut rx = client
+                .watch("test_watch")
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert!(rx.get_cloned().is_none());
+            // Current value is sent right away
+            assert_eq!(next_event::<Option<i32>>(&mut rx).await, None);
+
+            crate::lua_state()
+                .exec("box.broadcast('test_watch', 42)")
+                .unwrap();
+            assert_eq!(next_event::<Option<i32>>(&mut rx).await, Some(42));
+
+            let mut other_rx = client.watch("test_watch").await.unwrap();
+            crate::lua_state()
+                .exec("box.broadcast('test_watch', 'foo')")
+                .unwrap();
+            assert_eq!(next_event::<String>(&mut rx).await, "foo");
+            assert_eq!(next_event::<String>(&mut other_rx).await, "foo");
+
+            // The subscription is cancelled with the next event
+            drop(rx);
+            drop(other_rx);
+            crate::lua_state()
+                .exec("box.broadcast('test_watch', 13)")
+                .unwrap();
+            fiber::sleep(Duration::from_millis(100));
+            assert!(client.inner.borrow().watchers.is_empty());
+            crate::lua_state()
+                .exec("box.broadcast('test_watch', nil)")
+                .unwrap();
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn watch_after_reconnect() {
+        use crate::network::protocol::Feature;
+
+        fiber::block_on(async {
+            let client = test_client_with_reconnect(reconnect::InFlight::Fail).await;
+            let features = client.protocol_features().await.unwrap();
+            if !features.supports(Feature::Watchers) {
+                return;
+            }
+            let mut rx = client
+                .watch("test_watch_reconnect")
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            assert_eq!(next_event::<Option<i32>>(&mut rx).await, None);
+
+            break_connection(&client);
+            // Current value is sent again after the subscription is renewed
+            assert_eq!(next_event::<Option<i32>>(&mut rx).await, None);
+
+            crate::lua_state()
+                .exec("box.broadcast('test_watch_reconnect', 42)")
+                .unwrap();
+            assert_eq!(next_event::<Option<i32>>(&mut rx).await, Some(42));
+            crate::lua_state()
+                .exec("box.broadcast('test_watch_reconnect', nil)")
+                .unwrap();
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn watch_closed() {
+        use crate::network::protocol::Feature;
+
+        fiber::block_on(async {
+            let client = test_client().await;
+            let features = client.protocol_features().await.unwrap();
+            if !features.supports(Feature::Watchers) {
+                return;
+            }
+            let mut rx = client
+                .watch("test_watch_closed")
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            rx.changed().timeout(Duration::from_secs(3)).await.unwrap();
+            break_connection(&client);
+            // The channel is closed once the client is closed
+            assert!(rx.changed().timeout(Duration::from_secs(3)).await.is_err());
+        });
+    }
+
     #[crate::test(tarantool = "crate")]
     fn eval() {
         fiber::block_on(async {
@@ -0,0 +1,573 @@
+//! Pool of [`Client`]s connected to multiple Tarantool instances.
+//!
+//! Members are specified in any of the formats accepted by
+//! [`Client::connect_uri`], including Unix domain sockets.
+//!
+//! The pool periodically checks the health of its members with a `ping`,
+//! evicts the ones which failed the check and tries to reconnect to them on
+//! the next check. It also tracks which members are writable
+//! (`box.info.ro == false`) so that read requests can be balanced between all
+//! the healthy members, while write requests are routed to writable ones.
+//! Members supporting watchers (Tarantool 2.10 or later) report their status
+//! via the `box.status` key, which doesn't require any privileges, while
+//! `box.info.ro` is evaluated on older ones.
+//!
+//! # Example
+//! ```no_run
+//! # async {
+//! use tarantool::network::client::pool::{ClientPool, PoolOptions};
+//!
+//! let pool = ClientPool::connect(
+//!     vec![("replica-1", 3301), ("replica-2", 3301)],
+//!     PoolOptions::default(),
+//! )
+//! .await
+//! .unwrap();
+//!
+//! pool.read().unwrap().call("get_stats", &()).await.unwrap();
+//! pool.write().unwrap().call("update_stats", &()).await.unwrap();
+//! # };
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::io;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use futures::future::join_all;
+
+use super::{protocol, Client, ProtocolError};
+use crate::fiber;
+use crate::fiber::r#async::timeout::{self, IntoTimeout as _};
+use crate::fiber::r#async::watch;
+use crate::network::protocol::Feature;
+use crate::network::uri::{self, ToUri, Uri};
+use crate::tuple::RawByteBuf;
+
+/// Error returned by [`ClientPool`].
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("no healthy instances in the pool")]
+    NoHealthyInstances,
+    #[error("no healthy writable instances in the pool")]
+    NoWritableInstances,
+    #[error("invalid uri: {0}")]
+    Uri(#[from] uri::Error),
+    /// None of the healthy members is known to be writable, as checking
+    /// the status of some of them failed, e.g. because of insufficient
+    /// privileges to evaluate `box.info.ro`.
+    #[error("failed to check if a pool member is writable: {0}")]
+    StatusUnknown(Rc<timeout::Error<super::Error>>),
+    #[error("failed to start the health checker: {0}")]
+    HealthChecker(Box<crate::error::Error>),
+}
+
+/// Strategy of choosing a member of the pool for read requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Balancing {
+    /// Members are chosen in turns.
+    RoundRobin,
+    /// The member with the least number of requests awaiting response
+    /// is chosen.
+    LeastPending,
+}
+
+/// Configuration of [`ClientPool`].
+#[derive(Clone, Debug)]
+pub struct PoolOptions {
+    /// Configuration of the connection to each member.
+    pub config: protocol::Config,
+    /// Strategy of choosing a member for read requests.
+    pub balancing: Balancing,
+    /// Interval between health checks of the members.
+    pub health_check_interval: Duration,
+    /// Timeout of connecting to a member and of each request made during
+    /// a health check. If exceeded the member is considered unhealthy.
+    pub health_check_timeout: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            balancing: Balancing::RoundRobin,
+            health_check_interval: Duration::from_secs(1),
+            health_check_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Member {
+    uri: Uri,
+    /// `None` if the member is evicted from the pool.
+    client: Option<Client>,
+    /// Subscription to `box.status` of the member, if it supports watchers.
+    status: Option<watch::Receiver<Option<RawByteBuf>>>,
+    /// Error if the status couldn't be checked.
+    read_only: Result<bool, Rc<timeout::Error<super::Error>>>,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    members: RefCell<Vec<Member>>,
+    options: PoolOptions,
+    /// Index of the member to start the search from for round robin balancing.
+    next: Cell<usize>,
+    /// Notifies the health checker that the pool is dropped.
+    stop: watch::Sender<()>,
+    health_checker: RefCell<Option<fiber::JoinHandle<'static, ()>>>,
+}
+
+/// A pool of connections to multiple Tarantool instances.
+///
+/// Can be cloned and moved into different fibers, all the clones share the
+/// same connections. See [`super::pool`] for examples.
+#[derive(Clone, Debug)]
+pub struct ClientPool(Rc<PoolInner>);
+
+impl ClientPool {
+    /// Creates a pool of connections to `addresses` and starts a fiber
+    /// performing periodic health checks of the members.
+    ///
+    /// Members which couldn't be connected to are considered unhealthy and
+    /// are connected to on the following health checks.
+    ///
+    /// # Errors
+    /// [`Error::Uri`] is returned if any of the `addresses` is invalid.
+    /// [`Error::NoHealthyInstances`] is returned if none of the members could
+    /// be connected to.
+    /// [`Error::HealthChecker`] is returned if the health checker fiber
+    /// couldn't be started.
+    pub async fn connect<I>(addresses: I, options: PoolOptions) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        I::Item: ToUri,
+    {
+        let members = addresses
+            .into_iter()
+            .map(|address| {
+                Ok(Member {
+                    uri: address.to_uri()?,
+                    client: None,
+                    status: None,
+                    read_only: Ok(true),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        let (stop, stop_rx) = watch::channel(());
+        let pool = Self(Rc::new(PoolInner {
+            members: RefCell::new(members),
+            options,
+            next: Cell::new(0),
+            stop,
+            health_checker: RefCell::new(None),
+        }));
+        let results = join_all(member_checks(&pool.0)).await;
+        update_members(&pool.0, results);
+        if pool.healthy_count() == 0 {
+            return Err(Error::NoHealthyInstances);
+        }
+
+        let health_checker = fiber::Builder::new()
+            .func_async(health_checker(Rc::downgrade(&pool.0), stop_rx))
+            .name("network-client-pool-health-checker")
+            .start()
+            .map_err(|e| Error::HealthChecker(Box::new(e)))?;
+        *pool.0.health_checker.borrow_mut() = Some(health_checker);
+        Ok(pool)
+    }
+
+    /// Returns the number of members which passed the latest health check.
+    pub fn healthy_count(&self) -> usize {
+        self.0
+            .members
+            .borrow()
+            .iter()
+            .filter(|member| member.client.is_some())
+            .count()
+    }
+
+    /// Returns a client connected to a healthy member for sending read
+    /// requests. The member is chosen according to [`PoolOptions::balancing`].
+    ///
+    /// # Errors
+    /// [`Error::NoHealthyInstances`] is returned if none of the members
+    /// passed the latest health check.
+    pub fn read(&self) -> Result<Client, Error> {
+        let members = self.0.members.borrow();
+        let client = match self.0.options.balancing {
+            Balancing::RoundRobin => self.round_robin(&members, |_| true),
+            Balancing::LeastPending => members
+                .iter()
+                .filter_map(|member| member.client.as_ref())
+                .min_by_key(|client| client.pending_requests()),
+        };
+        client.cloned().ok_or(Error::NoHealthyInstances)
+    }
+
+    /// Returns a client connected to a healthy member with `box.info.ro ==
+    /// false` for sending write requests. If there are several such members,
+    /// they are chosen in turns.
+    ///
+    /// # Errors
+    /// [`Error::NoWritableInstances`] is returned if none of the writable
+    /// members passed the latest health check.
+    /// [`Error::StatusUnknown`] is returned instead if there are no writable
+    /// members, but the status of some of the healthy ones couldn't be
+    /// checked.
+    pub fn write(&self) -> Result<Client, Error> {
+        let members = self.0.members.borrow();
+        if let Some(client) =
+            self.round_robin(&members, |member| matches!(member.read_only, Ok(false)))
+        {
+            return Ok(client.clone());
+        }
+        let status_error = members
+            .iter()
+            .filter(|member| member.client.is_some())
+            .find_map(|member| member.read_only.as_ref().err());
+        match status_error {
+            Some(e) => Err(Error::StatusUnknown(e.clone())),
+            None => Err(Error::NoWritableInstances),
+        }
+    }
+
+    fn round_robin<'m>(
+        &self,
+        members: &'m [Member],
+        filter: impl Fn(&Member) -> bool,
+    ) -> Option<&'m Client> {
+        let start = self.0.next.get();
+        for i in 0..members.len() {
+            let index = (start + i) % members.len();
+            let member = &members[index];
+            if let Some(client) = member.client.as_ref().filter(|_| filter(member)) {
+                self.0.next.set(index + 1);
+                return Some(client);
+            }
+        }
+        None
+    }
+}
+
+impl Drop for ClientPool {
+    fn drop(&mut self) {
+        if Rc::strong_count(&self.0) == 1 {
+            // Stop the health checker
+            let _ = self.0.stop.send(());
+            if let Some(health_checker) = self.0.health_checker.borrow_mut().take() {
+                health_checker.join();
+            }
+        }
+    }
+}
+
+/// Health checker work loop. Yields during awaits.
+///
+/// Holds a strong reference to the pool only between awaits, so that the pool
+/// can be dropped while a health check is in progress.
+async fn health_checker(pool: Weak<PoolInner>, mut stop: watch::Receiver<()>) {
+    loop {
+        let interval = match pool.upgrade() {
+            Some(pool) => pool.options.health_check_interval,
+            None => return,
+        };
+        if stop.changed().timeout(interval).await.is_ok() {
+            return;
+        }
+        let checks = match pool.upgrade() {
+            Some(pool) => member_checks(&pool),
+            None => return,
+        };
+        let results = join_all(checks).await;
+        match pool.upgrade() {
+            Some(pool) => update_members(&pool, results),
+            None => return,
+        }
+    }
+}
+
+/// Returns futures checking the health of each member of the pool.
+fn member_checks(pool: &PoolInner) -> Vec<impl Future<Output = Member> + 'static> {
+    pool.members
+        .borrow()
+        .iter()
+        .map(|member| check_member(member.clone(), pool.options.clone()))
+        .collect()
+}
+
+/// Evicts members which failed the health check and updates read-only status
+/// of the healthy ones.
+fn update_members(pool: &PoolInner, results: Vec<Member>) {
+    let mut members = pool.members.borrow_mut();
+    for (member, result) in members.iter_mut().zip(results) {
+        *member = result;
+    }
+}
+
+/// Returns the `member` updated according to the result of its health check.
+/// Connects to the member if it was evicted.
+async fn check_member(mut member: Member, options: PoolOptions) -> Member {
+    let timeout = options.health_check_timeout;
+    let uri = &member.uri;
+    let client = match member.client.take() {
+        Some(client) => client,
+        None => {
+            member.status = None;
+            let result = Client::connect_uri(uri, options.config)
+                .timeout(timeout)
+                .await;
+            match result {
+                Ok(client) => client,
+                Err(e) => {
+                    log::warn!("failed to connect to pool member {}: {}", uri, e);
+                    return member;
+                }
+            }
+        }
+    };
+    if let Err(e) = client.ping().timeout(timeout).await {
+        log::warn!("evicting pool member {}: {}", uri, e);
+        member.status = None;
+        return member;
+    }
+    member.read_only = check_read_only(&client, &mut member.status, timeout)
+        .await
+        .map_err(|e| {
+            log::warn!("failed to check if pool member {} is read-only: {}", uri, e);
+            Rc::new(e)
+        });
+    member.client = Some(client);
+    member
+}
+
+/// Status of the instance broadcast via the `box.status` key.
+#[derive(serde::Deserialize)]
+struct BoxStatus {
+    is_ro: bool,
+}
+
+/// Returns whether the member `client` is connected to is read-only.
+///
+/// If the member supports watchers, its `status` is tracked with
+/// a subscription to `box.status`, which is made on the first call. Otherwise
+/// `box.info.ro` is evaluated.
+async fn check_read_only(
+    client: &Client,
+    status: &mut Option<watch::Receiver<Option<RawByteBuf>>>,
+    timeout: Duration,
+) -> Result<bool, timeout::Error<super::Error>> {
+    let features = client.protocol_features().timeout(timeout).await?;
+    if !features.supports(Feature::Watchers) {
+        let tuple = client
+            .eval("return box.info.ro", &())
+            .timeout(timeout)
+            .await?;
+        let invalid = || io::Error::from(io::ErrorKind::InvalidData).into();
+        let (read_only,) = tuple
+            .ok_or_else(invalid)
+            .and_then(|tuple| tuple.decode::<(bool,)>())
+            .map_err(decode_error)?;
+        return Ok(read_only);
+    }
+
+    let status = match status {
+        Some(status) => status,
+        None => status.insert(client.watch("box.status").timeout(timeout).await?),
+    };
+    // The current value is sent right after subscribing
+    if status.borrow().is_none() {
+        status
+            .changed()
+            .timeout(timeout)
+            .await
+            .map_err(|e| match e {
+                timeout::Error::Expired => timeout::Error::Expired,
+                timeout::Error::Failed(_) => {
+                    timeout::Error::Failed(super::Error::Other("connection closed".into()))
+                }
+            })?;
+    }
+    let data = status.get_cloned().expect("is set after the first event");
+    let BoxStatus { is_ro } = rmp_serde::from_slice(&data).map_err(|e| decode_error(e.into()))?;
+    Ok(is_ro)
+}
+
+fn decode_error(e: crate::error::Error) -> timeout::Error<super::Error> {
+    timeout::Error::Failed(ProtocolError::from(e).into())
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::test::util::TARANTOOL_LISTEN;
+
+    fn test_options(balancing: Balancing) -> PoolOptions {
+        PoolOptions {
+            config: protocol::Config {
+                creds: Some(("test_user".to_owned(), "password".to_owned())),
+                ..Default::default()
+            },
+            balancing,
+            health_check_interval: Duration::from_millis(10),
+            health_check_timeout: Duration::from_secs(3),
+        }
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn connect() {
+        fiber::block_on(async {
+            // Can be any other unused port
+            let addresses = vec![("localhost", TARANTOOL_LISTEN), ("localhost", 3300)];
+            let pool = ClientPool::connect(addresses, test_options(Balancing::RoundRobin))
+                .await
+                .unwrap();
+            assert_eq!(pool.healthy_count(), 1);
+
+            for _ in 0..3 {
+                pool.read()
+                    .unwrap()
+                    .ping()
+                    .timeout(Duration::from_secs(3))
+                    .await
+                    .unwrap();
+            }
+            pool.write()
+                .unwrap()
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn connect_failure() {
+        fiber::block_on(async {
+            // Can be any other unused port
+            let err = ClientPool::connect(vec![("localhost", 3300)], Default::default())
+                .await
+                .unwrap_err();
+            assert!(matches!(dbg!(err), Error::NoHealthyInstances));
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn connect_uri() {
+        use crate::test::util::unix_socket_proxy;
+
+        let path = std::env::temp_dir().join(format!("client-pool-{}.sock", std::process::id()));
+        unix_socket_proxy(&path, TARANTOOL_LISTEN).unwrap();
+        fiber::block_on(async {
+            let addresses = vec![
+                format!("localhost:{}", TARANTOOL_LISTEN),
+                format!("unix/:{}", path.display()),
+            ];
+            let pool = ClientPool::connect(addresses, test_options(Balancing::RoundRobin))
+                .await
+                .unwrap();
+            assert_eq!(pool.healthy_count(), 2);
+
+            let err = ClientPool::connect(vec!["localhost"], Default::default())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Uri(uri::Error::MissingPort(_))));
+        });
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn least_pending() {
+        fiber::block_on(async {
+            let addresses = vec![
+                ("localhost", TARANTOOL_LISTEN),
+                ("127.0.0.1", TARANTOOL_LISTEN),
+            ];
+            let pool = ClientPool::connect(addresses, test_options(Balancing::LeastPending))
+                .await
+                .unwrap();
+            assert_eq!(pool.healthy_count(), 2);
+
+            let busy = pool.read().unwrap();
+            let request = busy.eval("require('fiber').sleep(0.1)", &());
+            futures::pin_mut!(request);
+            // Send the request without waiting for the response
+            assert!(futures::poll!(request.as_mut()).is_pending());
+            assert_eq!(busy.pending_requests(), 1);
+
+            let idle = pool.read().unwrap();
+            assert_eq!(idle.pending_requests(), 0);
+            request.timeout(Duration::from_secs(3)).await.unwrap();
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn evict_and_reconnect() {
+        fiber::block_on(async {
+            let pool = ClientPool::connect(
+                vec![("localhost", TARANTOOL_LISTEN)],
+                test_options(Balancing::RoundRobin),
+            )
+            .await
+            .unwrap();
+
+            // Simulate a network failure
+            let client = pool.read().unwrap();
+            client
+                .inner
+                .borrow()
+                .close_token
+                .as_ref()
+                .unwrap()
+                .shutdown()
+                .unwrap();
+            assert!(client.ping().await.is_err());
+
+            // Member is evicted and reconnected by the health checker
+            fiber::sleep(Duration::from_millis(100));
+            pool.read()
+                .unwrap()
+                .ping()
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn write_without_privileges() {
+        crate::lua_state()
+            .exec(
+                "box.schema.user.create('test_pool_user', {
+                    password = 'password', if_not_exists = true,
+                })",
+            )
+            .unwrap();
+        fiber::block_on(async {
+            let options = PoolOptions {
+                config: protocol::Config {
+                    creds: Some(("test_pool_user".to_owned(), "password".to_owned())),
+                    ..Default::default()
+                },
+                ..test_options(Balancing::RoundRobin)
+            };
+            let pool = ClientPool::connect(vec![("localhost", TARANTOOL_LISTEN)], options)
+                .await
+                .unwrap();
+            let features = pool.read().unwrap().protocol_features().await.unwrap();
+            if features.supports(Feature::Watchers) {
+                // Subscribing to `box.status` requires no privileges
+                pool.write().unwrap();
+            } else {
+                // The user isn't allowed to evaluate `box.info.ro`
+                let err = pool.write().unwrap_err();
+                assert!(matches!(err, Error::StatusUnknown(_)));
+            }
+        });
+        crate::lua_state()
+            .exec("box.schema.user.drop('test_pool_user')")
+            .unwrap();
+    }
+}
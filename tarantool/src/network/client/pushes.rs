@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+use futures::future::{self, Either};
+
+use super::{Client, Error};
+use crate::fiber::r#async::{oneshot, watch};
+use crate::network::protocol::api::Call;
+use crate::network::protocol::SyncIndex;
+use crate::tuple::{RawByteBuf, Tuple};
+
+/// Message received in response to [`Client::call_with_pushes`].
+#[derive(Debug)]
+pub enum CallMessage {
+    /// Msgpack encoded value pushed by the procedure with `box.session.push`.
+    Push(RawByteBuf),
+    /// Value returned by the procedure. Is always the last message.
+    Response(Option<Tuple>),
+}
+
+/// Values pushed by the server during a request which are yet to be taken
+/// by the corresponding [`Pushes`].
+#[derive(Debug)]
+pub(super) struct PushQueue {
+    pushes: VecDeque<RawByteBuf>,
+    waker: watch::Sender<()>,
+}
+
+impl PushQueue {
+    /// Adds the pushed value to the queue and wakes the owner of the queue.
+    pub(super) fn push(&mut self, data: RawByteBuf) {
+        self.pushes.push_back(data);
+        let _ = self.waker.send(());
+    }
+}
+
+/// Messages received in response to a call made with
+/// [`Client::call_with_pushes`].
+///
+/// Yields the values pushed by the procedure in the order they were pushed
+/// followed by the value returned by the procedure.
+///
+/// Dropping it before the response is received doesn't cancel the call,
+/// but the following messages are discarded.
+#[derive(Debug)]
+pub struct Pushes {
+    client: Client,
+    sync: SyncIndex,
+    response: oneshot::Receiver<Result<(), Error>>,
+    pushed: watch::Receiver<()>,
+    /// Set once the response is received, but is only returned after all
+    /// the pushes are taken.
+    result: Option<Result<(), Error>>,
+    finished: bool,
+}
+
+impl Pushes {
+    /// Subscribes to the pushes of the request with the given `sync`.
+    pub(super) fn new(
+        client: Client,
+        sync: SyncIndex,
+        response: oneshot::Receiver<Result<(), Error>>,
+    ) -> Self {
+        let (waker, pushed) = watch::channel(());
+        client.inner.borrow_mut().pushes.insert(
+            sync,
+            PushQueue {
+                pushes: VecDeque::new(),
+                waker,
+            },
+        );
+        Self {
+            client,
+            sync,
+            response,
+            pushed,
+            result: None,
+            finished: false,
+        }
+    }
+
+    /// Returns the next message or `None` once the response was returned.
+    /// This function yields.
+    ///
+    /// If the connection is lost before the response is received an error
+    /// is returned instead of the response.
+    pub async fn next(&mut self) -> Result<Option<CallMessage>, Error> {
+        loop {
+            if self.finished {
+                return Ok(None);
+            }
+            if let Some(data) = self.take_push() {
+                return Ok(Some(CallMessage::Push(data)));
+            }
+            if let Some(result) = self.result.take() {
+                self.finished = true;
+                self.client.inner.borrow_mut().pushes.remove(&self.sync);
+                let result = result.and_then(|()| {
+                    // Arguments are not used for decoding the response
+                    let request = Call {
+                        fn_name: "",
+                        args: &(),
+                    };
+                    self.client.take_response(self.sync, &request)
+                });
+                return result.map(|tuple| Some(CallMessage::Response(tuple)));
+            }
+            match future::select(&mut self.response, self.pushed.changed()).await {
+                Either::Left((result, _)) => {
+                    self.result = Some(result.expect("channel should be open"));
+                }
+                Either::Right(_) => {
+                    // New push is received
+                }
+            }
+        }
+    }
+
+    fn take_push(&self) -> Option<RawByteBuf> {
+        let mut client = self.client.inner.borrow_mut();
+        client.pushes.get_mut(&self.sync)?.pushes.pop_front()
+    }
+}
+
+impl Drop for Pushes {
+    fn drop(&mut self) {
+        if !self.finished {
+            let mut client = self.client.inner.borrow_mut();
+            client.pushes.remove(&self.sync);
+            client.awaiting_response.remove(&self.sync);
+            client.protocol.drop_response(self.sync);
+            client.interceptors.discard(self.sync.0);
+        }
+    }
+}
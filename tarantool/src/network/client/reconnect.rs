@@ -0,0 +1,162 @@
+//! Reconnect policy of the [`Client`](super::Client).
+//!
+//! See [`Client::connect_with_reconnect`](super::Client::connect_with_reconnect).
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Defines how the client behaves once the connection is lost.
+///
+/// While the client is reconnecting new requests wait for the connection to
+/// be re-established (use [timeout](crate::fiber::async::timeout) to limit
+/// the time spent waiting). Once the policy gives up, the client is closed
+/// and all the requests fail with [`Error::ClosedWithErr`].
+///
+/// [`Error::ClosedWithErr`]: super::Error::ClosedWithErr
+#[derive(Clone, Debug, PartialEq)]
+pub struct Policy {
+    /// Delay before each reconnect attempt.
+    pub backoff: Backoff,
+    /// Fraction of the delay which is randomized to avoid reconnect attempts
+    /// of many clients happening at the same time. Must be within `0..=1`.
+    ///
+    /// For example with `jitter = 0.1` the actual delay is a random value
+    /// between 90% and 100% of the one specified by [`Policy::backoff`].
+    pub jitter: f64,
+    /// Maximum number of consecutive failed reconnect attempts after which
+    /// the client gives up. `None` means the client never gives up.
+    pub max_attempts: Option<u32>,
+    /// What to do with the requests which were sent but haven't received
+    /// a response at the moment the connection was lost.
+    pub in_flight: InFlight,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            backoff: Backoff::Exponential {
+                initial: Duration::from_millis(100),
+                max: Duration::from_secs(30),
+                factor: 2.0,
+            },
+            jitter: 0.1,
+            max_attempts: None,
+            in_flight: InFlight::Fail,
+        }
+    }
+}
+
+impl Policy {
+    /// Returns the delay before reconnect attempt number `attempt` (starting
+    /// from 0) with jitter applied.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.backoff.delay(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_fraction();
+        delay.mul_f64(1.0 - jitter)
+    }
+}
+
+/// Delay between reconnect attempts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Backoff {
+    /// The same delay before each attempt.
+    Fixed(Duration),
+    /// The delay starts at `initial` and is multiplied by `factor` after each
+    /// failed attempt but never exceeds `max`.
+    Exponential {
+        initial: Duration,
+        max: Duration,
+        factor: f64,
+    },
+}
+
+impl Backoff {
+    /// Returns the delay before reconnect attempt number `attempt` (starting
+    /// from 0) without jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            Self::Fixed(delay) => delay,
+            Self::Exponential {
+                initial,
+                max,
+                factor,
+            } => {
+                let exp = attempt.min(i32::MAX as u32) as i32;
+                let delay = initial.as_secs_f64() * factor.powi(exp);
+                if delay.is_finite() && delay < max.as_secs_f64() {
+                    Duration::from_secs_f64(delay)
+                } else {
+                    max
+                }
+            }
+        }
+    }
+}
+
+/// Defines what happens to the requests which were in flight at the moment
+/// the connection was lost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InFlight {
+    /// Requests fail with [`Error::ConnectionLost`](super::Error::ConnectionLost).
+    Fail,
+    /// Requests are sent again once the connection is re-established.
+    ///
+    /// Only use this if all the requests sent through the client are
+    /// idempotent, as a request may have already been executed by the server
+    /// before the connection was lost.
+    Retry,
+}
+
+/// Returns a pseudo random value within `0..1`.
+fn random_fraction() -> f64 {
+    // `RandomState` is seeded with random keys, which is good enough for jitter
+    let hash = RandomState::new().build_hasher().finish();
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_backoff() {
+        let backoff = Backoff::Fixed(Duration::from_secs(1));
+        assert_eq!(backoff.delay(0), Duration::from_secs(1));
+        assert_eq!(backoff.delay(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let backoff = Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            factor: 2.0,
+        };
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(1), Duration::from_millis(200));
+        assert_eq!(backoff.delay(3), Duration::from_millis(800));
+        assert_eq!(backoff.delay(4), Duration::from_secs(1));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter() {
+        let policy = Policy {
+            backoff: Backoff::Fixed(Duration::from_secs(1)),
+            jitter: 0.5,
+            ..Default::default()
+        };
+        for attempt in 0..100 {
+            let delay = policy.delay(attempt);
+            assert!(delay <= Duration::from_secs(1));
+            assert!(delay >= Duration::from_millis(500));
+        }
+
+        let policy = Policy {
+            jitter: 0.0,
+            ..policy
+        };
+        assert_eq!(policy.delay(0), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,134 @@
+//! Cached information about spaces and indexes of the remote server.
+//!
+//! See [`Client::schema`](super::Client::schema).
+
+use std::collections::HashMap;
+
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+use crate::index::IndexType;
+use crate::space::Field;
+use crate::tuple::Tuple;
+
+/// Metadata of a space on the remote server.
+#[derive(Clone, Debug)]
+pub struct SpaceMetadata {
+    pub id: u32,
+    pub name: String,
+    /// Field format of the space. Empty if the format wasn't specified.
+    pub format: Vec<Field>,
+}
+
+impl SpaceMetadata {
+    /// Returns the number of the field with the given `name` in the space
+    /// format.
+    pub fn field_index(&self, name: &str) -> Option<u32> {
+        self.format
+            .iter()
+            .position(|field| field.name == name)
+            .map(|i| i as u32)
+    }
+}
+
+/// Metadata of an index on the remote server.
+#[derive(Clone, Debug)]
+pub struct IndexMetadata {
+    pub space_id: u32,
+    pub id: u32,
+    pub name: String,
+    pub index_type: IndexType,
+    pub unique: bool,
+}
+
+/// A snapshot of the remote server's spaces and indexes.
+///
+/// The snapshot is never modified, instead the client replaces it with a new
+/// one once it detects that the remote schema has changed.
+#[derive(Debug, Default)]
+pub struct Schema {
+    version: u32,
+    spaces: HashMap<u32, SpaceMetadata>,
+    space_ids: HashMap<String, u32>,
+    indexes: HashMap<(u32, String), IndexMetadata>,
+}
+
+impl Schema {
+    /// Constructs the schema from tuples of `_vspace` & `_vindex` system
+    /// spaces.
+    pub(crate) fn from_rows(
+        version: u32,
+        spaces: Vec<Tuple>,
+        indexes: Vec<Tuple>,
+    ) -> crate::Result<Self> {
+        let mut schema = Self {
+            version,
+            ..Default::default()
+        };
+
+        for row in spaces {
+            #[derive(Deserialize)]
+            struct VSpaceRow(
+                u32,
+                IgnoredAny,
+                String,
+                IgnoredAny,
+                IgnoredAny,
+                IgnoredAny,
+                Vec<Field>,
+            );
+
+            let VSpaceRow(id, _, name, _, _, _, format) = row.decode()?;
+            schema.space_ids.insert(name.clone(), id);
+            schema.spaces.insert(id, SpaceMetadata { id, name, format });
+        }
+
+        for row in indexes {
+            #[derive(Deserialize)]
+            struct VIndexRow(u32, u32, String, IndexType, IndexOpts, IgnoredAny);
+
+            #[derive(Deserialize)]
+            struct IndexOpts {
+                #[serde(default = "default_unique")]
+                unique: bool,
+            }
+
+            fn default_unique() -> bool {
+                true
+            }
+
+            let VIndexRow(space_id, id, name, index_type, opts, _) = row.decode()?;
+            let metadata = IndexMetadata {
+                space_id,
+                id,
+                name: name.clone(),
+                index_type,
+                unique: opts.unique,
+            };
+            schema.indexes.insert((space_id, name), metadata);
+        }
+
+        Ok(schema)
+    }
+
+    /// Returns the remote schema version this snapshot corresponds to.
+    #[inline(always)]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Find space metadata by name.
+    pub fn space(&self, name: &str) -> Option<&SpaceMetadata> {
+        self.space_ids.get(name).and_then(|id| self.spaces.get(id))
+    }
+
+    /// Find space metadata by id.
+    pub fn space_by_id(&self, id: u32) -> Option<&SpaceMetadata> {
+        self.spaces.get(&id)
+    }
+
+    /// Find metadata of the index of space `space_id` by name.
+    pub fn index(&self, space_id: u32, name: &str) -> Option<&IndexMetadata> {
+        self.indexes.get(&(space_id, name.to_string()))
+    }
+}
@@ -0,0 +1,136 @@
+use super::index::RemoteIndex;
+use super::schema::SpaceMetadata;
+use super::{Client, Error};
+use crate::index::IteratorType;
+use crate::network::protocol::api::{Insert, Replace};
+use crate::tuple::{Encode, ToTupleBuffer, Tuple};
+
+/// Remote space.
+///
+/// Can be obtained with [`Client::space`].
+#[derive(Clone, Debug)]
+pub struct RemoteSpace {
+    client: Client,
+    space_id: u32,
+}
+
+impl RemoteSpace {
+    pub(crate) fn new(client: Client, space_id: u32) -> Self {
+        RemoteSpace { client, space_id }
+    }
+
+    /// Returns the id of this space on the remote server.
+    #[inline(always)]
+    pub fn id(&self) -> u32 {
+        self.space_id
+    }
+
+    /// Returns metadata of this space from the cached schema
+    /// (see [`Client::schema`]), including its field format.
+    ///
+    /// Returns `None` if the space was dropped on the remote server.
+    pub async fn metadata(&self) -> Result<Option<SpaceMetadata>, Error> {
+        Ok(self
+            .client
+            .schema()
+            .await?
+            .space_by_id(self.space_id)
+            .cloned())
+    }
+
+    /// Find index by name (on remote space).
+    pub async fn index(&self, name: &str) -> Result<Option<RemoteIndex>, Error> {
+        RemoteIndex::find(&self.client, self.space_id, name).await
+    }
+
+    /// Returns index with id = 0
+    #[inline(always)]
+    pub fn primary_key(&self) -> RemoteIndex {
+        RemoteIndex::new(self.client.clone(), self.space_id, 0)
+    }
+
+    /// The remote-call equivalent of the local call `Space::get(...)`
+    /// (see [details](crate::space::Space::get)).
+    pub async fn get<K>(&self, key: &K) -> Result<Option<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.primary_key().get(key).await
+    }
+
+    /// The remote-call equivalent of the local call `Space::select(...)`
+    /// (see [details](crate::space::Space::select)).
+    ///
+    /// Treats `limit` as unlimited if `None` is specified.
+    pub async fn select<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        limit: Option<u32>,
+        offset: u32,
+    ) -> Result<Vec<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.primary_key()
+            .select(iterator_type, key, limit, offset)
+            .await
+    }
+
+    /// The remote-call equivalent of the local call `Space::insert(...)`
+    /// (see [details](crate::space::Space::insert)).
+    pub async fn insert<T>(&self, value: &T) -> Result<Option<Tuple>, Error>
+    where
+        T: ToTupleBuffer,
+    {
+        self.client
+            .send_versioned(&Insert {
+                space_id: self.space_id,
+                value,
+            })
+            .await
+    }
+
+    /// The remote-call equivalent of the local call `Space::replace(...)`
+    /// (see [details](crate::space::Space::replace)).
+    pub async fn replace<T>(&self, value: &T) -> Result<Option<Tuple>, Error>
+    where
+        T: ToTupleBuffer,
+    {
+        self.client
+            .send_versioned(&Replace {
+                space_id: self.space_id,
+                value,
+            })
+            .await
+    }
+
+    /// The remote-call equivalent of the local call `Space::update(...)`
+    /// (see [details](crate::space::Space::update)).
+    pub async fn update<K, Op>(&self, key: &K, ops: &[Op]) -> Result<Option<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.primary_key().update(key, ops).await
+    }
+
+    /// The remote-call equivalent of the local call `Space::upsert(...)`
+    /// (see [details](crate::space::Space::upsert)).
+    pub async fn upsert<T, Op>(&self, value: &T, ops: &[Op]) -> Result<Option<Tuple>, Error>
+    where
+        T: ToTupleBuffer,
+        Op: Encode,
+    {
+        self.primary_key().upsert(value, ops).await
+    }
+
+    /// The remote-call equivalent of the local call `Space::delete(...)`
+    /// (see [details](crate::space::Space::delete)).
+    pub async fn delete<K>(&self, key: &K) -> Result<Option<Tuple>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.primary_key().delete(key).await
+    }
+}
@@ -0,0 +1,76 @@
+use std::io;
+
+use super::{Client, Error};
+use crate::network::protocol::api::ExecutePrepared;
+use crate::network::protocol::Error as ProtocolError;
+use crate::sql::SqlResponse;
+use crate::tuple::ToTupleBuffer;
+
+/// Sql statement prepared on the remote server with [`Client::prepare`].
+/// See [`sql`](crate::sql#prepared-statements) for the common semantics.
+///
+/// The statement can't be executed after the client reconnects.
+#[derive(Debug)]
+pub struct PreparedStatement {
+    client: Client,
+    id: u32,
+    /// Id of the client connection the statement was prepared over.
+    connection_id: u64,
+    response: SqlResponse,
+}
+
+impl PreparedStatement {
+    /// Fails if the `response` to the prepare request has no statement id.
+    pub(super) fn new(client: Client, response: SqlResponse) -> Result<Self, Error> {
+        let id = response.stmt_id().ok_or_else(|| {
+            ProtocolError::from(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "statement id is missing in the prepare response",
+            ))
+        })?;
+        let connection_id = client.acquire_statement(id);
+        Ok(Self {
+            client,
+            id,
+            connection_id,
+            response,
+        })
+    }
+
+    /// Returns the id of the statement on the server.
+    #[inline(always)]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Response to the prepare request containing the metadata of the result
+    /// columns & bind parameters of the statement.
+    #[inline(always)]
+    pub fn response(&self) -> &SqlResponse {
+        &self.response
+    }
+
+    /// Executes the statement with the given bind parameters.
+    /// At most `limit` rows are kept if it is set. This function yields.
+    ///
+    /// See also [`Client::execute`].
+    pub async fn execute<T: ToTupleBuffer>(
+        &self,
+        bind_params: &T,
+        limit: Option<usize>,
+    ) -> Result<SqlResponse, Error> {
+        self.client
+            .send(&ExecutePrepared {
+                stmt_id: self.id,
+                bind_params,
+                limit,
+            })
+            .await
+    }
+}
+
+impl Drop for PreparedStatement {
+    fn drop(&mut self) {
+        self.client.release_statement(self.id, self.connection_id);
+    }
+}
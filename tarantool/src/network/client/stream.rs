@@ -0,0 +1,91 @@
+use std::ops::Deref;
+use std::time::Duration;
+
+use super::{Client, Error};
+use crate::network::protocol::api::{Begin, Commit, Rollback};
+use crate::transaction::IsolationLevel;
+
+/// A stream of requests within a single connection. Created with
+/// [`Client::stream`].
+///
+/// Requests of the same stream are executed by the server sequentially
+/// in the order they were sent. A stream can also run an interactive
+/// transaction spanning multiple requests:
+/// ```no_run
+/// # async {
+/// use tarantool::network::client::Client;
+/// use tarantool::transaction::IsolationLevel;
+///
+/// let client = Client::connect("localhost", 3301).await.unwrap();
+/// let stream = client.stream();
+/// stream.begin(IsolationLevel::Default, None).await.unwrap();
+/// let space = stream.space("accounts").await.unwrap().unwrap();
+/// space.update(&(1,), &[("-", 1, 100)]).await.unwrap();
+/// space.update(&(2,), &[("+", 1, 100)]).await.unwrap();
+/// stream.commit().await.unwrap();
+/// # };
+/// ```
+///
+/// All the [`Client`] methods can be called on a stream, including the ones
+/// returning [`RemoteSpace`](super::RemoteSpace) handles, requests of which
+/// will also be sent within the stream.
+///
+/// Requires Tarantool 2.10 or later. Transactions in memtx spaces also
+/// require `box.cfg.memtx_use_mvcc_engine` to be enabled on the server.
+/// If the connection is lost before the transaction is committed, it is
+/// rolled back by the server. Requests of a stream are therefore never sent
+/// again after the client reconnects, regardless of the
+/// [`InFlight`](super::reconnect::InFlight) policy: the requests in flight
+/// and all the following requests of the stream fail with
+/// [`Error::ConnectionLost`]. Note that dropping the stream doesn't roll back
+/// its transaction.
+#[derive(Clone, Debug)]
+pub struct Stream {
+    client: Client,
+}
+
+impl Stream {
+    #[inline(always)]
+    pub(crate) fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Returns the id of the stream.
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        self.client.stream_id.expect("is set for streams")
+    }
+
+    /// Begins a transaction in the stream. All the following requests of
+    /// the stream are executed within the transaction until it is committed
+    /// or rolled back.
+    ///
+    /// If `timeout` is specified, the transaction is rolled back by the
+    /// server after it expires.
+    pub async fn begin(
+        &self,
+        isolation: IsolationLevel,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        self.client.send(&Begin { isolation, timeout }).await
+    }
+
+    /// Commits the transaction of the stream.
+    pub async fn commit(&self) -> Result<(), Error> {
+        self.client.send(&Commit).await
+    }
+
+    /// Rolls back the transaction of the stream.
+    pub async fn rollback(&self) -> Result<(), Error> {
+        self.client.send(&Rollback).await
+    }
+}
+
+impl Deref for Stream {
+    type Target = Client;
+
+    #[inline(always)]
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
@@ -27,12 +27,14 @@ use std::os::unix::prelude::IntoRawFd;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use std::{io, ptr};
 
 use futures::{AsyncRead, AsyncWrite};
 
-use crate::ffi::tarantool as ffi;
+pub use super::transport::CloseToken;
+
+use super::transport;
 use crate::fiber::r#async::context::ContextExt;
 use crate::fiber::r#async::{self, timeout};
 
@@ -91,25 +93,7 @@ impl TcpStream {
 
     /// Close token for [`TcpStream`] to be able to close it from other fibers.
     pub fn close_token(&self) -> CloseToken {
-        CloseToken(self.fd)
-    }
-}
-
-/// Close token for [`TcpStream`] to be able to close it from other fibers.
-#[derive(Debug)]
-pub struct CloseToken(RawFd);
-
-impl CloseToken {
-    pub fn close(&self) -> io::Result<()> {
-        let (res, err) = (
-            unsafe { ffi::coio_close(self.0) },
-            io::Error::last_os_error(),
-        );
-        if res != 0 {
-            Err(err)
-        } else {
-            Ok(())
-        }
+        CloseToken::new(self.fd)
     }
 }
 
@@ -191,33 +175,8 @@ impl AsyncWrite for TcpStream {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        let (result, err) = (
-            // `self.fd` must be nonblocking for this to work correctly
-            unsafe { libc::write(self.fd, buf.as_ptr() as *const libc::c_void, buf.len()) },
-            io::Error::last_os_error(),
-        );
-
-        if result >= 0 {
-            return Poll::Ready(Ok(result as usize));
-        }
-        match err.kind() {
-            io::ErrorKind::WouldBlock => {
-                // SAFETY: Safe as long as this future is executed by
-                // `fiber::block_on` async executor.
-                unsafe { ContextExt::set_coio_wait(cx, self.fd, ffi::CoIOFlags::WRITE) }
-                Poll::Pending
-            }
-            io::ErrorKind::Interrupted => {
-                // Return poll pending without setting coio wait
-                // so that write can be retried immediately.
-                //
-                // SAFETY: Safe as long as this future is executed by
-                // `fiber::block_on` async executor.
-                unsafe { ContextExt::set_deadline(cx, Instant::now()) }
-                Poll::Pending
-            }
-            _ => Poll::Ready(Err(err)),
-        }
+        // `self.fd` is nonblocking
+        transport::poll_write(self.fd, cx, buf)
     }
 
     fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -239,33 +198,8 @@ impl AsyncRead for TcpStream {
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        let (result, err) = (
-            // `self.fd` must be nonblocking for this to work correctly
-            unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) },
-            io::Error::last_os_error(),
-        );
-
-        if result >= 0 {
-            return Poll::Ready(Ok(result as usize));
-        }
-        match err.kind() {
-            io::ErrorKind::WouldBlock => {
-                // SAFETY: Safe as long as this future is executed by
-                // `fiber::block_on` async executor.
-                unsafe { ContextExt::set_coio_wait(cx, self.fd, ffi::CoIOFlags::READ) }
-                Poll::Pending
-            }
-            io::ErrorKind::Interrupted => {
-                // Return poll pending without setting coio wait
-                // so that read can be retried immediately.
-                //
-                // SAFETY: Safe as long as this future is executed by
-                // `fiber::block_on` async executor.
-                unsafe { ContextExt::set_deadline(cx, Instant::now()) }
-                Poll::Pending
-            }
-            _ => Poll::Ready(Err(err)),
-        }
+        // `self.fd` is nonblocking
+        transport::poll_read(self.fd, cx, buf)
     }
 }
 
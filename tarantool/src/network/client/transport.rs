@@ -0,0 +1,193 @@
+//! Transport layer of the [`Client`](super::Client).
+//!
+//! [`Transport`] is a coio based stream connected to a [`Uri`], which is
+//! either a [`TcpStream`] or a [`UnixStream`].
+
+use std::io;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use futures::{AsyncRead, AsyncWrite};
+
+use super::tcp::TcpStream;
+use super::unix::UnixStream;
+use super::Error;
+use crate::ffi::tarantool as ffi;
+use crate::fiber::r#async::context::ContextExt;
+use crate::network::uri::Uri;
+
+/// Async stream connected either over TCP or over a Unix domain socket.
+#[derive(Debug)]
+pub enum Transport {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Transport {
+    /// Connects to the instance at `uri`.
+    ///
+    /// This functions makes the fiber **yield**.
+    pub async fn connect(uri: &Uri) -> Result<Self, Error> {
+        match uri {
+            Uri::Tcp { host, port } => Ok(Self::Tcp(TcpStream::connect(host, *port).await?)),
+            Uri::Unix(path) => Ok(Self::Unix(
+                UnixStream::connect(path)
+                    .await
+                    .map_err(Error::UnixConnect)?,
+            )),
+        }
+    }
+
+    /// Close token for the stream to be able to close it from other fibers.
+    pub fn close_token(&self) -> CloseToken {
+        match self {
+            Self::Tcp(stream) => stream.close_token(),
+            Self::Unix(stream) => stream.close_token(),
+        }
+    }
+}
+
+impl AsyncWrite for Transport {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_close(cx),
+            Self::Unix(stream) => Pin::new(stream).poll_close(cx),
+        }
+    }
+}
+
+impl AsyncRead for Transport {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+/// Close token for a stream to be able to close it from other fibers.
+#[derive(Debug)]
+pub struct CloseToken(RawFd);
+
+impl CloseToken {
+    #[inline(always)]
+    pub(super) fn new(fd: RawFd) -> Self {
+        Self(fd)
+    }
+
+    pub fn close(&self) -> io::Result<()> {
+        let (res, err) = (
+            unsafe { ffi::coio_close(self.0) },
+            io::Error::last_os_error(),
+        );
+        if res != 0 {
+            Err(err)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Shuts down both reading and writing halves of the stream without
+    /// closing the file descriptor. Fibers blocked on the stream are woken up
+    /// and get an error, while the descriptor is closed once the stream is dropped.
+    pub fn shutdown(&self) -> io::Result<()> {
+        let res = unsafe { libc::shutdown(self.0, libc::SHUT_RDWR) };
+        if res != 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Writes `buf` into a nonblocking `fd`, waiting with coio until it becomes
+/// writable if needed.
+pub(super) fn poll_write(fd: RawFd, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    let (result, err) = (
+        // `fd` must be nonblocking for this to work correctly
+        unsafe { libc::write(fd, buf.as_ptr() as *const libc::c_void, buf.len()) },
+        io::Error::last_os_error(),
+    );
+
+    if result >= 0 {
+        return Poll::Ready(Ok(result as usize));
+    }
+    match err.kind() {
+        io::ErrorKind::WouldBlock => {
+            // SAFETY: Safe as long as this future is executed by
+            // `fiber::block_on` async executor.
+            unsafe { ContextExt::set_coio_wait(cx, fd, ffi::CoIOFlags::WRITE) }
+            Poll::Pending
+        }
+        io::ErrorKind::Interrupted => {
+            // Return poll pending without setting coio wait
+            // so that write can be retried immediately.
+            //
+            // SAFETY: Safe as long as this future is executed by
+            // `fiber::block_on` async executor.
+            unsafe { ContextExt::set_deadline(cx, Instant::now()) }
+            Poll::Pending
+        }
+        _ => Poll::Ready(Err(err)),
+    }
+}
+
+/// Reads from a nonblocking `fd` into `buf`, waiting with coio until it
+/// becomes readable if needed.
+pub(super) fn poll_read(
+    fd: RawFd,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+) -> Poll<io::Result<usize>> {
+    let (result, err) = (
+        // `fd` must be nonblocking for this to work correctly
+        unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) },
+        io::Error::last_os_error(),
+    );
+
+    if result >= 0 {
+        return Poll::Ready(Ok(result as usize));
+    }
+    match err.kind() {
+        io::ErrorKind::WouldBlock => {
+            // SAFETY: Safe as long as this future is executed by
+            // `fiber::block_on` async executor.
+            unsafe { ContextExt::set_coio_wait(cx, fd, ffi::CoIOFlags::READ) }
+            Poll::Pending
+        }
+        io::ErrorKind::Interrupted => {
+            // Return poll pending without setting coio wait
+            // so that read can be retried immediately.
+            //
+            // SAFETY: Safe as long as this future is executed by
+            // `fiber::block_on` async executor.
+            unsafe { ContextExt::set_deadline(cx, Instant::now()) }
+            Poll::Pending
+        }
+        _ => Poll::Ready(Err(err)),
+    }
+}
@@ -0,0 +1,167 @@
+//! Contains an implementation of a custom async coio based [`UnixStream`].
+//!
+//! ## Example
+//! ```no_run
+//! # async {
+//! use futures::AsyncReadExt;
+//! use tarantool::network::client::unix::UnixStream;
+//!
+//! let mut stream = UnixStream::connect("/var/run/tarantool.sock")
+//!     .await
+//!     .unwrap();
+//! let mut buf = vec![];
+//! let read_size = stream
+//!     .read(&mut buf)
+//!     .await
+//!     .unwrap();
+//! # };
+//! ```
+
+use std::io;
+use std::os::unix::io::{IntoRawFd, RawFd};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{future, AsyncRead, AsyncWrite};
+
+use super::transport::{self, CloseToken};
+use crate::coio;
+use crate::ffi::tarantool as ffi;
+use crate::fiber::r#async::context::ContextExt;
+
+/// Async stream connected to a Unix domain socket based on fibers and coio.
+///
+/// Has the same properties as [`TcpStream`](super::tcp::TcpStream).
+#[derive(Debug)]
+pub struct UnixStream {
+    fd: RawFd,
+}
+
+impl UnixStream {
+    /// Creates a [`UnixStream`] connected to the socket at `path`.
+    ///
+    /// This functions makes the fiber **yield** if the connection can't be
+    /// established right away.
+    pub async fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (stream, in_progress) = coio::start_connect_unix(path.as_ref())?;
+        let stream = Self {
+            fd: stream.into_raw_fd(),
+        };
+        if in_progress {
+            // The connection is established once the socket becomes writable
+            let mut waited = false;
+            future::poll_fn(|cx| {
+                if waited {
+                    return Poll::Ready(());
+                }
+                waited = true;
+                // SAFETY: Safe as long as this future is executed by
+                // `fiber::block_on` async executor.
+                unsafe { ContextExt::set_coio_wait(cx, stream.fd, ffi::CoIOFlags::WRITE) }
+                Poll::Pending
+            })
+            .await;
+            coio::take_socket_error(stream.fd)?;
+        }
+        Ok(stream)
+    }
+
+    /// Close token for [`UnixStream`] to be able to close it from other fibers.
+    pub fn close_token(&self) -> CloseToken {
+        CloseToken::new(self.fd)
+    }
+}
+
+impl AsyncWrite for UnixStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        // `self.fd` is nonblocking
+        transport::poll_write(self.fd, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Nothing is buffered
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.close_token().close())
+    }
+}
+
+impl AsyncRead for UnixStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        // `self.fd` is nonblocking
+        transport::poll_read(self.fd, cx, buf)
+    }
+}
+
+impl Drop for UnixStream {
+    fn drop(&mut self) {
+        let _ = self.close_token().close();
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+
+    use crate::fiber;
+    use crate::fiber::r#async::timeout;
+
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use futures::{AsyncReadExt, AsyncWriteExt};
+
+    const _10_SEC: Duration = Duration::from_secs(10);
+
+    #[crate::test(tarantool = "crate")]
+    fn connect_error() {
+        let err = fiber::block_on(UnixStream::connect("/nonexistent/tarantool.sock")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn read_write() {
+        let path = std::env::temp_dir().join(format!("unix-stream-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        // Echo server
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = vec![0; 5];
+            <std::os::unix::net::UnixStream as std::io::Read>::read_exact(&mut stream, &mut buf)
+                .unwrap();
+            <std::os::unix::net::UnixStream as std::io::Write>::write_all(&mut stream, &buf)
+                .unwrap();
+        });
+
+        fiber::block_on(async {
+            let mut stream = timeout::timeout(_10_SEC, UnixStream::connect(&path))
+                .await
+                .unwrap();
+            timeout::timeout(_10_SEC, stream.write_all(&[1, 2, 3]))
+                .await
+                .unwrap();
+            timeout::timeout(_10_SEC, stream.write_all(&[4, 5]))
+                .await
+                .unwrap();
+            let mut buf = vec![0; 5];
+            timeout::timeout(_10_SEC, stream.read_exact(&mut buf))
+                .await
+                .unwrap();
+            assert_eq!(buf, vec![1, 2, 3, 4, 5]);
+        });
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,416 @@
+//! Hooks invoked for every request sent by a connector.
+//!
+//! An [`Interceptor`] can be added to a [`network::client::Client`] with
+//! [`Client::add_interceptor`] and to a [`net_box::Conn`] with
+//! [`Conn::add_interceptor`]. It's called before each request is sent and
+//! once the corresponding response is received, which is useful for logging,
+//! collecting latency metrics or tracing.
+//!
+//! # Example
+//! ```no_run
+//! use std::rc::Rc;
+//! use tarantool::network::interceptor::{Interceptor, RequestInfo, ResponseInfo};
+//!
+//! struct Logger;
+//!
+//! impl Interceptor for Logger {
+//!     fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+//!         log::info!(
+//!             "{} {:?} took {:?}, error: {:?}",
+//!             request.kind,
+//!             request.name,
+//!             response.duration,
+//!             response.error.map(|e| e.to_string()),
+//!         );
+//!     }
+//! }
+//!
+//! # fn get_conn() -> tarantool::net_box::Conn { todo!() }
+//! let conn = get_conn();
+//! conn.add_interceptor(Rc::new(Logger));
+//! ```
+//!
+//! [`network::client::Client`]: super::client::Client
+//! [`Client::add_interceptor`]: super::client::Client::add_interceptor
+//! [`net_box::Conn`]: crate::net_box::Conn
+//! [`Conn::add_interceptor`]: crate::net_box::Conn::add_interceptor
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::Cursor;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::msgpack;
+
+const REQUEST_TYPE: u64 = 0x00;
+const SYNC: u64 = 0x01;
+const SPACE_ID: u64 = 0x10;
+const FUNCTION_NAME: u64 = 0x22;
+const EXPR: u64 = 0x27;
+const SQL_TEXT: u64 = 0x40;
+const EVENT_KEY: u64 = 0x57;
+
+crate::define_str_enum! {
+    /// Kind of an intercepted request, see [`RequestInfo`].
+    pub enum RequestKind {
+        Select = "select",
+        Insert = "insert",
+        Replace = "replace",
+        Update = "update",
+        Delete = "delete",
+        Upsert = "upsert",
+        Call = "call",
+        Eval = "eval",
+        Execute = "execute",
+        Prepare = "prepare",
+        Begin = "begin",
+        Commit = "commit",
+        Rollback = "rollback",
+        Ping = "ping",
+        Watch = "watch",
+        Unwatch = "unwatch",
+    }
+}
+
+impl RequestKind {
+    /// Returns the kind of the request by its `IPROTO_REQUEST_TYPE` code.
+    fn from_code(code: u64) -> Option<Self> {
+        let kind = match code {
+            1 => Self::Select,
+            2 => Self::Insert,
+            3 => Self::Replace,
+            4 => Self::Update,
+            5 => Self::Delete,
+            8 => Self::Eval,
+            9 => Self::Upsert,
+            10 => Self::Call,
+            11 => Self::Execute,
+            13 => Self::Prepare,
+            14 => Self::Begin,
+            15 => Self::Commit,
+            16 => Self::Rollback,
+            64 => Self::Ping,
+            74 => Self::Watch,
+            75 => Self::Unwatch,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// Information about an intercepted request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestInfo {
+    pub kind: RequestKind,
+    /// Sync of the request, unique within a connection.
+    pub sync: u64,
+    /// Id of the space for select and data manipulation requests.
+    pub space_id: Option<u32>,
+    /// Name of the object the request is made to:
+    /// - space name for select and data manipulation requests if the space is
+    ///   found in the cached schema
+    /// - function name for [`RequestKind::Call`]
+    /// - lua expression for [`RequestKind::Eval`]
+    /// - sql text for [`RequestKind::Execute`] & [`RequestKind::Prepare`]
+    ///   unless a prepared statement is executed
+    /// - watched key for [`RequestKind::Watch`] & [`RequestKind::Unwatch`]
+    pub name: Option<String>,
+    /// Size of the encoded request in bytes.
+    pub size: usize,
+}
+
+impl RequestInfo {
+    /// Decodes the information about the request from the encoded request
+    /// header and body. `space_name` is used to look up the name of the space
+    /// by its id.
+    ///
+    /// Returns `None` if `data` isn't a request which can be intercepted.
+    pub(crate) fn decode(
+        data: &[u8],
+        space_name: impl FnOnce(u32) -> Option<String>,
+    ) -> Option<Self> {
+        let mut cursor = Cursor::new(data);
+        let mut kind = None;
+        let mut sync = None;
+        let header_len = rmp::decode::read_map_len(&mut cursor).ok()?;
+        for _ in 0..header_len {
+            match rmp::decode::read_int(&mut cursor).ok()? {
+                REQUEST_TYPE => {
+                    kind = RequestKind::from_code(rmp::decode::read_int(&mut cursor).ok()?)
+                }
+                SYNC => sync = Some(rmp::decode::read_int(&mut cursor).ok()?),
+                _ => msgpack::skip_value(&mut cursor).ok()?,
+            }
+        }
+
+        let mut space_id = None;
+        let mut name = None;
+        let body_len = rmp::decode::read_map_len(&mut cursor).ok()?;
+        for _ in 0..body_len {
+            match rmp::decode::read_int(&mut cursor).ok()? {
+                SPACE_ID => space_id = Some(rmp::decode::read_int(&mut cursor).ok()?),
+                FUNCTION_NAME | EXPR | SQL_TEXT | EVENT_KEY => name = Some(read_str(&mut cursor)?),
+                _ => msgpack::skip_value(&mut cursor).ok()?,
+            }
+        }
+
+        Some(Self {
+            kind: kind?,
+            sync: sync?,
+            space_id,
+            name: name.or_else(|| space_id.and_then(space_name)),
+            size: data.len(),
+        })
+    }
+}
+
+fn read_str(cursor: &mut Cursor<&[u8]>) -> Option<String> {
+    let len = rmp::decode::read_str_len(cursor).ok()? as usize;
+    let start = cursor.position() as usize;
+    let data = cursor.get_ref().get(start..start + len)?;
+    cursor.set_position((start + len) as u64);
+    Some(String::from_utf8_lossy(data).into_owned())
+}
+
+/// Information about the response to an intercepted request.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseInfo<'a> {
+    /// Size of the received response in bytes or 0 if the response wasn't
+    /// received, e.g. because the connection was lost.
+    pub size: usize,
+    /// Time elapsed since the request was sent.
+    pub duration: Duration,
+    /// Error returned by the server or the error which happened while
+    /// waiting for the response or decoding it.
+    pub error: Option<&'a (dyn Error + 'static)>,
+}
+
+/// Hooks invoked for every request sent by a connector.
+/// See [module level documentation](self) for details.
+///
+/// Interceptors are called from the fiber sending the request or receiving
+/// the response and **must not yield**.
+pub trait Interceptor {
+    /// Is called once the request is encoded and is about to be sent.
+    fn before_request(&self, request: &RequestInfo) {
+        let _ = request;
+    }
+
+    /// Is called once the response to the request is received and decoded or
+    /// the request failed, e.g. because the connection was lost.
+    ///
+    /// Is not called if the response is no longer awaited, e.g. if a future
+    /// of an async request was dropped.
+    fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+        let _ = (request, response);
+    }
+}
+
+/// Interceptors of a connection along with the requests awaiting response.
+#[derive(Default)]
+pub(crate) struct Interceptors {
+    interceptors: RefCell<Vec<Rc<dyn Interceptor>>>,
+    in_flight: RefCell<HashMap<u64, InFlight>>,
+}
+
+struct InFlight {
+    request: RequestInfo,
+    sent_at: Instant,
+    response_size: usize,
+}
+
+impl Interceptors {
+    pub(crate) fn add(&self, interceptor: Rc<dyn Interceptor>) {
+        self.interceptors.borrow_mut().push(interceptor);
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.interceptors.borrow().is_empty()
+    }
+
+    fn interceptors(&self) -> Vec<Rc<dyn Interceptor>> {
+        // Interceptors may be added from inside the hooks
+        self.interceptors.borrow().clone()
+    }
+
+    /// Calls [`Interceptor::before_request`] for the request encoded in
+    /// `data` and starts tracking it.
+    pub(crate) fn before_request(
+        &self,
+        data: &[u8],
+        space_name: impl FnOnce(u32) -> Option<String>,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+        let request = match RequestInfo::decode(data, space_name) {
+            Some(request) => request,
+            None => return,
+        };
+        for interceptor in self.interceptors() {
+            interceptor.before_request(&request);
+        }
+        self.in_flight.borrow_mut().insert(
+            request.sync,
+            InFlight {
+                request,
+                sent_at: Instant::now(),
+                response_size: 0,
+            },
+        );
+    }
+
+    /// Records the size of the response to the request with `sync`.
+    pub(crate) fn response_received(&self, sync: u64, size: usize) {
+        if let Some(in_flight) = self.in_flight.borrow_mut().get_mut(&sync) {
+            in_flight.response_size = size;
+        }
+    }
+
+    /// Calls [`Interceptor::after_response`] for the request with `sync`
+    /// and stops tracking it. Does nothing if the request isn't tracked.
+    pub(crate) fn after_response(&self, sync: u64, error: Option<&(dyn Error + 'static)>) {
+        let in_flight = self.in_flight.borrow_mut().remove(&sync);
+        if let Some(in_flight) = in_flight {
+            self.notify(in_flight, error);
+        }
+    }
+
+    /// Calls [`Interceptor::after_response`] with `error` for all
+    /// the requests awaiting response.
+    pub(crate) fn fail_all(&self, error: &(dyn Error + 'static)) {
+        let in_flight: Vec<_> = self.in_flight.borrow_mut().drain().collect();
+        for (_, in_flight) in in_flight {
+            self.notify(in_flight, Some(error));
+        }
+    }
+
+    /// Stops tracking the request with `sync` without calling the interceptors.
+    pub(crate) fn discard(&self, sync: u64) {
+        self.in_flight.borrow_mut().remove(&sync);
+    }
+
+    fn notify(&self, in_flight: InFlight, error: Option<&(dyn Error + 'static)>) {
+        let response = ResponseInfo {
+            size: in_flight.response_size,
+            duration: in_flight.sent_at.elapsed(),
+            error,
+        };
+        for interceptor in self.interceptors() {
+            interceptor.after_response(&in_flight.request, &response);
+        }
+    }
+}
+
+impl fmt::Debug for Interceptors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interceptors")
+            .field("count", &self.interceptors.borrow().len())
+            .field("in_flight", &self.in_flight.borrow().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::protocol::api::{Call, Insert, Ping, Request};
+    use crate::network::protocol::SyncIndex;
+    use std::cell::Cell;
+
+    fn encode(request: &impl Request, sync: u64) -> Vec<u8> {
+        let mut data = vec![];
+        request.encode(&mut data, SyncIndex(sync)).unwrap();
+        data
+    }
+
+    #[test]
+    fn decode_request_info() {
+        let data = encode(
+            &Insert {
+                space_id: 512,
+                value: &(1, "foo"),
+            },
+            3,
+        );
+        let info = RequestInfo::decode(&data, |id| Some(format!("space_{}", id))).unwrap();
+        assert_eq!(
+            info,
+            RequestInfo {
+                kind: RequestKind::Insert,
+                sync: 3,
+                space_id: Some(512),
+                name: Some("space_512".into()),
+                size: data.len(),
+            }
+        );
+
+        let data = encode(
+            &Call {
+                fn_name: "foo",
+                args: &(1, 2),
+            },
+            4,
+        );
+        let info = RequestInfo::decode(&data, |_| unreachable!()).unwrap();
+        assert_eq!(info.kind, RequestKind::Call);
+        assert_eq!(info.sync, 4);
+        assert_eq!(info.space_id, None);
+        assert_eq!(info.name.as_deref(), Some("foo"));
+
+        let data = encode(&Ping, 5);
+        let info = RequestInfo::decode(&data, |_| unreachable!()).unwrap();
+        assert_eq!(info.kind, RequestKind::Ping);
+        assert_eq!(info.name, None);
+
+        assert!(RequestInfo::decode(&data[..3], |_| None).is_none());
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        before: Cell<usize>,
+        after: Cell<usize>,
+        errors: Cell<usize>,
+    }
+
+    impl Interceptor for Counter {
+        fn before_request(&self, _: &RequestInfo) {
+            self.before.set(self.before.get() + 1);
+        }
+
+        fn after_response(&self, _: &RequestInfo, response: &ResponseInfo) {
+            self.after.set(self.after.get() + 1);
+            if response.error.is_some() {
+                self.errors.set(self.errors.get() + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn interceptors() {
+        let interceptors = Interceptors::default();
+        let counter = Rc::new(Counter::default());
+        interceptors.add(counter.clone());
+
+        for sync in 1..=3 {
+            interceptors.before_request(&encode(&Ping, sync), |_| None);
+        }
+        assert_eq!(counter.before.get(), 3);
+
+        interceptors.response_received(1, 10);
+        interceptors.after_response(1, None);
+        // Only reported once
+        interceptors.after_response(1, None);
+        assert_eq!(counter.after.get(), 1);
+
+        interceptors.discard(2);
+        let error = std::io::Error::from(std::io::ErrorKind::NotConnected);
+        interceptors.fail_all(&error);
+        assert_eq!(counter.after.get(), 2);
+        assert_eq!(counter.errors.get(), 1);
+    }
+}
@@ -0,0 +1,934 @@
+//! Scriptable fake binary protocol peer for testing code built on top of
+//! the connectors without a running Tarantool.
+//!
+//! A [`MockPeer`] is given a list of expected requests along with the
+//! [`Reply`] to each of them: a response with data, an error, a delayed
+//! response, an event, no response at all or a dropped connection. Like the
+//! [`protocol`] layer it is sans-I/O, i.e. it consumes the bytes sent by the
+//! client and produces the bytes to be sent back, so it can be used:
+//! - in plain `cargo test` against a [`Protocol`] directly, see [`exchange`]
+//! - from `#[`[`tarantool::test`]`]` with [`network::client::Client`] or
+//!   [`net_box::Conn`] connected to a [`MockServer`]
+//!
+//! The greeting, the id and the auth requests are handled by the peer
+//! automatically, see [`MockPeer::features`] & [`MockPeer::auth_reply`].
+//! Requests which don't match the next expectation are answered with an
+//! error and cause [`MockPeer::assert_done`] to panic.
+//!
+//! Responses to most of the requests are decoded into [`Tuple`]s, which
+//! can only be created inside Tarantool, so outside of it the [`protocol`]
+//! is best tested with requests like [`Ping`].
+//!
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use tarantool::error::TarantoolErrorCode;
+//! use tarantool::network::mock::{self, Expect, MockPeer, Reply};
+//! use tarantool::network::protocol::api::Ping;
+//! use tarantool::network::protocol::Protocol;
+//!
+//! let mut peer = MockPeer::new()
+//!     .expect(
+//!         Expect::ping(),
+//!         Reply::error(TarantoolErrorCode::Loading as u32, "instance is loading"),
+//!     )
+//!     .expect(Expect::ping(), Reply::ok().after(Duration::from_secs(1)))
+//!     .expect(Expect::ping(), Reply::drop_connection());
+//!
+//! let mut protocol = Protocol::new();
+//! mock::exchange(&mut protocol, &mut peer).unwrap();
+//! assert!(protocol.is_ready());
+//!
+//! let sync = protocol.send_request(&Ping).unwrap();
+//! mock::exchange(&mut protocol, &mut peer).unwrap();
+//! assert!(protocol.take_response(sync, &Ping).unwrap().is_err());
+//!
+//! let sync = protocol.send_request(&Ping).unwrap();
+//! // The response is only sent once the time passes
+//! assert!(mock::exchange(&mut protocol, &mut peer).unwrap().is_empty());
+//! peer.advance(Duration::from_secs(1));
+//! assert_eq!(mock::exchange(&mut protocol, &mut peer).unwrap(), [sync]);
+//! assert!(protocol.take_response(sync, &Ping).unwrap().is_ok());
+//!
+//! protocol.send_request(&Ping).unwrap();
+//! mock::exchange(&mut protocol, &mut peer).unwrap();
+//! assert!(peer.is_closed());
+//!
+//! peer.assert_done();
+//! ```
+//!
+//! [`protocol`]: super::protocol
+//! [`tarantool::test`]: macro@crate::test
+//! [`network::client::Client`]: super::client::Client
+//! [`net_box::Conn`]: crate::net_box::Conn
+//! [`Tuple`]: crate::tuple::Tuple
+//! [`Ping`]: super::protocol::api::Ping
+
+mod server;
+
+pub use server::MockServer;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Cursor, Write};
+use std::time::Duration;
+
+use crate::error::TarantoolErrorCode;
+use crate::msgpack;
+use crate::network::interceptor::{RequestInfo, RequestKind};
+use crate::network::protocol::codec::IProtoType;
+use crate::network::protocol::{self, Feature, Protocol, ProtocolFeatures, SizeHint, SyncIndex};
+use crate::tuple::ToTupleBuffer;
+
+const REQUEST_TYPE: u8 = 0x00;
+const SYNC: u8 = 0x01;
+const SCHEMA_VERSION: u8 = 0x05;
+const DATA: u8 = 0x30;
+const ERROR: u8 = 0x31;
+const VERSION: u8 = 0x54;
+const FEATURES: u8 = 0x55;
+const EVENT_KEY: u8 = 0x57;
+const EVENT_DATA: u8 = 0x58;
+
+/// Base64 encoded salt sent in the greeting.
+const SALT: &str = "QK2HoFZGXTXBq2vFj7soCsHqTo6PGTF575ssUBAJLAI=";
+
+/// Matches the requests received by a [`MockPeer`].
+///
+/// All the specified conditions must hold for a request to match.
+#[derive(Default)]
+pub struct Expect {
+    kind: Option<RequestKind>,
+    name: Option<String>,
+    space_id: Option<u32>,
+    predicate: Option<Predicate>,
+}
+
+type Predicate = Box<dyn Fn(&RequestInfo) -> bool>;
+
+impl Expect {
+    /// Matches any request.
+    #[inline(always)]
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// Matches requests of the given `kind`.
+    #[inline(always)]
+    pub fn kind(kind: RequestKind) -> Self {
+        Self {
+            kind: Some(kind),
+            ..Self::default()
+        }
+    }
+
+    /// Matches ping requests.
+    #[inline(always)]
+    pub fn ping() -> Self {
+        Self::kind(RequestKind::Ping)
+    }
+
+    /// Matches calls of the function `function_name`.
+    #[inline(always)]
+    pub fn call(function_name: impl Into<String>) -> Self {
+        Self::kind(RequestKind::Call).name(function_name)
+    }
+
+    /// Matches evals of the lua expression `expr`.
+    #[inline(always)]
+    pub fn eval(expr: impl Into<String>) -> Self {
+        Self::kind(RequestKind::Eval).name(expr)
+    }
+
+    /// Matches subscriptions to the `key`. Is usually replied to with
+    /// [`Reply::event`]. Watch requests which don't match the next
+    /// expectation are ignored, as the client sends one every time it
+    /// acknowledges an event.
+    #[inline(always)]
+    pub fn watch(key: impl Into<String>) -> Self {
+        Self::kind(RequestKind::Watch).name(key)
+    }
+
+    /// Additionally requires the [`RequestInfo::name`] to be equal to `name`.
+    #[inline(always)]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Additionally requires the request to be made to the space with id
+    /// `space_id`.
+    #[inline(always)]
+    pub fn space_id(mut self, space_id: u32) -> Self {
+        self.space_id = Some(space_id);
+        self
+    }
+
+    /// Additionally requires the `predicate` to return `true` for
+    /// the request.
+    #[inline(always)]
+    pub fn matching(mut self, predicate: impl Fn(&RequestInfo) -> bool + 'static) -> Self {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    fn matches(&self, request: &RequestInfo) -> bool {
+        self.kind.map_or(true, |kind| kind == request.kind)
+            && self
+                .name
+                .as_ref()
+                .map_or(true, |name| Some(name) == request.name.as_ref())
+            && self
+                .space_id
+                .map_or(true, |space_id| Some(space_id) == request.space_id)
+            && self
+                .predicate
+                .as_ref()
+                .map_or(true, |predicate| predicate(request))
+    }
+}
+
+impl fmt::Debug for Expect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Expect")
+            .field("kind", &self.kind)
+            .field("name", &self.name)
+            .field("space_id", &self.space_id)
+            .field("predicate", &self.predicate.is_some())
+            .finish()
+    }
+}
+
+/// What a [`MockPeer`] does once it receives the expected request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    action: Action,
+    delay: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    /// Send the response with the given msgpack encoded body.
+    Respond(Vec<u8>),
+    Error {
+        code: u32,
+        message: String,
+    },
+    /// Send the event with the given key and msgpack encoded value.
+    Event {
+        key: String,
+        data: Vec<u8>,
+    },
+    DropConnection,
+    Ignore,
+}
+
+impl Reply {
+    #[inline(always)]
+    fn new(action: Action) -> Self {
+        Self {
+            action,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Responds with an empty body, e.g. to a ping.
+    #[inline(always)]
+    pub fn ok() -> Self {
+        Self::from_raw_body(vec![0x80])
+    }
+
+    /// Responds with the `data`, e.g. the values returned from a function.
+    ///
+    /// # Panicking
+    /// Panics if `data` can't be encoded.
+    pub fn data<T>(data: &T) -> Self
+    where
+        T: ToTupleBuffer + ?Sized,
+    {
+        let mut body = vec![];
+        rmp::encode::write_map_len(&mut body, 1).expect("writing to vec never fails");
+        rmp::encode::write_pfix(&mut body, DATA).expect("writing to vec never fails");
+        data.write_tuple_data(&mut body)
+            .expect("reply data should be encodable");
+        Self::from_raw_body(body)
+    }
+
+    /// Responds with an array of `tuples`, e.g. the result of a select.
+    ///
+    /// # Panicking
+    /// Panics if `tuples` can't be encoded.
+    pub fn tuples<T>(tuples: &[T]) -> Self
+    where
+        T: ToTupleBuffer,
+    {
+        let mut body = vec![];
+        rmp::encode::write_map_len(&mut body, 1).expect("writing to vec never fails");
+        rmp::encode::write_pfix(&mut body, DATA).expect("writing to vec never fails");
+        rmp::encode::write_array_len(&mut body, tuples.len() as _)
+            .expect("writing to vec never fails");
+        for tuple in tuples {
+            tuple
+                .write_tuple_data(&mut body)
+                .expect("reply tuples should be encodable");
+        }
+        Self::from_raw_body(body)
+    }
+
+    /// Responds with the msgpack encoded response `body`.
+    #[inline(always)]
+    pub fn from_raw_body(body: Vec<u8>) -> Self {
+        Self::new(Action::Respond(body))
+    }
+
+    /// Responds with an error.
+    #[inline(always)]
+    pub fn error(code: u32, message: impl Into<String>) -> Self {
+        Self::new(Action::Error {
+            code,
+            message: message.into(),
+        })
+    }
+
+    /// Broadcasts the `value` of the `key` instead of responding, e.g. to
+    /// a watch request. `Reply::event("box.shutdown", &true)` tells the
+    /// client that the server is shutting down.
+    ///
+    /// # Panicking
+    /// Panics if `value` can't be encoded.
+    pub fn event<T>(key: impl Into<String>, value: &T) -> Self
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        Self::new(Action::Event {
+            key: key.into(),
+            data: rmp_serde::to_vec(value).expect("event value should be encodable"),
+        })
+    }
+
+    /// Closes the connection instead of responding. The responses sent
+    /// before are still delivered.
+    #[inline(always)]
+    pub fn drop_connection() -> Self {
+        Self::new(Action::DropConnection)
+    }
+
+    /// Never responds to the request, e.g. to test timeouts.
+    #[inline(always)]
+    pub fn no_response() -> Self {
+        Self::new(Action::Ignore)
+    }
+
+    /// Postpones the reply by `delay` since the request is received.
+    /// Replies to the requests received later may be sent first.
+    #[inline(always)]
+    pub fn after(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A reply which is sent once the delay passes.
+#[derive(Debug)]
+struct Delayed {
+    remaining: Duration,
+    sync: u64,
+    action: Action,
+}
+
+/// Scriptable fake binary protocol server.
+/// See [module level documentation](self) for details.
+#[derive(Debug)]
+pub struct MockPeer {
+    expectations: VecDeque<(Expect, Reply)>,
+    auth_reply: Reply,
+    features: Option<ProtocolFeatures>,
+    schema_version: u64,
+    /// Received bytes not yet processed.
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+    delayed: Vec<Delayed>,
+    received: Vec<RequestInfo>,
+    unexpected: Vec<RequestInfo>,
+    closed: bool,
+}
+
+impl Default for MockPeer {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockPeer {
+    /// Creates a peer which expects no requests. The greeting is ready to be
+    /// sent right away.
+    pub fn new() -> Self {
+        let mut peer = Self {
+            expectations: VecDeque::new(),
+            auth_reply: Reply::ok(),
+            features: Some(ProtocolFeatures::new(
+                protocol::PROTOCOL_VERSION,
+                Feature::ALL.iter().map(|&f| f as u64),
+            )),
+            schema_version: 1,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            delayed: Vec::new(),
+            received: Vec::new(),
+            unexpected: Vec::new(),
+            closed: false,
+        };
+        peer.reconnect();
+        peer
+    }
+
+    /// Adds an expectation of a request matching `request` to which
+    /// the peer replies with `reply`.
+    ///
+    /// Requests are expected in the order the expectations are added.
+    #[inline(always)]
+    pub fn expect(mut self, request: Expect, reply: Reply) -> Self {
+        self.expectations.push_back((request, reply));
+        self
+    }
+
+    /// Sets the reply to auth requests. The credentials aren't checked.
+    ///
+    /// Default: [`Reply::ok`].
+    #[inline(always)]
+    pub fn auth_reply(mut self, reply: Reply) -> Self {
+        self.auth_reply = reply;
+        self
+    }
+
+    /// Sets the protocol features reported in response to the id request.
+    /// If `None`, the id request is rejected like servers prior to
+    /// Tarantool 2.10 do.
+    ///
+    /// Default: all the [`Feature`]s of [`protocol::PROTOCOL_VERSION`].
+    #[inline(always)]
+    pub fn features(mut self, features: Option<ProtocolFeatures>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Sets the schema version sent in the response headers.
+    ///
+    /// Default: `1`.
+    #[inline(always)]
+    pub fn schema_version(mut self, schema_version: u64) -> Self {
+        self.schema_version = schema_version;
+        self
+    }
+
+    /// Starts a new connection: discards the unprocessed bytes, the outgoing
+    /// data and the delayed replies and queues the greeting. The remaining
+    /// expectations are kept.
+    pub fn reconnect(&mut self) {
+        self.incoming.clear();
+        self.outgoing.clear();
+        self.delayed.clear();
+        self.closed = false;
+        for line in ["Tarantool 2.10.0 (Binary) mock", SALT] {
+            self.outgoing.extend(line.as_bytes());
+            self.outgoing
+                .resize(self.outgoing.len() + 63 - line.len(), b' ');
+            self.outgoing.push(b'\n');
+        }
+    }
+
+    /// Processes the bytes sent by the client. Replies to the complete
+    /// requests are appended to the outgoing data, see
+    /// [`MockPeer::drain_outgoing`].
+    ///
+    /// Data received after the connection is dropped is ignored.
+    pub fn process_incoming(&mut self, data: &[u8]) -> crate::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        self.incoming.extend(data);
+        let mut consumed = 0;
+        while !self.closed {
+            let mut cursor = Cursor::new(&self.incoming[consumed..]);
+            let len: usize = match rmp::decode::read_int(&mut cursor) {
+                Ok(len) => len,
+                Err(rmp::decode::NumValueReadError::InvalidMarkerRead(e))
+                | Err(rmp::decode::NumValueReadError::InvalidDataRead(e))
+                    if e.kind() == io::ErrorKind::UnexpectedEof =>
+                {
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let start = consumed + cursor.position() as usize;
+            if self.incoming.len() < start + len {
+                break;
+            }
+            let request = self.incoming[start..start + len].to_vec();
+            self.process_request(&request)?;
+            consumed = start + len;
+        }
+        self.incoming.drain(..consumed);
+        Ok(())
+    }
+
+    fn process_request(&mut self, data: &[u8]) -> crate::Result<()> {
+        let (request_type, sync) = decode_header(data)?;
+        if request_type == IProtoType::Id as u64 {
+            let action = match self.features {
+                Some(features) => Action::Respond(encode_id(features)?),
+                None => Action::Error {
+                    code: TarantoolErrorCode::UnknownRequestType as u32,
+                    message: format!("Unknown request type {}", request_type),
+                },
+            };
+            return self.reply(sync, action, Duration::ZERO);
+        }
+        if request_type == IProtoType::Auth as u64 {
+            let Reply { action, delay } = self.auth_reply.clone();
+            return self.reply(sync, action, delay);
+        }
+
+        let request = RequestInfo::decode(data, |_| None).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported request type {}", request_type),
+            )
+        })?;
+        self.received.push(request.clone());
+        match self.expectations.front() {
+            Some((expect, _)) if expect.matches(&request) => {
+                let (_, Reply { action, delay }) = self.expectations.pop_front().expect("is some");
+                self.reply(sync, action, delay)
+            }
+            // Watchers are only answered when an event is broadcast
+            _ if matches!(request.kind, RequestKind::Watch | RequestKind::Unwatch) => Ok(()),
+            _ => {
+                let message = format!(
+                    "unexpected {} request {:?}, expected {:?}",
+                    request.kind,
+                    request.name,
+                    self.expectations.front().map(|(expect, _)| expect),
+                );
+                self.unexpected.push(request);
+                let action = Action::Error {
+                    code: TarantoolErrorCode::ProcC as u32,
+                    message,
+                };
+                self.reply(sync, action, Duration::ZERO)
+            }
+        }
+    }
+
+    fn reply(&mut self, sync: u64, action: Action, delay: Duration) -> crate::Result<()> {
+        if delay.is_zero() {
+            return self.perform(sync, action);
+        }
+        self.delayed.push(Delayed {
+            remaining: delay,
+            sync,
+            action,
+        });
+        Ok(())
+    }
+
+    fn perform(&mut self, sync: u64, action: Action) -> crate::Result<()> {
+        let (status, body) = match action {
+            Action::Respond(body) => (0, body),
+            Action::Error { code, message } => {
+                let mut body = Vec::new();
+                rmp::encode::write_map_len(&mut body, 1)?;
+                rmp::encode::write_pfix(&mut body, ERROR)?;
+                rmp::encode::write_str(&mut body, &message)?;
+                // See `IPROTO_TYPE_ERROR` in tarantool docs
+                (0x8000 | code as u64, body)
+            }
+            Action::Event { key, data } => {
+                let mut header = Vec::new();
+                rmp::encode::write_map_len(&mut header, 1)?;
+                rmp::encode::write_pfix(&mut header, REQUEST_TYPE)?;
+                rmp::encode::write_uint(&mut header, IProtoType::Event as u64)?;
+                let mut body = Vec::new();
+                rmp::encode::write_map_len(&mut body, 2)?;
+                rmp::encode::write_pfix(&mut body, EVENT_KEY)?;
+                rmp::encode::write_str(&mut body, &key)?;
+                rmp::encode::write_pfix(&mut body, EVENT_DATA)?;
+                body.extend_from_slice(&data);
+                return self.send_message(&header, &body);
+            }
+            Action::DropConnection => {
+                self.closed = true;
+                self.delayed.clear();
+                return Ok(());
+            }
+            Action::Ignore => return Ok(()),
+        };
+        let mut header = Vec::new();
+        rmp::encode::write_map_len(&mut header, 3)?;
+        rmp::encode::write_pfix(&mut header, REQUEST_TYPE)?;
+        rmp::encode::write_uint(&mut header, status)?;
+        rmp::encode::write_pfix(&mut header, SYNC)?;
+        rmp::encode::write_uint(&mut header, sync)?;
+        rmp::encode::write_pfix(&mut header, SCHEMA_VERSION)?;
+        rmp::encode::write_uint(&mut header, self.schema_version)?;
+        self.send_message(&header, &body)
+    }
+
+    fn send_message(&mut self, header: &[u8], body: &[u8]) -> crate::Result<()> {
+        rmp::encode::write_u32(&mut self.outgoing, (header.len() + body.len()) as u32)?;
+        self.outgoing.write_all(header)?;
+        self.outgoing.write_all(body)?;
+        Ok(())
+    }
+
+    /// Advances the time of the peer by `elapsed`, the delayed replies
+    /// the time of which has come are performed.
+    pub fn advance(&mut self, elapsed: Duration) {
+        let mut due = Vec::new();
+        for mut delayed in std::mem::take(&mut self.delayed) {
+            if delayed.remaining <= elapsed {
+                due.push(delayed);
+            } else {
+                delayed.remaining -= elapsed;
+                self.delayed.push(delayed);
+            }
+        }
+        due.sort_by_key(|delayed| delayed.remaining);
+        for Delayed { sync, action, .. } in due {
+            if self.closed {
+                break;
+            }
+            self.perform(sync, action)
+                .expect("encoding a reply never fails");
+        }
+    }
+
+    /// Returns the time left until the next delayed reply or `None` if there
+    /// are no delayed replies.
+    #[inline]
+    pub fn next_delay(&self) -> Option<Duration> {
+        self.delayed.iter().map(|delayed| delayed.remaining).min()
+    }
+
+    /// Returns the data to be sent to the client.
+    #[inline(always)]
+    pub fn drain_outgoing(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Returns `true` if the connection was dropped by a
+    /// [`Reply::drop_connection`]. Once the outgoing data is sent
+    /// the connection must be closed.
+    #[inline(always)]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns all the requests received so far, except for the id and
+    /// the auth requests.
+    #[inline(always)]
+    pub fn received(&self) -> &[RequestInfo] {
+        &self.received
+    }
+
+    /// Returns the number of expected requests not yet received.
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        self.expectations.len()
+    }
+
+    /// Checks that all the expected requests were received and no unexpected
+    /// ones were.
+    ///
+    /// # Panicking
+    /// Panics if the check fails.
+    #[track_caller]
+    pub fn assert_done(&self) {
+        assert!(
+            self.unexpected.is_empty(),
+            "unexpected requests received: {:?}",
+            self.unexpected
+        );
+        assert!(
+            self.expectations.is_empty(),
+            "expected requests not received: {:?}",
+            self.expectations
+        );
+    }
+}
+
+/// Delivers the outgoing data of `protocol` to `peer` and vice versa until
+/// neither has anything more to send.
+///
+/// Returns the indexes of the responses received by the `protocol` which can
+/// be retrieved with [`Protocol::take_response`]. Check
+/// [`MockPeer::is_closed`] to find out whether the connection was dropped.
+pub fn exchange(
+    protocol: &mut Protocol,
+    peer: &mut MockPeer,
+) -> Result<Vec<SyncIndex>, protocol::Error> {
+    let mut responses = Vec::new();
+    loop {
+        let outgoing: Vec<u8> = protocol.drain_outgoing_data(None).collect();
+        peer.process_incoming(&outgoing)?;
+        let incoming = peer.drain_outgoing();
+        if incoming.is_empty() {
+            return Ok(responses);
+        }
+        let mut cursor = Cursor::new(&incoming[..]);
+        while (cursor.position() as usize) < incoming.len() {
+            let len = match protocol.read_size_hint() {
+                SizeHint::Hint(len) => len,
+                SizeHint::FirstU32 => rmp::decode::read_u32(&mut cursor)? as usize,
+            };
+            let start = cursor.position() as usize;
+            let mut message = Cursor::new(&incoming[start..start + len]);
+            cursor.set_position((start + len) as u64);
+            if let Some(sync) = protocol.process_incoming(&mut message)? {
+                responses.push(sync);
+            }
+        }
+    }
+}
+
+fn decode_header(data: &[u8]) -> crate::Result<(u64, u64)> {
+    let mut cursor = Cursor::new(data);
+    let mut request_type = None;
+    let mut sync = None;
+    for _ in 0..rmp::decode::read_map_len(&mut cursor)? {
+        match rmp::decode::read_int(&mut cursor)? {
+            REQUEST_TYPE => request_type = Some(rmp::decode::read_int(&mut cursor)?),
+            SYNC => sync = Some(rmp::decode::read_int(&mut cursor)?),
+            _ => msgpack::skip_value(&mut cursor)?,
+        }
+    }
+    match (request_type, sync) {
+        (Some(request_type), Some(sync)) => Ok((request_type, sync)),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "incomplete request header").into()),
+    }
+}
+
+fn encode_id(features: ProtocolFeatures) -> crate::Result<Vec<u8>> {
+    let ids: Vec<_> = Feature::ALL
+        .iter()
+        .filter(|&&feature| features.supports(feature))
+        .collect();
+    let mut body = Vec::new();
+    rmp::encode::write_map_len(&mut body, 2)?;
+    rmp::encode::write_pfix(&mut body, VERSION)?;
+    rmp::encode::write_uint(&mut body, features.version())?;
+    rmp::encode::write_pfix(&mut body, FEATURES)?;
+    rmp::encode::write_array_len(&mut body, ids.len() as _)?;
+    for &&feature in &ids {
+        rmp::encode::write_uint(&mut body, feature as u64)?;
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IteratorType;
+    use crate::network::protocol::api::{Call, Ping, Select, Watch};
+    use crate::tuple::RawByteBuf;
+
+    fn connected(peer: &mut MockPeer) -> Protocol {
+        let mut protocol = Protocol::with_config(protocol::Config {
+            creds: Some(("test_user".into(), "password".into())),
+            ..Default::default()
+        });
+        assert!(exchange(&mut protocol, peer).unwrap().is_empty());
+        assert!(protocol.is_ready());
+        protocol
+    }
+
+    #[test]
+    fn handshake() {
+        let mut peer = MockPeer::new();
+        let protocol = connected(&mut peer);
+        assert_eq!(
+            protocol.features().unwrap().version(),
+            protocol::PROTOCOL_VERSION
+        );
+        assert!(protocol.features().unwrap().supports(Feature::Watchers));
+        peer.assert_done();
+
+        let mut peer = MockPeer::new().features(None);
+        let protocol = connected(&mut peer);
+        assert_eq!(protocol.features(), Some(ProtocolFeatures::default()));
+
+        let mut peer = MockPeer::new().auth_reply(Reply::error(
+            TarantoolErrorCode::PasswordMismatch as u32,
+            "wrong password",
+        ));
+        let mut protocol = Protocol::with_config(protocol::Config {
+            creds: Some(("test_user".into(), "wrong".into())),
+            ..Default::default()
+        });
+        let err = exchange(&mut protocol, &mut peer).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "service responded with error: wrong password"
+        );
+    }
+
+    /// Returns the status of the response without decoding its body, as
+    /// the tuples can't be created outside of tarantool.
+    fn take_status(protocol: &mut Protocol, sync: SyncIndex) -> Result<(), protocol::Error> {
+        protocol.take_response(sync, &Ping).unwrap()
+    }
+
+    #[test]
+    fn replies() {
+        let mut peer = MockPeer::new()
+            .expect(Expect::call("foo"), Reply::data(&(1, "bar")))
+            .expect(
+                Expect::kind(RequestKind::Select).space_id(512),
+                Reply::tuples(&[(1,), (2,)]),
+            )
+            .expect(
+                Expect::call("foo"),
+                Reply::error(TarantoolErrorCode::ProcLua as u32, "oops"),
+            );
+        let mut protocol = connected(&mut peer);
+
+        let call = Call {
+            fn_name: "foo",
+            args: &(),
+        };
+        let sync = protocol.send_request(&call).unwrap();
+        assert_eq!(exchange(&mut protocol, &mut peer).unwrap(), [sync]);
+        take_status(&mut protocol, sync).unwrap();
+
+        let select = Select {
+            space_id: 512,
+            index_id: 0,
+            limit: 10,
+            offset: 0,
+            iterator_type: IteratorType::All,
+            key: &(),
+        };
+        let sync = protocol.send_request(&select).unwrap();
+        assert_eq!(exchange(&mut protocol, &mut peer).unwrap(), [sync]);
+        take_status(&mut protocol, sync).unwrap();
+
+        let sync = protocol.send_request(&call).unwrap();
+        exchange(&mut protocol, &mut peer).unwrap();
+        let err = take_status(&mut protocol, sync).unwrap_err();
+        assert_eq!(err.to_string(), "service responded with error: oops");
+
+        peer.assert_done();
+        let kinds: Vec<_> = peer.received().iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            [RequestKind::Call, RequestKind::Select, RequestKind::Call]
+        );
+    }
+
+    #[test]
+    fn reply_data() {
+        let mut expected = vec![0x81, 0x30];
+        expected.extend(rmp_serde::to_vec(&(1, "bar")).unwrap());
+        assert_eq!(Reply::data(&(1, "bar")), Reply::from_raw_body(expected));
+
+        let mut expected = vec![0x81, 0x30, 0x92];
+        expected.extend(rmp_serde::to_vec(&(1,)).unwrap());
+        expected.extend(rmp_serde::to_vec(&(2,)).unwrap());
+        assert_eq!(Reply::tuples(&[(1,), (2,)]), Reply::from_raw_body(expected));
+    }
+
+    #[test]
+    fn delays() {
+        let mut peer = MockPeer::new()
+            .expect(Expect::ping(), Reply::ok().after(Duration::from_secs(2)))
+            .expect(Expect::ping(), Reply::ok().after(Duration::from_secs(1)))
+            .expect(Expect::ping(), Reply::no_response());
+        let mut protocol = connected(&mut peer);
+
+        let first = protocol.send_request(&Ping).unwrap();
+        let second = protocol.send_request(&Ping).unwrap();
+        protocol.send_request(&Ping).unwrap();
+        assert!(exchange(&mut protocol, &mut peer).unwrap().is_empty());
+        assert_eq!(peer.next_delay(), Some(Duration::from_secs(1)));
+
+        peer.advance(Duration::from_secs(1));
+        assert_eq!(exchange(&mut protocol, &mut peer).unwrap(), [second]);
+        assert_eq!(peer.next_delay(), Some(Duration::from_secs(1)));
+
+        peer.advance(Duration::from_secs(5));
+        assert_eq!(exchange(&mut protocol, &mut peer).unwrap(), [first]);
+        assert_eq!(peer.next_delay(), None);
+        peer.assert_done();
+    }
+
+    #[test]
+    fn drop_connection() {
+        let mut peer = MockPeer::new()
+            .expect(Expect::ping(), Reply::ok())
+            .expect(Expect::ping(), Reply::drop_connection())
+            .expect(Expect::ping(), Reply::ok());
+        let mut protocol = connected(&mut peer);
+
+        let sync = protocol.send_request(&Ping).unwrap();
+        protocol.send_request(&Ping).unwrap();
+        protocol.send_request(&Ping).unwrap();
+        assert_eq!(exchange(&mut protocol, &mut peer).unwrap(), [sync]);
+        assert!(peer.is_closed());
+        assert_eq!(peer.remaining(), 1);
+
+        // The remaining expectations are kept for the next connection
+        peer.reconnect();
+        let mut protocol = connected(&mut peer);
+        let sync = protocol.send_request(&Ping).unwrap();
+        assert_eq!(exchange(&mut protocol, &mut peer).unwrap(), [sync]);
+        peer.assert_done();
+    }
+
+    #[test]
+    fn event() {
+        let mut peer = MockPeer::new().expect(
+            Expect::watch("box.shutdown"),
+            Reply::event("box.shutdown", &true),
+        );
+        let mut protocol = connected(&mut peer);
+
+        protocol
+            .send_request(&Watch {
+                key: "box.shutdown",
+            })
+            .unwrap();
+        assert!(exchange(&mut protocol, &mut peer).unwrap().is_empty());
+        let event = protocol.take_event().unwrap();
+        assert_eq!(event.key, "box.shutdown");
+        assert_eq!(event.data, RawByteBuf(vec![0xc3]));
+        assert_eq!(peer.received()[0].name.as_deref(), Some("box.shutdown"));
+
+        // Acknowledgements of the event are ignored
+        protocol
+            .send_request(&Watch {
+                key: "box.shutdown",
+            })
+            .unwrap();
+        exchange(&mut protocol, &mut peer).unwrap();
+        assert!(protocol.take_event().is_none());
+        peer.assert_done();
+    }
+
+    #[test]
+    #[should_panic = "unexpected requests received"]
+    fn unexpected_request() {
+        let mut peer = MockPeer::new().expect(Expect::call("foo"), Reply::ok());
+        let mut protocol = connected(&mut peer);
+
+        let call = Call {
+            fn_name: "bar",
+            args: &(),
+        };
+        let sync = protocol.send_request(&call).unwrap();
+        exchange(&mut protocol, &mut peer).unwrap();
+        assert!(take_status(&mut protocol, sync).is_err());
+        assert_eq!(peer.remaining(), 1);
+        peer.assert_done();
+    }
+}
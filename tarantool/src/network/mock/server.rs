@@ -0,0 +1,590 @@
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::rc::Rc;
+use std::time::Instant;
+
+use super::MockPeer;
+use crate::coio::{CoIOListener, CoIOStream};
+use crate::fiber;
+
+/// Serves a [`MockPeer`] over TCP, so that it can be connected to with
+/// [`network::client::Client`] or [`net_box::Conn`].
+///
+/// Connections are served one at a time in a separate fiber. Every new
+/// connection starts with [`MockPeer::reconnect`], so a client which
+/// reconnects after a [`Reply::drop_connection`] receives the remaining
+/// replies over the new connection.
+///
+/// Can be used only from inside Tarantool.
+///
+/// # Example
+/// ```no_run
+/// # async {
+/// use tarantool::network::client::Client;
+/// use tarantool::network::mock::{Expect, MockPeer, MockServer, Reply};
+///
+/// let peer = MockPeer::new().expect(Expect::call("echo"), Reply::data(&("hello",)));
+/// let server = MockServer::start(peer).unwrap();
+///
+/// let client = Client::connect("127.0.0.1", server.port()).await.unwrap();
+/// client.call("echo", &("hello",)).await.unwrap();
+///
+/// server.finish().assert_done();
+/// # };
+/// ```
+///
+/// [`network::client::Client`]: crate::network::client::Client
+/// [`net_box::Conn`]: crate::net_box::Conn
+/// [`Reply::drop_connection`]: super::Reply::drop_connection
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Rc<State>,
+    handle: Option<fiber::JoinHandle<'static, crate::Result<MockPeer>>>,
+}
+
+#[derive(Debug)]
+struct State {
+    stopped: Cell<bool>,
+    listener_fd: RawFd,
+    /// Socket of the connection being served.
+    connection_fd: Cell<Option<RawFd>>,
+}
+
+impl MockServer {
+    /// Starts serving the `peer` on a random port of the loopback interface.
+    pub fn start(peer: MockPeer) -> crate::Result<Self> {
+        let listener = CoIOListener::try_from(TcpListener::bind("127.0.0.1:0")?)?;
+        let addr = listener.local_addr()?;
+        let state = Rc::new(State {
+            stopped: Cell::new(false),
+            listener_fd: listener.as_raw_fd(),
+            connection_fd: Cell::new(None),
+        });
+        let handle = fiber::Builder::new()
+            .name("mock_server")
+            .func({
+                let state = state.clone();
+                move || serve(listener, peer, &state)
+            })
+            .start()?;
+        Ok(Self {
+            addr,
+            state,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    #[inline(always)]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the port the server is listening on.
+    #[inline(always)]
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// Stops the server, closes the connection being served and returns
+    /// the peer, e.g. to call [`MockPeer::assert_done`]. This function
+    /// yields.
+    ///
+    /// # Panicking
+    /// Panics if serving the peer failed, e.g. because a malformed request
+    /// was received.
+    #[track_caller]
+    pub fn finish(mut self) -> MockPeer {
+        self.stop().expect("mock server failed")
+    }
+
+    fn stop(&mut self) -> crate::Result<MockPeer> {
+        self.state.stopped.set(true);
+        // Wakes the fiber waiting for a new connection or a request
+        unsafe {
+            libc::shutdown(self.state.listener_fd, libc::SHUT_RD);
+            if let Some(fd) = self.state.connection_fd.get() {
+                libc::shutdown(fd, libc::SHUT_RD);
+            }
+        }
+        self.handle.take().expect("is set until stopped").join()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            if let Err(e) = self.stop() {
+                log::warn!("mock server failed: {}", e);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockServer")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+fn serve(listener: CoIOListener, mut peer: MockPeer, state: &State) -> crate::Result<MockPeer> {
+    loop {
+        let mut stream = match listener.accept() {
+            Ok(_) | Err(_) if state.stopped.get() => return Ok(peer),
+            Ok(stream) => stream,
+            Err(e) => return Err(e.into()),
+        };
+        state.connection_fd.set(Some(stream.as_raw_fd()));
+        peer.reconnect();
+        let result = serve_connection(&mut stream, &mut peer);
+        state.connection_fd.set(None);
+        result?;
+    }
+}
+
+/// Serves a single connection until either side closes it.
+fn serve_connection(stream: &mut CoIOStream, peer: &mut MockPeer) -> crate::Result<()> {
+    let mut buf = vec![0; 4096];
+    loop {
+        stream.write_all(&peer.drain_outgoing())?;
+        if peer.is_closed() {
+            return Ok(());
+        }
+        let start = Instant::now();
+        let result = stream.read_with_timeout(&mut buf, peer.next_delay());
+        peer.advance(start.elapsed());
+        match result {
+            // The client closed the connection
+            Ok(0) => return Ok(()),
+            Ok(len) => peer.process_incoming(&buf[..len])?,
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::error::TarantoolErrorCode;
+    use crate::fiber::r#async::timeout::{self, IntoTimeout as _};
+    use crate::index::IteratorType;
+    use crate::net_box::{Conn, ConnOptions, Options};
+    use crate::network::client::{reconnect, Client, Error};
+    use crate::network::interceptor::RequestKind;
+    use crate::network::mock::{Expect, Reply};
+    use crate::network::protocol::{self, Error as ProtocolError};
+    use std::time::Duration;
+
+    #[crate::test(tarantool = "crate")]
+    fn client_replies() {
+        let peer = MockPeer::new()
+            .expect(Expect::call("echo"), Reply::data(&(1, "foo")))
+            .expect(
+                Expect::ping(),
+                Reply::ok().after(Duration::from_millis(100)),
+            )
+            .expect(Expect::ping(), Reply::no_response())
+            .expect(
+                Expect::any().space_id(512),
+                Reply::tuples(&[(1, "foo"), (2, "bar")]),
+            )
+            .expect(
+                Expect::call("echo"),
+                Reply::error(TarantoolErrorCode::ProcLua as u32, "oops"),
+            );
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect("127.0.0.1", server.port()).await.unwrap();
+
+            let result = client.call("echo", &(1, "foo")).await.unwrap().unwrap();
+            assert_eq!(result.decode::<(u32, String)>().unwrap(), (1, "foo".into()));
+
+            let start = Instant::now();
+            client.ping().await.unwrap();
+            assert!(start.elapsed() >= Duration::from_millis(100));
+
+            let err = client
+                .ping()
+                .timeout(Duration::from_millis(50))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, timeout::Error::Expired));
+
+            let tuples = client
+                .select(512, 0, IteratorType::All, &(), None, 0)
+                .await
+                .unwrap();
+            let tuples: Vec<(u32, String)> = tuples.iter().map(|t| t.decode().unwrap()).collect();
+            assert_eq!(tuples, [(1, "foo".into()), (2, "bar".into())]);
+
+            let err = client.call("echo", &()).await.unwrap_err();
+            match err {
+                Error::Protocol(ProtocolError::Response(e)) => {
+                    assert_eq!(e.code(), TarantoolErrorCode::ProcLua as u32);
+                    assert_eq!(e.message(), "oops");
+                }
+                e => panic!("unexpected error: {}", e),
+            }
+        });
+
+        let peer = server.finish();
+        peer.assert_done();
+        assert_eq!(peer.received()[0].name.as_deref(), Some("echo"));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_reconnect() {
+        let peer = MockPeer::new()
+            .expect(Expect::ping(), Reply::ok())
+            .expect(Expect::ping(), Reply::drop_connection())
+            .expect(Expect::ping(), Reply::ok());
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect_with_reconnect(
+                "127.0.0.1",
+                server.port(),
+                protocol::Config::default(),
+                reconnect::Policy {
+                    backoff: reconnect::Backoff::Fixed(Duration::from_millis(10)),
+                    in_flight: reconnect::InFlight::Retry,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            client.ping().await.unwrap();
+            // Is sent again over the new connection
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+        });
+
+        server.finish().assert_done();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_reconnect_stream() {
+        let peer = MockPeer::new()
+            .expect(Expect::kind(RequestKind::Begin), Reply::ok())
+            .expect(Expect::call("transfer"), Reply::drop_connection())
+            .expect(Expect::ping(), Reply::ok());
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect_with_reconnect(
+                "127.0.0.1",
+                server.port(),
+                protocol::Config::default(),
+                reconnect::Policy {
+                    backoff: reconnect::Backoff::Fixed(Duration::from_millis(10)),
+                    in_flight: reconnect::InFlight::Retry,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            let stream = client.stream();
+            stream
+                .begin(crate::transaction::IsolationLevel::Default, None)
+                .await
+                .unwrap();
+            // The transaction is rolled back by the server once the connection
+            // is lost, so the request is not sent again
+            let err = stream
+                .call("transfer", &())
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                &err,
+                timeout::Error::Failed(Error::ConnectionLost(e)) if e.contains("transaction aborted")
+            ));
+            // Neither are the following requests of the stream
+            let err = stream.commit().await.unwrap_err();
+            assert!(matches!(
+                &err,
+                Error::ConnectionLost(e) if e.contains("transaction aborted")
+            ));
+            // While the other requests are sent over the new connection
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+        });
+
+        let peer = server.finish();
+        peer.assert_done();
+        let kinds: Vec<_> = peer.received().iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            [RequestKind::Begin, RequestKind::Call, RequestKind::Ping]
+        );
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_reconnect_stream_batch() {
+        let peer = MockPeer::new()
+            .expect(Expect::kind(RequestKind::Begin), Reply::ok())
+            .expect(Expect::ping(), Reply::ok())
+            .expect(Expect::ping(), Reply::drop_connection())
+            .expect(Expect::ping(), Reply::ok());
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect_with_reconnect(
+                "127.0.0.1",
+                server.port(),
+                protocol::Config::default(),
+                reconnect::Policy {
+                    backoff: reconnect::Backoff::Fixed(Duration::from_millis(10)),
+                    in_flight: reconnect::InFlight::Retry,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            let stream = client.stream();
+            stream
+                .begin(crate::transaction::IsolationLevel::Default, None)
+                .await
+                .unwrap();
+            let mut batch = stream.batch::<()>().max_in_flight(1);
+            for _ in 0..3 {
+                batch.push(protocol::api::Ping);
+            }
+            let results = batch.execute().await;
+            assert!(results[0].is_ok());
+            // Neither the request in flight nor the following one is sent
+            // over the new connection
+            for result in &results[1..] {
+                assert!(matches!(
+                    result,
+                    Err(Error::ConnectionLost(e)) if e.contains("transaction aborted")
+                ));
+            }
+            client.ping().timeout(Duration::from_secs(3)).await.unwrap();
+        });
+
+        let peer = server.finish();
+        peer.assert_done();
+        assert_eq!(peer.received().len(), 4);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_shutdown() {
+        let peer = MockPeer::new()
+            .expect(
+                Expect::watch("box.shutdown"),
+                Reply::event("box.shutdown", &true).after(Duration::from_millis(100)),
+            )
+            .expect(
+                Expect::call("slow"),
+                Reply::data(&("done",)).after(Duration::from_millis(300)),
+            );
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect("127.0.0.1", server.port()).await.unwrap();
+            // Make sure the client is subscribed to the shutdown first
+            client.protocol_features().await.unwrap();
+            let slow = client.call("slow", &());
+            let ping = async {
+                fiber::sleep(Duration::from_millis(200));
+                client.ping().await
+            };
+            let (slow, ping) = futures::join!(slow, ping);
+            // The requests in flight are still served
+            let result = slow.unwrap().unwrap();
+            assert_eq!(result.decode::<(String,)>().unwrap(), ("done".into(),));
+            // While the new ones are not sent
+            assert!(matches!(ping, Err(Error::ServerShutdown)));
+            // The connection is closed once the requests in flight are done
+            fiber::sleep(Duration::from_millis(50));
+            assert!(matches!(client.ping().await, Err(Error::ServerShutdown)));
+        });
+
+        let peer = server.finish();
+        peer.assert_done();
+        assert_eq!(peer.received().len(), 2);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_shutdown_timeout() {
+        let peer = MockPeer::new()
+            .expect(
+                Expect::watch("box.shutdown"),
+                Reply::event("box.shutdown", &true).after(Duration::from_millis(50)),
+            )
+            .expect(Expect::call("stuck"), Reply::no_response());
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect("127.0.0.1", server.port()).await.unwrap();
+            client.set_shutdown_timeout(Duration::from_millis(100));
+            client.protocol_features().await.unwrap();
+            let start = Instant::now();
+            let err = client
+                .call("stuck", &())
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, timeout::Error::Failed(Error::ServerShutdown)));
+            assert!(start.elapsed() >= Duration::from_millis(100));
+        });
+
+        server.finish().assert_done();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_prepare_without_stmt_id() {
+        let peer = MockPeer::new().expect(Expect::kind(RequestKind::Prepare), Reply::ok());
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect("127.0.0.1", server.port()).await.unwrap();
+            let err = client.prepare("SELECT 1").await.unwrap_err();
+            assert!(matches!(err, Error::Protocol(ProtocolError::Io(_))));
+        });
+
+        server.finish().assert_done();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_prepare_reconnect() {
+        // {IPROTO_STMT_ID: 7}
+        let prepared = Reply::from_raw_body(vec![0x81, 0x43, 0x07]);
+        let prepare = || Expect::kind(RequestKind::Prepare).name("SELECT 1");
+        // Unprepare requests only contain the statement id
+        let unprepare = Expect::kind(RequestKind::Prepare).matching(|r| r.name.is_none());
+        let peer = MockPeer::new()
+            .expect(prepare(), prepared.clone())
+            .expect(prepare(), prepared.clone())
+            .expect(Expect::ping(), Reply::drop_connection())
+            .expect(prepare(), prepared)
+            .expect(unprepare, Reply::ok())
+            .expect(Expect::ping(), Reply::ok());
+        let server = MockServer::start(peer).unwrap();
+
+        fiber::block_on(async {
+            let client = Client::connect_with_reconnect(
+                "127.0.0.1",
+                server.port(),
+                protocol::Config::default(),
+                reconnect::Policy {
+                    backoff: reconnect::Backoff::Fixed(Duration::from_millis(10)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            let stmt_1 = client.prepare("SELECT 1").await.unwrap();
+            let stmt_2 = client.prepare("SELECT 1").await.unwrap();
+            // Not unprepared while there is another handle
+            drop(stmt_1);
+            client.ping().await.unwrap_err();
+
+            // Statements of the previous session are not unprepared
+            let stmt_3 = client
+                .prepare("SELECT 1")
+                .timeout(Duration::from_secs(3))
+                .await
+                .unwrap();
+            drop(stmt_2);
+            drop(stmt_3);
+            client.ping().await.unwrap();
+        });
+
+        server.finish().assert_done();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn net_box_replies() {
+        let peer = MockPeer::new()
+            .expect(Expect::call("echo"), Reply::data(&(1, "foo")))
+            .expect(Expect::eval("return box.info.ro"), Reply::data(&(true,)));
+        let server = MockServer::start(peer).unwrap();
+
+        let conn = Conn::new(server.addr(), ConnOptions::default(), None).unwrap();
+        let result = conn
+            .call("echo", &(1, "foo"), &Options::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.decode::<(u32, String)>().unwrap(), (1, "foo".into()));
+        let result = conn
+            .eval("return box.info.ro", &(), &Options::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.decode::<(bool,)>().unwrap(), (true,));
+        conn.close();
+
+        server.finish().assert_done();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn net_box_shutdown() {
+        let peer = MockPeer::new()
+            .expect(
+                Expect::watch("box.shutdown"),
+                Reply::event("box.shutdown", &true).after(Duration::from_millis(100)),
+            )
+            .expect(
+                Expect::call("slow"),
+                Reply::data(&("done",)).after(Duration::from_millis(300)),
+            );
+        let server = MockServer::start(peer).unwrap();
+
+        let conn = Conn::new(server.addr(), ConnOptions::default(), None).unwrap();
+        let slow = conn.call_async::<_, (String,)>("slow", ()).unwrap();
+        fiber::sleep(Duration::from_millis(200));
+        // New requests are not sent
+        let err = conn.ping(&Options::default()).unwrap_err();
+        assert!(matches!(err, crate::error::Error::ServerShutdown));
+        // While the ones in flight are still served
+        assert_eq!(slow.wait().unwrap(), ("done".into(),));
+        fiber::sleep(Duration::from_millis(50));
+        assert!(!conn.is_connected());
+        let err = conn.ping(&Options::default()).unwrap_err();
+        assert!(matches!(err, crate::error::Error::ServerShutdown));
+
+        server.finish().assert_done();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn net_box_shutdown_timeout() {
+        let peer = MockPeer::new()
+            .expect(
+                Expect::watch("box.shutdown"),
+                Reply::event("box.shutdown", &true).after(Duration::from_millis(50)),
+            )
+            .expect(Expect::call("stuck"), Reply::no_response());
+        let server = MockServer::start(peer).unwrap();
+
+        let options = ConnOptions {
+            shutdown_timeout: Duration::from_millis(100),
+            ..ConnOptions::default()
+        };
+        let conn = Conn::new(server.addr(), options, None).unwrap();
+        let start = Instant::now();
+        let err = conn.call("stuck", &(), &Options::default()).unwrap_err();
+        assert!(matches!(err, crate::error::Error::ServerShutdown));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        server.finish().assert_done();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn net_box_prepare_without_stmt_id() {
+        let peer = MockPeer::new().expect(Expect::kind(RequestKind::Prepare), Reply::ok());
+        let server = MockServer::start(peer).unwrap();
+
+        let conn = Conn::new(server.addr(), ConnOptions::default(), None).unwrap();
+        let err = conn.prepare("SELECT 1", &Options::default()).unwrap_err();
+        assert!(matches!(err, crate::error::Error::IO(_)));
+        conn.close();
+
+        server.finish().assert_done();
+    }
+}
@@ -3,6 +3,10 @@
 //! Consists of:
 //! - Runtime and transport agnostic [`protocol`] layer
 //! - Async and coio based [`client`] layer
+//! - Coio based [`server`] for serving binary protocol clients
+//! - [`uri`] parsing shared with [`super::net_box`]
+//! - Request [`interceptor`]s shared with [`super::net_box`]
+//! - Scriptable [`mock`] server for testing code which uses the connectors
 //!
 //! More on Sans-I/O pattern can be found on the respective [wiki](https://sans-io.readthedocs.io/how-to-sans-io.html).
 //!
@@ -11,4 +15,10 @@
 
 #[cfg(feature = "network_client")]
 pub mod client;
+pub mod interceptor;
+#[cfg(feature = "test")]
+pub mod mock;
 pub mod protocol;
+#[cfg(feature = "network_server")]
+pub mod server;
+pub mod uri;
@@ -1,17 +1,44 @@
 use std::io::{Cursor, Write};
+use std::time::Duration;
 
 use super::Error;
+use crate::auth::AuthMethod;
+use crate::index::{IteratorType, TuplePosition};
+use crate::sql::SqlResponse;
+use crate::transaction::IsolationLevel;
 use crate::tuple::{ToTupleBuffer, Tuple};
 
 use super::codec::IProtoType;
-use super::{codec, SyncIndex};
+use super::{codec, Feature, ProtocolFeatures, SyncIndex};
 
 pub trait Request {
     const TYPE: IProtoType;
     type Response: Sized;
 
+    /// Schema version expected by the client. See [`WithSchemaVersion`].
+    fn schema_version(&self) -> Option<u32> {
+        None
+    }
+
+    /// Id of the stream the request belongs to. See [`WithStreamId`].
+    fn stream_id(&self) -> Option<u64> {
+        None
+    }
+
+    /// Protocol feature the server must support to process the request.
+    /// See [`Protocol::send_request`](super::Protocol::send_request).
+    fn required_feature(&self) -> Option<Feature> {
+        None
+    }
+
     fn encode_header(&self, out: &mut impl Write, sync: SyncIndex) -> Result<(), Error> {
-        codec::encode_header(out, sync, Self::TYPE)
+        codec::encode_header_ext(
+            out,
+            sync,
+            Self::TYPE,
+            self.schema_version(),
+            self.stream_id(),
+        )
     }
 
     fn encode_body(&self, out: &mut impl Write) -> Result<(), Error>;
@@ -25,8 +52,6 @@ pub trait Request {
     fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error>;
 }
 
-// TODO: Implement `Request` for other types in `IProtoType`
-
 pub struct Ping;
 
 impl Request for Ping {
@@ -86,14 +111,448 @@ pub struct Execute<'a, 'b, T> {
 
 impl<'a, 'b, T: ToTupleBuffer> Request for Execute<'a, 'b, T> {
     const TYPE: IProtoType = IProtoType::Execute;
-    type Response = Vec<Tuple>;
+    type Response = SqlResponse;
 
     fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
         codec::encode_execute(out, self.sql, self.bind_params)
     }
 
     fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
-        codec::decode_multiple_rows(r#in, self.limit)
+        Ok(SqlResponse::decode(r#in, self.limit)?)
+    }
+}
+
+pub struct ExecutePrepared<'b, T> {
+    pub stmt_id: u32,
+    pub bind_params: &'b T,
+    pub limit: Option<usize>,
+}
+
+impl<'b, T: ToTupleBuffer> Request for ExecutePrepared<'b, T> {
+    const TYPE: IProtoType = IProtoType::Execute;
+    type Response = SqlResponse;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_execute_prepared(out, self.stmt_id, self.bind_params)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(SqlResponse::decode(r#in, self.limit)?)
+    }
+}
+
+/// Prepares the sql statement for the execution with [`ExecutePrepared`].
+///
+/// Response contains the statement id and the metadata of the statement's
+/// bind parameters and result columns.
+pub struct Prepare<'a> {
+    pub sql: &'a str,
+}
+
+impl<'a> Request for Prepare<'a> {
+    const TYPE: IProtoType = IProtoType::Prepare;
+    type Response = SqlResponse;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_prepare(out, self.sql)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(SqlResponse::decode(r#in, None)?)
+    }
+}
+
+/// Deallocates the statement prepared with [`Prepare`] in the current session.
+pub struct Unprepare {
+    pub stmt_id: u32,
+}
+
+impl Request for Unprepare {
+    const TYPE: IProtoType = IProtoType::Prepare;
+    type Response = ();
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_unprepare(out, self.stmt_id)
+    }
+
+    fn decode_body(&self, _in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(())
+    }
+}
+
+pub struct Select<'a, K> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub limit: u32,
+    pub offset: u32,
+    pub iterator_type: IteratorType,
+    pub key: &'a K,
+}
+
+impl<'a, K: ToTupleBuffer> Request for Select<'a, K> {
+    const TYPE: IProtoType = IProtoType::Select;
+    type Response = Vec<Tuple>;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_select(
+            out,
+            self.space_id,
+            self.index_id,
+            self.limit,
+            self.offset,
+            self.iterator_type,
+            self.key,
+        )
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_multiple_rows(r#in, None)
+    }
+}
+
+/// Select request which also returns the position of the last selected tuple
+/// for keyset pagination. See [`crate::index::Index::select_page`].
+pub struct SelectPage<'a, 'p, K> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub limit: u32,
+    pub iterator_type: IteratorType,
+    pub key: &'a K,
+    pub after: Option<&'p TuplePosition>,
+}
+
+impl<'a, 'p, K: ToTupleBuffer> Request for SelectPage<'a, 'p, K> {
+    const TYPE: IProtoType = IProtoType::Select;
+    type Response = (Vec<Tuple>, Option<TuplePosition>);
+
+    fn required_feature(&self) -> Option<Feature> {
+        Some(Feature::Pagination)
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_select_page(
+            out,
+            self.space_id,
+            self.index_id,
+            self.limit,
+            self.iterator_type,
+            self.key,
+            self.after,
+        )
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_select_page(r#in)
+    }
+}
+
+pub struct Insert<'a, T> {
+    pub space_id: u32,
+    pub value: &'a T,
+}
+
+impl<'a, T: ToTupleBuffer> Request for Insert<'a, T> {
+    const TYPE: IProtoType = IProtoType::Insert;
+    type Response = Option<Tuple>;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_insert(out, self.space_id, self.value)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_single_row(r#in)
+    }
+}
+
+pub struct Replace<'a, T> {
+    pub space_id: u32,
+    pub value: &'a T,
+}
+
+impl<'a, T: ToTupleBuffer> Request for Replace<'a, T> {
+    const TYPE: IProtoType = IProtoType::Replace;
+    type Response = Option<Tuple>;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_replace(out, self.space_id, self.value)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_single_row(r#in)
+    }
+}
+
+pub struct Update<'a, 'b, K, Op: ?Sized> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub key: &'a K,
+    pub ops: &'b Op,
+}
+
+impl<'a, 'b, K, Op> Request for Update<'a, 'b, K, Op>
+where
+    K: ToTupleBuffer,
+    Op: ToTupleBuffer + ?Sized,
+{
+    const TYPE: IProtoType = IProtoType::Update;
+    type Response = Option<Tuple>;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_update(out, self.space_id, self.index_id, self.key, self.ops)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_single_row(r#in)
+    }
+}
+
+pub struct Upsert<'a, 'b, T, Op: ?Sized> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub value: &'a T,
+    pub ops: &'b Op,
+}
+
+impl<'a, 'b, T, Op> Request for Upsert<'a, 'b, T, Op>
+where
+    T: ToTupleBuffer,
+    Op: ToTupleBuffer + ?Sized,
+{
+    const TYPE: IProtoType = IProtoType::Upsert;
+    type Response = Option<Tuple>;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_upsert(out, self.space_id, self.index_id, self.value, self.ops)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_single_row(r#in)
+    }
+}
+
+pub struct Delete<'a, K> {
+    pub space_id: u32,
+    pub index_id: u32,
+    pub key: &'a K,
+}
+
+impl<'a, K: ToTupleBuffer> Request for Delete<'a, K> {
+    const TYPE: IProtoType = IProtoType::Delete;
+    type Response = Option<Tuple>;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_delete(out, self.space_id, self.index_id, self.key)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_single_row(r#in)
+    }
+}
+
+/// Wraps a `request` so that its header contains the `schema_version`
+/// expected by the client. If the actual schema version on the server differs,
+/// the request is rejected with [`TarantoolErrorCode::WrongSchemaVersion`].
+///
+/// [`TarantoolErrorCode::WrongSchemaVersion`]: crate::error::TarantoolErrorCode::WrongSchemaVersion
+pub struct WithSchemaVersion<'r, R> {
+    pub request: &'r R,
+    pub schema_version: u32,
+}
+
+impl<'r, R: Request> Request for WithSchemaVersion<'r, R> {
+    const TYPE: IProtoType = R::TYPE;
+    type Response = R::Response;
+
+    fn schema_version(&self) -> Option<u32> {
+        Some(self.schema_version)
+    }
+
+    fn stream_id(&self) -> Option<u64> {
+        self.request.stream_id()
+    }
+
+    fn required_feature(&self) -> Option<Feature> {
+        self.request.required_feature()
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        self.request.encode_body(out)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        self.request.decode_body(r#in)
+    }
+}
+
+/// Wraps a `request` so that it is executed in the stream with `stream_id`.
+///
+/// Requests of the same stream are executed by the server sequentially in
+/// the order they were sent. A stream can also run a transaction spanning
+/// multiple requests, see [`Begin`], [`Commit`] & [`Rollback`].
+pub struct WithStreamId<'r, R> {
+    pub request: &'r R,
+    pub stream_id: u64,
+}
+
+impl<'r, R: Request> Request for WithStreamId<'r, R> {
+    const TYPE: IProtoType = R::TYPE;
+    type Response = R::Response;
+
+    fn schema_version(&self) -> Option<u32> {
+        self.request.schema_version()
+    }
+
+    fn stream_id(&self) -> Option<u64> {
+        Some(self.stream_id)
+    }
+
+    fn required_feature(&self) -> Option<Feature> {
+        self.request.required_feature().or(Some(Feature::Streams))
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        self.request.encode_body(out)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        self.request.decode_body(r#in)
+    }
+}
+
+/// Begins a transaction in the stream. Must be wrapped in [`WithStreamId`].
+pub struct Begin {
+    pub isolation: IsolationLevel,
+    /// Timeout after which the transaction is rolled back by the server.
+    pub timeout: Option<Duration>,
+}
+
+impl Request for Begin {
+    const TYPE: IProtoType = IProtoType::Begin;
+    type Response = ();
+
+    fn required_feature(&self) -> Option<Feature> {
+        Some(Feature::Transactions)
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_begin(out, self.isolation, self.timeout)
+    }
+
+    fn decode_body(&self, _in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(())
+    }
+}
+
+/// Commits the transaction of the stream. Must be wrapped in [`WithStreamId`].
+pub struct Commit;
+
+impl Request for Commit {
+    const TYPE: IProtoType = IProtoType::Commit;
+    type Response = ();
+
+    fn required_feature(&self) -> Option<Feature> {
+        Some(Feature::Transactions)
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_ping(out)
+    }
+
+    fn decode_body(&self, _in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(())
+    }
+}
+
+/// Rolls back the transaction of the stream. Must be wrapped in [`WithStreamId`].
+pub struct Rollback;
+
+impl Request for Rollback {
+    const TYPE: IProtoType = IProtoType::Rollback;
+    type Response = ();
+
+    fn required_feature(&self) -> Option<Feature> {
+        Some(Feature::Transactions)
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_ping(out)
+    }
+
+    fn decode_body(&self, _in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(())
+    }
+}
+
+/// Announces the protocol version and features supported by the client.
+/// The server responds with the ones it supports.
+pub struct Id<'f> {
+    pub version: u64,
+    pub features: &'f [Feature],
+}
+
+impl<'f> Request for Id<'f> {
+    const TYPE: IProtoType = IProtoType::Id;
+    type Response = ProtocolFeatures;
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_id(out, self.version, self.features)
+    }
+
+    fn decode_body(&self, r#in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        codec::decode_id(r#in)
+    }
+}
+
+/// Subscribes to updates of a key broadcast by the server with
+/// `box.broadcast`.
+///
+/// The server doesn't respond to this request. Instead it sends the current
+/// value of the key right away and then every time it changes as
+/// [`Event`](super::Event)s which can be retrieved with
+/// [`Protocol::take_event`](super::Protocol::take_event). The next event for
+/// the key is only sent after the previous one is acknowledged by sending the
+/// request again.
+pub struct Watch<'k> {
+    pub key: &'k str,
+}
+
+impl<'k> Request for Watch<'k> {
+    const TYPE: IProtoType = IProtoType::Watch;
+    type Response = ();
+
+    fn required_feature(&self) -> Option<Feature> {
+        Some(Feature::Watchers)
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_watch(out, self.key)
+    }
+
+    fn decode_body(&self, _in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(())
+    }
+}
+
+/// Cancels the subscription made with [`Watch`]. The server doesn't respond
+/// to this request.
+pub struct Unwatch<'k> {
+    pub key: &'k str,
+}
+
+impl<'k> Request for Unwatch<'k> {
+    const TYPE: IProtoType = IProtoType::Unwatch;
+    type Response = ();
+
+    fn required_feature(&self) -> Option<Feature> {
+        Some(Feature::Watchers)
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
+        codec::encode_watch(out, self.key)
+    }
+
+    fn decode_body(&self, _in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
+        Ok(())
     }
 }
 
@@ -101,6 +560,7 @@ pub struct Auth<'u, 'p, 's> {
     pub user: &'u str,
     pub pass: &'p str,
     pub salt: &'s [u8],
+    pub method: AuthMethod,
 }
 
 impl<'u, 'p, 's> Request for Auth<'u, 'p, 's> {
@@ -108,7 +568,7 @@ impl<'u, 'p, 's> Request for Auth<'u, 'p, 's> {
     type Response = ();
 
     fn encode_body(&self, out: &mut impl Write) -> Result<(), Error> {
-        codec::encode_auth(out, self.user, self.pass, self.salt)
+        codec::encode_auth(out, self.user, self.pass, self.salt, self.method)
     }
 
     fn decode_body(&self, _in: &mut Cursor<Vec<u8>>) -> Result<Self::Response, Error> {
@@ -3,20 +3,24 @@ use std::convert::TryFrom;
 use std::io::{self, Cursor, Read, Seek, Write};
 use std::os::raw::c_char;
 use std::str::from_utf8;
+use std::time::Duration;
 
 use num_derive::FromPrimitive;
-use sha1::{Digest, Sha1};
 
 use super::Error;
-use crate::index::IteratorType;
+use crate::auth::{self, AuthMethod};
+use crate::error::ExtendedError;
+use crate::index::{IteratorType, TuplePosition};
 use crate::msgpack;
+use crate::transaction::IsolationLevel;
 use crate::tuple::{ToTupleBuffer, Tuple};
 
-use super::{ResponseError, SyncIndex};
+use super::{Event, Feature, ProtocolFeatures, Push, ResponseError, SyncIndex};
 
 const REQUEST_TYPE: u8 = 0x00;
 const SYNC: u8 = 0x01;
 const SCHEMA_VERSION: u8 = 0x05;
+const STREAM_ID: u8 = 0x0a;
 
 const SPACE_ID: u8 = 0x10;
 const INDEX_ID: u8 = 0x11;
@@ -24,6 +28,7 @@ const LIMIT: u8 = 0x12;
 const OFFSET: u8 = 0x13;
 const ITERATOR: u8 = 0x14;
 const INDEX_BASE: u8 = 0x15;
+const FETCH_POSITION: u8 = 0x1f;
 
 const KEY: u8 = 0x20;
 const TUPLE: u8 = 0x21;
@@ -31,12 +36,24 @@ const FUNCTION_NAME: u8 = 0x22;
 const USER_NAME: u8 = 0x23;
 const EXPR: u8 = 0x27;
 const OPS: u8 = 0x28;
+const AFTER_POSITION: u8 = 0x2e;
 
 const DATA: u8 = 0x30;
 const ERROR: u8 = 0x31;
+const POSITION: u8 = 0x35;
+const ERROR_STACK: u8 = 0x52;
 
 const SQL_TEXT: u8 = 0x40;
 const SQL_BIND: u8 = 0x41;
+const STMT_ID: u8 = 0x43;
+
+const VERSION: u8 = 0x54;
+const FEATURES: u8 = 0x55;
+
+const TIMEOUT: u8 = 0x56;
+const EVENT_KEY: u8 = 0x57;
+const EVENT_DATA: u8 = 0x58;
+const TXN_ISOLATION: u8 = 0x59;
 
 #[derive(Debug, Clone, Copy, serde::Deserialize, FromPrimitive)]
 #[serde(try_from = "u8")]
@@ -45,22 +62,34 @@ enum IProtoKey {
     RequestType = REQUEST_TYPE,
     Sync = SYNC,
     SchemaVersion = SCHEMA_VERSION,
+    StreamId = STREAM_ID,
     SpaceId = SPACE_ID,
     IndexId = INDEX_ID,
     Limit = LIMIT,
     Offset = OFFSET,
     Iterator = ITERATOR,
     IndexBase = INDEX_BASE,
+    FetchPosition = FETCH_POSITION,
     Key = KEY,
     Tuple = TUPLE,
     FunctionName = FUNCTION_NAME,
     UserName = USER_NAME,
     Expr = EXPR,
     Ops = OPS,
+    AfterPosition = AFTER_POSITION,
     Data = DATA,
     Error = ERROR,
+    Position = POSITION,
+    ErrorStack = ERROR_STACK,
     SqlText = SQL_TEXT,
     SqlBind = SQL_BIND,
+    StmtId = STMT_ID,
+    Version = VERSION,
+    Features = FEATURES,
+    Timeout = TIMEOUT,
+    EventKey = EVENT_KEY,
+    EventData = EVENT_DATA,
+    TxnIsolation = TXN_ISOLATION,
 }
 
 impl TryFrom<u8> for IProtoKey {
@@ -82,7 +111,16 @@ pub enum IProtoType {
     Upsert = 9,
     Call = 10,
     Execute = 11,
+    Prepare = 13,
+    Begin = 14,
+    Commit = 15,
+    Rollback = 16,
     Ping = 64,
+    Id = 73,
+    Watch = 74,
+    Unwatch = 75,
+    Event = 76,
+    Chunk = 128,
 }
 
 pub fn encode_header(
@@ -98,49 +136,54 @@ pub fn encode_header(
     Ok(())
 }
 
+/// Encodes a request header which may also contain:
+/// - `schema_version` the client expects. The server will respond with
+///   [`TarantoolErrorCode::WrongSchemaVersion`] if it doesn't match the actual
+///   one.
+/// - `stream_id` of the stream the request belongs to. Requests of the same
+///   stream are executed sequentially and can be combined into a transaction.
+///
+/// [`TarantoolErrorCode::WrongSchemaVersion`]: crate::error::TarantoolErrorCode::WrongSchemaVersion
+pub fn encode_header_ext(
+    stream: &mut impl Write,
+    sync: SyncIndex,
+    request_type: IProtoType,
+    schema_version: Option<u32>,
+    stream_id: Option<u64>,
+) -> Result<(), Error> {
+    let len = 2 + schema_version.is_some() as u32 + stream_id.is_some() as u32;
+    rmp::encode::write_map_len(stream, len)?;
+    rmp::encode::write_pfix(stream, REQUEST_TYPE)?;
+    rmp::encode::write_pfix(stream, request_type as u8)?;
+    rmp::encode::write_pfix(stream, SYNC)?;
+    rmp::encode::write_uint(stream, sync.0)?;
+    if let Some(schema_version) = schema_version {
+        rmp::encode::write_pfix(stream, SCHEMA_VERSION)?;
+        rmp::encode::write_uint(stream, schema_version as u64)?;
+    }
+    if let Some(stream_id) = stream_id {
+        rmp::encode::write_pfix(stream, STREAM_ID)?;
+        rmp::encode::write_uint(stream, stream_id)?;
+    }
+    Ok(())
+}
+
 pub fn encode_auth(
     stream: &mut impl Write,
     user: &str,
     password: &str,
     salt: &[u8],
+    method: AuthMethod,
 ) -> Result<(), Error> {
-    // prepare 'chap-sha1' scramble:
-    // salt = base64_decode(encoded_salt);
-    // step_1 = sha1(password);
-    // step_2 = sha1(step_1);
-    // step_3 = sha1(first_20_bytes_of_salt, step_2);
-    // scramble = xor(step_1, step_3);
-
-    let mut hasher = Sha1::new();
-    hasher.update(password.as_bytes());
-    let mut step_1_and_scramble = hasher.finalize();
-
-    let mut hasher = Sha1::new();
-    hasher.update(step_1_and_scramble);
-    let step_2 = hasher.finalize();
-
-    let mut hasher = Sha1::new();
-    hasher.update(&salt[0..20]);
-    hasher.update(step_2);
-    let step_3 = hasher.finalize();
-
-    step_1_and_scramble
-        .iter_mut()
-        .zip(step_3.iter())
-        .for_each(|(a, b)| *a ^= *b);
-
     rmp::encode::write_map_len(stream, 2)?;
 
     // username:
     rmp::encode::write_pfix(stream, USER_NAME)?;
     rmp::encode::write_str(stream, user)?;
 
-    // encrypted password:
+    // auth method and data:
     rmp::encode::write_pfix(stream, TUPLE)?;
-    rmp::encode::write_array_len(stream, 2)?;
-    rmp::encode::write_str(stream, "chap-sha1")?;
-    rmp::encode::write_str_len(stream, 20)?;
-    stream.write_all(&step_1_and_scramble)?;
+    auth::encode_auth_data(stream, method, password, salt)?;
     Ok(())
 }
 
@@ -149,6 +192,44 @@ pub fn encode_ping(stream: &mut impl Write) -> Result<(), Error> {
     Ok(())
 }
 
+pub fn encode_watch(stream: &mut impl Write, key: &str) -> Result<(), Error> {
+    rmp::encode::write_map_len(stream, 1)?;
+    rmp::encode::write_pfix(stream, EVENT_KEY)?;
+    rmp::encode::write_str(stream, key)?;
+    Ok(())
+}
+
+pub fn encode_id(stream: &mut impl Write, version: u64, features: &[Feature]) -> Result<(), Error> {
+    rmp::encode::write_map_len(stream, 2)?;
+    rmp::encode::write_pfix(stream, VERSION)?;
+    rmp::encode::write_uint(stream, version)?;
+    rmp::encode::write_pfix(stream, FEATURES)?;
+    rmp::encode::write_array_len(stream, features.len() as u32)?;
+    for feature in features {
+        rmp::encode::write_uint(stream, *feature as u64)?;
+    }
+    Ok(())
+}
+
+pub fn encode_begin(
+    stream: &mut impl Write,
+    isolation: IsolationLevel,
+    timeout: Option<Duration>,
+) -> Result<(), Error> {
+    let is_default_isolation = isolation == IsolationLevel::Default;
+    let len = timeout.is_some() as u32 + !is_default_isolation as u32;
+    rmp::encode::write_map_len(stream, len)?;
+    if let Some(timeout) = timeout {
+        rmp::encode::write_pfix(stream, TIMEOUT)?;
+        rmp::encode::write_f64(stream, timeout.as_secs_f64())?;
+    }
+    if !is_default_isolation {
+        rmp::encode::write_pfix(stream, TXN_ISOLATION)?;
+        rmp::encode::write_uint(stream, isolation as u64)?;
+    }
+    Ok(())
+}
+
 pub fn encode_execute(
     stream: &mut impl Write,
     sql: &str,
@@ -163,6 +244,36 @@ pub fn encode_execute(
     Ok(())
 }
 
+pub fn encode_execute_prepared(
+    stream: &mut impl Write,
+    stmt_id: u32,
+    bind_params: &impl ToTupleBuffer,
+) -> Result<(), Error> {
+    rmp::encode::write_map_len(stream, 2)?;
+    rmp::encode::write_pfix(stream, STMT_ID)?;
+    rmp::encode::write_uint(stream, stmt_id as u64)?;
+
+    rmp::encode::write_pfix(stream, SQL_BIND)?;
+    bind_params.write_tuple_data(stream)?;
+    Ok(())
+}
+
+pub fn encode_prepare(stream: &mut impl Write, sql: &str) -> Result<(), Error> {
+    rmp::encode::write_map_len(stream, 1)?;
+    rmp::encode::write_pfix(stream, SQL_TEXT)?;
+    rmp::encode::write_str(stream, sql)?;
+    Ok(())
+}
+
+/// Unprepare request is a prepare request with the statement id instead of
+/// the sql text.
+pub fn encode_unprepare(stream: &mut impl Write, stmt_id: u32) -> Result<(), Error> {
+    rmp::encode::write_map_len(stream, 1)?;
+    rmp::encode::write_pfix(stream, STMT_ID)?;
+    rmp::encode::write_uint(stream, stmt_id as u64)?;
+    Ok(())
+}
+
 pub fn encode_call<T>(stream: &mut impl Write, function_name: &str, args: &T) -> Result<(), Error>
 where
     T: ToTupleBuffer,
@@ -219,6 +330,43 @@ where
     Ok(())
 }
 
+/// Encodes a select request which also asks for the position of the last
+/// selected tuple and optionally starts after the `after` position.
+#[allow(clippy::too_many_arguments)]
+pub fn encode_select_page<K>(
+    stream: &mut impl Write,
+    space_id: u32,
+    index_id: u32,
+    limit: u32,
+    iterator_type: IteratorType,
+    key: &K,
+    after: Option<&TuplePosition>,
+) -> Result<(), Error>
+where
+    K: ToTupleBuffer,
+    K: ?Sized,
+{
+    rmp::encode::write_map_len(stream, 6 + after.is_some() as u32)?;
+    rmp::encode::write_pfix(stream, SPACE_ID)?;
+    rmp::encode::write_u32(stream, space_id)?;
+    rmp::encode::write_pfix(stream, INDEX_ID)?;
+    rmp::encode::write_u32(stream, index_id)?;
+    rmp::encode::write_pfix(stream, LIMIT)?;
+    rmp::encode::write_u32(stream, limit)?;
+    rmp::encode::write_pfix(stream, ITERATOR)?;
+    rmp::encode::write_u32(stream, iterator_type as u32)?;
+    rmp::encode::write_pfix(stream, KEY)?;
+    key.write_tuple_data(stream)?;
+    rmp::encode::write_pfix(stream, FETCH_POSITION)?;
+    rmp::encode::write_bool(stream, true)?;
+    if let Some(after) = after {
+        rmp::encode::write_pfix(stream, AFTER_POSITION)?;
+        rmp::encode::write_str_len(stream, after.as_bytes().len() as u32)?;
+        stream.write_all(after.as_bytes())?;
+    }
+    Ok(())
+}
+
 pub fn encode_insert<T>(stream: &mut impl Write, space_id: u32, value: &T) -> Result<(), Error>
 where
     T: ToTupleBuffer,
@@ -341,35 +489,141 @@ pub fn decode_header(stream: &mut (impl Read + Seek)) -> Result<Header, Error> {
         }
     }
 
-    if sync.is_none() || status_code.is_none() || schema_version.is_none() {
+    let status_code = status_code.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+    if status_code == IProtoType::Event as u32 {
+        // Event pushes are not responses to requests and may lack these fields
+        return Ok(Header {
+            sync: SyncIndex(sync.unwrap_or(0)),
+            status_code,
+            schema_version: schema_version.unwrap_or(0),
+        });
+    }
+
+    if sync.is_none() || schema_version.is_none() {
         return Err(io::Error::from(io::ErrorKind::InvalidData).into());
     }
 
     Ok(Header {
         sync: SyncIndex(sync.unwrap()),
-        status_code: status_code.unwrap(),
+        status_code,
         schema_version: schema_version.unwrap(),
     })
 }
 
-pub fn decode_error(stream: &mut impl Read) -> Result<ResponseError, Error> {
+pub fn decode_error(
+    stream: &mut (impl Read + Seek),
+    header: &Header,
+) -> Result<ResponseError, Error> {
     let mut message: Option<String> = None;
+    let mut extended = None;
 
     let map_len = rmp::decode::read_map_len(stream)?;
     for _ in 0..map_len {
-        if rmp::decode::read_pfix(stream)? == ERROR {
-            let str_len = rmp::decode::read_str_len(stream)? as usize;
-            let mut str_buf = vec![0u8; str_len];
-            stream.read_exact(&mut str_buf)?;
-            message = Some(from_utf8(&str_buf)?.to_string());
+        match rmp::decode::read_pfix(stream)? {
+            ERROR => {
+                let str_len = rmp::decode::read_str_len(stream)? as usize;
+                let mut str_buf = vec![0u8; str_len];
+                stream.read_exact(&mut str_buf)?;
+                message = Some(from_utf8(&str_buf)?.to_string());
+            }
+            ERROR_STACK => extended = ExtendedError::decode(stream)?.map(Box::new),
+            _ => msgpack::skip_value(stream)?,
         }
     }
 
+    let message = match (message, &extended) {
+        (Some(message), _) => message,
+        (None, Some(extended)) => extended.message.clone(),
+        (None, None) => return Err(io::Error::from(io::ErrorKind::InvalidData).into()),
+    };
     Ok(ResponseError {
-        message: message.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?,
+        // See `IPROTO_TYPE_ERROR` in tarantool docs
+        code: header.status_code & 0x7fff,
+        message,
+        extended,
+    })
+}
+
+pub fn decode_id(stream: &mut (impl Read + Seek)) -> Result<ProtocolFeatures, Error> {
+    let mut version = 0;
+    let mut features = Vec::new();
+
+    let map_len = rmp::decode::read_map_len(stream)?;
+    for _ in 0..map_len {
+        let key = rmp::decode::read_pfix(stream)?;
+        match key {
+            VERSION => version = rmp::decode::read_int(stream)?,
+            FEATURES => {
+                let len = rmp::decode::read_array_len(stream)?;
+                for _ in 0..len {
+                    features.push(rmp::decode::read_int(stream)?);
+                }
+            }
+            _ => msgpack::skip_value(stream)?,
+        }
+    }
+
+    Ok(ProtocolFeatures::new(version, features))
+}
+
+pub fn decode_event(stream: &mut (impl Read + Seek)) -> Result<Event, Error> {
+    let mut key: Option<String> = None;
+    // Absent data means `nil`
+    let mut data = vec![0xc0];
+
+    let map_len = rmp::decode::read_map_len(stream)?;
+    for _ in 0..map_len {
+        match rmp::decode::read_pfix(stream)? {
+            EVENT_KEY => {
+                let str_len = rmp::decode::read_str_len(stream)? as usize;
+                let mut str_buf = vec![0u8; str_len];
+                stream.read_exact(&mut str_buf)?;
+                key = Some(from_utf8(&str_buf)?.to_string());
+            }
+            EVENT_DATA => {
+                let start = stream.stream_position()?;
+                msgpack::skip_value(stream)?;
+                let end = stream.stream_position()?;
+                data = vec![0; (end - start) as usize];
+                stream.seek(io::SeekFrom::Start(start))?;
+                stream.read_exact(&mut data)?;
+            }
+            _ => msgpack::skip_value(stream)?,
+        }
+    }
+
+    Ok(Event {
+        key: key.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?,
+        data: data.into(),
     })
 }
 
+pub fn decode_push(stream: &mut (impl Read + Seek), header: &Header) -> Result<Push, Error> {
+    let map_len = rmp::decode::read_map_len(stream)?;
+    for _ in 0..map_len {
+        match rmp::decode::read_pfix(stream)? {
+            DATA => {
+                // Pushed value is wrapped into an array of one element
+                if rmp::decode::read_array_len(stream)? != 1 {
+                    return Err(io::Error::from(io::ErrorKind::InvalidData).into());
+                }
+                let start = stream.stream_position()?;
+                msgpack::skip_value(stream)?;
+                let end = stream.stream_position()?;
+                let mut data = vec![0; (end - start) as usize];
+                stream.seek(io::SeekFrom::Start(start))?;
+                stream.read_exact(&mut data)?;
+                return Ok(Push {
+                    sync: header.sync,
+                    data: data.into(),
+                });
+            }
+            _ => msgpack::skip_value(stream)?,
+        }
+    }
+    Err(io::Error::from(io::ErrorKind::InvalidData).into())
+}
+
 pub fn decode_greeting(stream: &mut impl Read) -> Result<Vec<u8>, Error> {
     let mut buf = [0; 128];
     stream.read_exact(&mut buf)?;
@@ -422,6 +676,35 @@ pub fn decode_multiple_rows(
     Ok(vec![])
 }
 
+/// Decodes the selected tuples and the position of the last one from the
+/// response to a request encoded with [`encode_select_page`].
+pub fn decode_select_page(
+    buffer: &mut Cursor<Vec<u8>>,
+) -> Result<(Vec<Tuple>, Option<TuplePosition>), Error> {
+    let mut tuples = Vec::new();
+    let mut position = None;
+    let payload_len = rmp::decode::read_map_len(buffer)?;
+    for _ in 0..payload_len {
+        match rmp::decode::read_pfix(buffer)? {
+            DATA => {
+                let items_count = rmp::decode::read_array_len(buffer)? as usize;
+                tuples.reserve(items_count);
+                for _ in 0..items_count {
+                    tuples.push(decode_tuple(buffer)?);
+                }
+            }
+            POSITION => {
+                let len = rmp::decode::read_str_len(buffer)? as usize;
+                let mut data = vec![0; len];
+                buffer.read_exact(&mut data)?;
+                position = Some(TuplePosition::from_bytes(data));
+            }
+            _ => msgpack::skip_value(buffer)?,
+        }
+    }
+    Ok((tuples, position))
+}
+
 pub fn decode_single_row(buffer: &mut Cursor<Vec<u8>>) -> Result<Option<Tuple>, Error> {
     let payload_len = rmp::decode::read_map_len(buffer)?;
     for _ in 0..payload_len {
@@ -10,13 +10,17 @@ pub mod api;
 pub mod codec;
 
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{Cursor, Read, Seek};
 use std::str::Utf8Error;
 use std::vec::Drain;
 
 use api::Request;
 
+use crate::auth::AuthMethod;
+use crate::error::{ExtendedError, TarantoolErrorCode};
+use crate::tuple::RawByteBuf;
+
 /// Error returned by [`Protocol`].
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -32,6 +36,10 @@ pub enum Error {
     Response(#[from] ResponseError),
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("feature {0:?} is not supported by the server")]
+    UnsupportedFeature(Feature),
+    #[error("authentication method {0} is not supported by the server")]
+    UnsupportedAuthMethod(AuthMethod),
     // TODO: Remove when `Encode` trait will return rmp errors
     #[error("{0}")]
     Other(#[from] Box<crate::error::Error>),
@@ -46,7 +54,7 @@ impl From<crate::error::Error> for Error {
 /// Unique identifier of the sent message on this connection.
 /// It is used to retrieve response for the corresponding request.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct SyncIndex(u64);
+pub struct SyncIndex(pub(crate) u64);
 
 impl SyncIndex {
     pub fn next_index(&mut self) -> Self {
@@ -64,13 +72,141 @@ impl SyncIndex {
 #[derive(Debug, thiserror::Error)]
 #[error("{message}")]
 pub struct ResponseError {
+    code: u32,
     message: String,
+    extended: Option<Box<ExtendedError>>,
+}
+
+impl ResponseError {
+    /// Creates an error with the given `code` and `message`, e.g. to be sent
+    /// to a client by a [`network::server::Handler`].
+    ///
+    /// [`network::server::Handler`]: crate::network::server::Handler
+    #[inline]
+    pub fn new(code: u32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            extended: None,
+        }
+    }
+
+    /// Returns the error code. Usually it is one of [`TarantoolErrorCode`].
+    ///
+    /// [`TarantoolErrorCode`]: crate::error::TarantoolErrorCode
+    #[inline(always)]
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Returns the error message.
+    #[inline(always)]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the error with all the details and the stack of errors it
+    /// was caused by. Is `None` if the server is older than 2.4.1 and
+    /// doesn't send this information.
+    #[inline(always)]
+    pub fn extended(&self) -> Option<&ExtendedError> {
+        self.extended.as_deref()
+    }
+}
+
+/// Features of the binary protocol which may be supported by the server.
+///
+/// See [`ProtocolFeatures`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Feature {
+    /// Requests can be sent in streams, see [`api::WithStreamId`].
+    Streams = 0,
+    /// Interactive transactions in streams, see [`api::Begin`].
+    Transactions = 1,
+    /// Errors can be encoded with `MP_ERROR` msgpack extension.
+    ErrorExtension = 2,
+    /// Subscriptions to remote events.
+    Watchers = 3,
+    /// Keyset pagination of select results.
+    Pagination = 4,
+}
+
+impl Feature {
+    /// All the features supported by this implementation.
+    pub const ALL: [Self; 5] = [
+        Self::Streams,
+        Self::Transactions,
+        Self::ErrorExtension,
+        Self::Watchers,
+        Self::Pagination,
+    ];
+}
+
+/// Protocol version this implementation corresponds to.
+pub const PROTOCOL_VERSION: u64 = 4;
+
+/// Protocol version and features supported by the server.
+///
+/// Negotiated with the server right after the connection is established.
+/// Servers prior to Tarantool 2.10 don't support the negotiation, in which
+/// case the version is 0 and none of the features are supported.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ProtocolFeatures {
+    version: u64,
+    /// Bit set of [`Feature`]s.
+    features: u64,
+}
+
+impl ProtocolFeatures {
+    /// Construct [`ProtocolFeatures`] from the version and the feature ids
+    /// reported by the server. Unknown features are ignored.
+    pub fn new(version: u64, features: impl IntoIterator<Item = u64>) -> Self {
+        let features = features
+            .into_iter()
+            .filter(|&id| Feature::ALL.iter().any(|&f| f as u64 == id))
+            .fold(0, |set, id| set | 1 << id);
+        Self { version, features }
+    }
+
+    /// Returns the protocol version supported by the server.
+    #[inline(always)]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns `true` if the `feature` is supported by the server.
+    #[inline(always)]
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.features & (1 << feature as u64) != 0
+    }
+}
+
+/// Update of a key the client is subscribed to with [`api::Watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// The key passed to `box.broadcast`.
+    pub key: String,
+    /// Msgpack encoded value of the key. Is `nil` if the key was never
+    /// broadcast or was broadcast without a value.
+    pub data: RawByteBuf,
+}
+
+/// Value pushed by the server with `box.session.push` while processing
+/// a request. Pushes are received before the response to the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Push {
+    /// Sync index of the request during which the value was pushed.
+    pub sync: SyncIndex,
+    /// Msgpack encoded pushed value.
+    pub data: RawByteBuf,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum State {
     /// Awaits greeting
     Init,
+    /// Awaits response to the id request
+    Id,
     /// Awaits auth
     Auth,
     /// Ready to accept new messages
@@ -93,6 +229,8 @@ pub enum SizeHint {
 pub struct Config {
     /// (user, password)
     pub creds: Option<(String, String)>,
+    /// Method used to authenticate the user if `creds` are specified.
+    pub auth_method: AuthMethod,
     // TODO: add buffer limits here
 }
 
@@ -114,6 +252,16 @@ pub struct Protocol {
     incoming: HashMap<SyncIndex, Result<Vec<u8>, ResponseError>>,
     /// (user, password)
     creds: Option<(String, String)>,
+    auth_method: AuthMethod,
+    /// Schema version received in the latest response header.
+    schema_version: Option<u32>,
+    /// Features negotiated with the server, `None` until the response to
+    /// the id request is received.
+    features: Option<ProtocolFeatures>,
+    // TODO: limit events size
+    events: VecDeque<Event>,
+    // TODO: limit pushes size
+    pushes: VecDeque<Push>,
 }
 
 impl Default for Protocol {
@@ -130,8 +278,13 @@ impl Protocol {
             sync: SyncIndex(0),
             pending_outgoing: Vec::new(),
             creds: None,
+            auth_method: AuthMethod::default(),
             outgoing: Vec::new(),
             incoming: HashMap::new(),
+            schema_version: None,
+            features: None,
+            events: VecDeque::new(),
+            pushes: VecDeque::new(),
         }
     }
 
@@ -139,9 +292,27 @@ impl Protocol {
     pub fn with_config(config: Config) -> Self {
         let mut protocol = Self::new();
         protocol.creds = config.creds;
+        protocol.auth_method = config.auth_method;
         protocol
     }
 
+    /// Resets the [`Protocol`] to the initial state so that it can be used
+    /// with a new connection. Configuration is preserved, while all buffered
+    /// requests and responses are dropped.
+    ///
+    /// Sync indexes keep growing, so that responses to the requests sent before
+    /// the reset can't be confused with responses to the new ones.
+    pub fn reset(&mut self) {
+        self.state = State::Init;
+        self.outgoing.clear();
+        self.pending_outgoing.clear();
+        self.incoming.clear();
+        self.schema_version = None;
+        self.features = None;
+        self.events.clear();
+        self.pushes.clear();
+    }
+
     /// Returns `true` if the [`Protocol`] has passed initialization and authorization
     /// stages.
     ///
@@ -152,13 +323,41 @@ impl Protocol {
         matches!(self.state, State::Ready)
     }
 
+    /// Returns the schema version of the remote server as of the latest
+    /// received response or `None` if no responses were received yet.
+    ///
+    /// The version is incremented on the server every time a DDL operation
+    /// is performed, so it can be used to detect stale schema caches.
+    pub fn schema_version(&self) -> Option<u32> {
+        self.schema_version
+    }
+
+    /// Returns protocol version and features supported by the server or
+    /// `None` if they are not negotiated yet.
+    ///
+    /// Negotiation happens right after the greeting is received, so the
+    /// features are known by the time the response to any request is received.
+    pub fn features(&self) -> Option<ProtocolFeatures> {
+        self.features
+    }
+
     /// Processes incoming request and buffers generated outgoing bytes.
     /// Outgoing bytes can be retrieved with [`Protocol::drain_outgoing_data`]
     ///
     /// Data can be sent independently of whether the protocol [`Self::is_ready`].
     /// If the protocol is not ready data will be queued and eventually processed
     /// after auth is done.
+    ///
+    /// # Errors
+    /// Returns [`Error::UnsupportedFeature`] if the request requires
+    /// a [`Feature`] the server doesn't support. The check is only performed
+    /// once the features are negotiated, see [`Self::features`].
     pub fn send_request(&mut self, request: &impl Request) -> Result<SyncIndex, Error> {
+        if let (Some(feature), Some(features)) = (request.required_feature(), self.features) {
+            if !features.supports(feature) {
+                return Err(Error::UnsupportedFeature(feature));
+            }
+        }
         let end = self.pending_outgoing.len();
         let mut buf = Cursor::new(&mut self.pending_outgoing);
         buf.set_position(end as u64);
@@ -181,6 +380,21 @@ impl Protocol {
         Some(request.decode_body(&mut Cursor::new(response)))
     }
 
+    /// Take the oldest received [`Event`] if there is one.
+    ///
+    /// Events are received for the keys subscribed to with [`api::Watch`].
+    pub fn take_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    /// Take the oldest received [`Push`] if there is one.
+    ///
+    /// Pushes of a request are received before its response. Pushes of the
+    /// requests nobody is interested in should be taken and dropped anyway.
+    pub fn take_push(&mut self) -> Option<Push> {
+        self.pushes.pop_front()
+    }
+
     /// Drop response by [`SyncIndex`] if it exists. If not - does nothing.
     pub fn drop_response(&mut self, sync: SyncIndex) {
         self.incoming.remove(&sync);
@@ -200,6 +414,9 @@ impl Protocol {
     ///
     /// Returns a [`SyncIndex`] if non-technical message was received.
     /// This message can be retreived by this index with [`Protocol::take_response`].
+    ///
+    /// Received events and pushes are buffered and can be retrieved with
+    /// [`Protocol::take_event`] and [`Protocol::take_push`] respectively.
     pub fn process_incoming<R: Read + Seek>(
         &mut self,
         chunk: &mut R,
@@ -207,40 +424,80 @@ impl Protocol {
         let sync = match self.state {
             State::Init => {
                 let salt = codec::decode_greeting(chunk)?;
+                // Write straight to outgoing, it should be empty
+                debug_assert!(self.outgoing.is_empty());
+                let mut buf = Cursor::new(&mut self.outgoing);
+                write_to_buffer(
+                    &mut buf,
+                    self.sync.next_index(),
+                    &api::Id {
+                        version: PROTOCOL_VERSION,
+                        features: &Feature::ALL,
+                    },
+                )?;
                 if let Some((user, pass)) = self.creds.as_ref() {
-                    // Auth
-                    self.state = State::Auth;
-                    // Write straight to outgoing, it should be empty
-                    debug_assert!(self.outgoing.is_empty());
-                    let mut buf = Cursor::new(&mut self.outgoing);
-                    let sync = self.sync.next_index();
+                    // Auth is sent without waiting for the id response
                     write_to_buffer(
                         &mut buf,
-                        sync,
+                        self.sync.next_index(),
                         &api::Auth {
                             user,
                             pass,
                             salt: &salt,
+                            method: self.auth_method,
                         },
                     )?;
-                } else {
-                    // No auth
-                    self.state = State::Ready;
                 }
+                self.state = State::Id;
+                None
+            }
+            State::Id => {
+                let header = codec::decode_header(chunk)?;
+                self.schema_version = Some(header.schema_version);
+                let features = if header.status_code == 0 {
+                    codec::decode_id(chunk)?
+                } else {
+                    let error = codec::decode_error(chunk, &header)?;
+                    if error.code != TarantoolErrorCode::UnknownRequestType as u32 {
+                        return Err(error.into());
+                    }
+                    // Servers prior to 2.10 don't support the id request
+                    ProtocolFeatures::default()
+                };
+                self.features = Some(features);
+                self.state = if self.creds.is_some() {
+                    State::Auth
+                } else {
+                    State::Ready
+                };
                 None
             }
             State::Auth => {
                 let header = codec::decode_header(chunk)?;
+                self.schema_version = Some(header.schema_version);
                 if header.status_code != 0 {
-                    return Err(codec::decode_error(chunk)?.into());
+                    let error = codec::decode_error(chunk, &header)?;
+                    if self.auth_method.is_rejected_by(error.code) {
+                        return Err(Error::UnsupportedAuthMethod(self.auth_method));
+                    }
+                    return Err(error.into());
                 }
                 self.state = State::Ready;
                 None
             }
             State::Ready => {
                 let header = codec::decode_header(chunk)?;
+                if header.status_code == codec::IProtoType::Event as u32 {
+                    self.events.push_back(codec::decode_event(chunk)?);
+                    return Ok(None);
+                }
+                self.schema_version = Some(header.schema_version);
+                if header.status_code == codec::IProtoType::Chunk as u32 {
+                    self.pushes.push_back(codec::decode_push(chunk, &header)?);
+                    return Ok(None);
+                }
                 let response = if header.status_code != 0 {
-                    Err(codec::decode_error(chunk)?)
+                    Err(codec::decode_error(chunk, &header)?)
                 } else {
                     let mut buf = Vec::new();
                     chunk.read_to_end(&mut buf)?;
@@ -320,21 +577,381 @@ mod tests {
         greeting
     }
 
+    fn fake_response(sync: u64, status_code: u32, schema_version: u32) -> Vec<u8> {
+        let mut response = Vec::new();
+        rmp::encode::write_map_len(&mut response, 3).unwrap();
+        rmp::encode::write_pfix(&mut response, 0).unwrap();
+        rmp::encode::write_uint(&mut response, status_code as _).unwrap();
+        rmp::encode::write_pfix(&mut response, 0x01).unwrap();
+        rmp::encode::write_uint(&mut response, sync).unwrap();
+        rmp::encode::write_pfix(&mut response, 0x05).unwrap();
+        rmp::encode::write_uint(&mut response, schema_version as _).unwrap();
+        if status_code == 0 {
+            rmp::encode::write_map_len(&mut response, 0).unwrap();
+        } else {
+            rmp::encode::write_map_len(&mut response, 1).unwrap();
+            rmp::encode::write_pfix(&mut response, 0x31).unwrap();
+            rmp::encode::write_str(&mut response, "wrong schema version").unwrap();
+        }
+        response
+    }
+
+    fn fake_id_response(version: u64, features: &[u64]) -> Vec<u8> {
+        let mut response = Vec::new();
+        rmp::encode::write_map_len(&mut response, 3).unwrap();
+        rmp::encode::write_pfix(&mut response, 0).unwrap();
+        rmp::encode::write_uint(&mut response, 0).unwrap();
+        rmp::encode::write_pfix(&mut response, 0x01).unwrap();
+        rmp::encode::write_uint(&mut response, 0).unwrap();
+        rmp::encode::write_pfix(&mut response, 0x05).unwrap();
+        rmp::encode::write_uint(&mut response, 1).unwrap();
+        rmp::encode::write_map_len(&mut response, 2).unwrap();
+        rmp::encode::write_pfix(&mut response, 0x54).unwrap();
+        rmp::encode::write_uint(&mut response, version).unwrap();
+        rmp::encode::write_pfix(&mut response, 0x55).unwrap();
+        rmp::encode::write_array_len(&mut response, features.len() as _).unwrap();
+        for &feature in features {
+            rmp::encode::write_uint(&mut response, feature).unwrap();
+        }
+        response
+    }
+
+    fn fake_event(key: &str, data: Option<u64>) -> Vec<u8> {
+        let mut event = Vec::new();
+        rmp::encode::write_map_len(&mut event, 2).unwrap();
+        rmp::encode::write_pfix(&mut event, 0).unwrap();
+        rmp::encode::write_uint(&mut event, 76).unwrap();
+        rmp::encode::write_pfix(&mut event, 0x01).unwrap();
+        rmp::encode::write_uint(&mut event, 0).unwrap();
+        rmp::encode::write_map_len(&mut event, 1 + data.is_some() as u32).unwrap();
+        rmp::encode::write_pfix(&mut event, 0x57).unwrap();
+        rmp::encode::write_str(&mut event, key).unwrap();
+        if let Some(data) = data {
+            rmp::encode::write_pfix(&mut event, 0x58).unwrap();
+            rmp::encode::write_uint(&mut event, data).unwrap();
+        }
+        event
+    }
+
+    fn fake_push(sync: u64, data: u64) -> Vec<u8> {
+        let mut push = Vec::new();
+        rmp::encode::write_map_len(&mut push, 3).unwrap();
+        rmp::encode::write_pfix(&mut push, 0).unwrap();
+        rmp::encode::write_uint(&mut push, 128).unwrap();
+        rmp::encode::write_pfix(&mut push, 0x01).unwrap();
+        rmp::encode::write_uint(&mut push, sync).unwrap();
+        rmp::encode::write_pfix(&mut push, 0x05).unwrap();
+        rmp::encode::write_uint(&mut push, 1).unwrap();
+        rmp::encode::write_map_len(&mut push, 1).unwrap();
+        rmp::encode::write_pfix(&mut push, 0x30).unwrap();
+        rmp::encode::write_array_len(&mut push, 1).unwrap();
+        rmp::encode::write_uint(&mut push, data).unwrap();
+        push
+    }
+
+    /// Processes the greeting and the response to the id request.
+    fn handshake(conn: &mut Protocol) {
+        conn.process_incoming(&mut Cursor::new(fake_greeting()))
+            .unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_id_response(4, &[0, 1, 2, 3, 4])))
+            .unwrap();
+    }
+
     #[test]
     fn connection_established() {
         let mut conn = Protocol::new();
         assert!(!conn.is_ready());
         conn.process_incoming(&mut Cursor::new(fake_greeting()))
             .unwrap();
-        assert!(conn.is_ready())
+        assert!(!conn.is_ready());
+        assert_eq!(conn.features(), None);
+        // Id request is sent right after the greeting
+        assert!(conn.ready_outgoing_len() > 0);
+        conn.process_incoming(&mut Cursor::new(fake_id_response(4, &[0, 1, 2, 3, 4])))
+            .unwrap();
+        assert!(conn.is_ready());
+        let features = conn.features().unwrap();
+        assert_eq!(features.version(), 4);
+        for feature in Feature::ALL {
+            assert!(features.supports(feature));
+        }
     }
 
     #[test]
-    fn send_bytes_generated() {
+    fn features() {
+        let features = ProtocolFeatures::new(3, vec![0, 3, 42]);
+        assert_eq!(features.version(), 3);
+        assert!(features.supports(Feature::Streams));
+        assert!(!features.supports(Feature::Transactions));
+        assert!(!features.supports(Feature::ErrorExtension));
+        assert!(features.supports(Feature::Watchers));
+        assert!(!features.supports(Feature::Pagination));
+    }
+
+    #[test]
+    fn id_unsupported() {
+        let mut conn = Protocol::new();
+        conn.process_incoming(&mut Cursor::new(fake_greeting()))
+            .unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_response(0, 0x8000 | 48, 1)))
+            .unwrap();
+        assert!(conn.is_ready());
+        assert_eq!(conn.features(), Some(ProtocolFeatures::default()));
+
+        let err = conn.send_request(&api::Commit).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedFeature(Feature::Transactions)
+        ));
+        let err = conn
+            .send_request(&api::WithStreamId {
+                request: &api::Ping,
+                stream_id: 1,
+            })
+            .unwrap_err();
+        assert!(matches!(err, Error::UnsupportedFeature(Feature::Streams)));
+        conn.send_request(&api::Ping).unwrap();
+    }
+
+    #[test]
+    fn id_error() {
+        let mut conn = Protocol::new();
+        conn.process_incoming(&mut Cursor::new(fake_greeting()))
+            .unwrap();
+        let err = conn
+            .process_incoming(&mut Cursor::new(fake_response(0, 0x8000 | 109, 1)))
+            .unwrap_err();
+        assert!(matches!(err, Error::Response(_)));
+    }
+
+    #[test]
+    fn auth_method_rejected() {
+        let config = |auth_method| Config {
+            creds: Some(("user".into(), "password".into())),
+            auth_method,
+        };
+
+        let mut conn = Protocol::with_config(config(AuthMethod::PapSha256));
+        conn.process_incoming(&mut Cursor::new(fake_greeting()))
+            .unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_id_response(4, &[])))
+            .unwrap();
+        let err = conn
+            .process_incoming(&mut Cursor::new(fake_response(1, 0x8000 | 249, 1)))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedAuthMethod(AuthMethod::PapSha256)
+        ));
+
+        // Wrong password is reported as is
+        let mut conn = Protocol::with_config(config(AuthMethod::ChapSha1));
+        conn.process_incoming(&mut Cursor::new(fake_greeting()))
+            .unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_id_response(4, &[])))
+            .unwrap();
+        let err = conn
+            .process_incoming(&mut Cursor::new(fake_response(1, 0x8000 | 47, 1)))
+            .unwrap_err();
+        assert!(matches!(err, Error::Response(_)));
+    }
+
+    #[test]
+    fn schema_version_tracked() {
         let mut conn = Protocol::new();
         conn.process_incoming(&mut Cursor::new(fake_greeting()))
             .unwrap();
+        assert_eq!(conn.schema_version(), None);
+        conn.process_incoming(&mut Cursor::new(fake_id_response(4, &[])))
+            .unwrap();
+        assert_eq!(conn.schema_version(), Some(1));
+
+        let sync = conn.send_request(&api::Ping).unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_response(sync.0, 0, 78)))
+            .unwrap();
+        assert_eq!(conn.schema_version(), Some(78));
+        conn.take_response(sync, &api::Ping).unwrap().unwrap();
+
+        let sync = conn.send_request(&api::Ping).unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_response(sync.0, 0x8000 | 109, 80)))
+            .unwrap();
+        assert_eq!(conn.schema_version(), Some(80));
+        let err = conn.take_response(sync, &api::Ping).unwrap().unwrap_err();
+        match err {
+            Error::Response(e) => {
+                assert_eq!(e.code(), 109);
+                assert_eq!(e.message(), "wrong schema version");
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn reset() {
+        let mut conn = Protocol::new();
+        handshake(&mut conn);
+        let sync = conn.send_request(&api::Ping).unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_response(sync.0, 0, 78)))
+            .unwrap();
+        conn.send_request(&api::Ping).unwrap();
+
+        conn.reset();
+        assert!(!conn.is_ready());
+        assert_eq!(conn.ready_outgoing_len(), 0);
+        assert_eq!(conn.schema_version(), None);
+        assert_eq!(conn.features(), None);
+        assert!(conn.take_response(sync, &api::Ping).is_none());
+
+        handshake(&mut conn);
+        let new_sync = conn.send_request(&api::Ping).unwrap();
+        assert!(new_sync > sync);
+    }
+
+    #[test]
+    fn send_bytes_generated() {
+        let mut conn = Protocol::new();
+        handshake(&mut conn);
+        conn.drain_outgoing_data(None);
         conn.send_request(&api::Ping).unwrap();
         assert!(conn.ready_outgoing_len() > 0);
     }
+
+    #[test]
+    fn events() {
+        let mut conn = Protocol::new();
+        handshake(&mut conn);
+        conn.send_request(&api::Watch { key: "foo" }).unwrap();
+        conn.send_request(&api::Watch { key: "bar" }).unwrap();
+        assert_eq!(conn.take_event(), None);
+
+        let sync = conn
+            .process_incoming(&mut Cursor::new(fake_event("foo", Some(42))))
+            .unwrap();
+        assert_eq!(sync, None);
+        conn.process_incoming(&mut Cursor::new(fake_event("bar", None)))
+            .unwrap();
+        // Events don't carry schema version
+        assert_eq!(conn.schema_version(), Some(1));
+
+        let event = conn.take_event().unwrap();
+        assert_eq!(event.key, "foo");
+        assert_eq!(event.data.0, [42]);
+        let event = conn.take_event().unwrap();
+        assert_eq!(event.key, "bar");
+        assert_eq!(event.data.0, [0xc0]);
+        assert_eq!(conn.take_event(), None);
+
+        conn.process_incoming(&mut Cursor::new(fake_event("foo", Some(1))))
+            .unwrap();
+        conn.reset();
+        assert_eq!(conn.take_event(), None);
+    }
+
+    #[test]
+    fn watchers_unsupported() {
+        let mut conn = Protocol::new();
+        conn.process_incoming(&mut Cursor::new(fake_greeting()))
+            .unwrap();
+        conn.process_incoming(&mut Cursor::new(fake_id_response(2, &[0, 1])))
+            .unwrap();
+        let err = conn.send_request(&api::Watch { key: "foo" }).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedFeature(Feature::Watchers)));
+    }
+
+    #[test]
+    fn pushes() {
+        let mut conn = Protocol::new();
+        handshake(&mut conn);
+        let sync = conn.send_request(&api::Ping).unwrap();
+        for data in [1, 2] {
+            let result = conn
+                .process_incoming(&mut Cursor::new(fake_push(sync.0, data)))
+                .unwrap();
+            assert_eq!(result, None);
+        }
+        assert!(conn.take_response(sync, &api::Ping).is_none());
+        let result = conn
+            .process_incoming(&mut Cursor::new(fake_response(sync.0, 0, 1)))
+            .unwrap();
+        assert_eq!(result, Some(sync));
+
+        let push = conn.take_push().unwrap();
+        assert_eq!(push.sync, sync);
+        assert_eq!(push.data.0, [1]);
+        let push = conn.take_push().unwrap();
+        assert_eq!(push.sync, sync);
+        assert_eq!(push.data.0, [2]);
+        assert_eq!(conn.take_push(), None);
+        conn.take_response(sync, &api::Ping).unwrap().unwrap();
+    }
+
+    #[test]
+    fn extended_error() {
+        use rmpv::Value;
+
+        let mut conn = Protocol::new();
+        handshake(&mut conn);
+        let sync = conn.send_request(&api::Ping).unwrap();
+
+        let mut response = Vec::new();
+        let header = Value::Map(vec![
+            (0x00.into(), (0x8000 | 32).into()),
+            (0x01.into(), sync.0.into()),
+            (0x05.into(), 1.into()),
+        ]);
+        let stack = Value::Array(vec![
+            Value::Map(vec![
+                (0x00.into(), "CustomError".into()),
+                (0x01.into(), "app.lua".into()),
+                (0x02.into(), 13.into()),
+                (0x03.into(), "top".into()),
+                (0x04.into(), 0.into()),
+                (0x05.into(), 32.into()),
+                (
+                    0x06.into(),
+                    Value::Map(vec![("custom_type".into(), "MyError".into())]),
+                ),
+            ]),
+            Value::Map(vec![
+                (0x00.into(), "ClientError".into()),
+                (0x03.into(), "cause".into()),
+                (0x04.into(), 2.into()),
+                (0x05.into(), 3.into()),
+            ]),
+        ]);
+        let body = Value::Map(vec![
+            // Unknown keys are skipped
+            (0x60.into(), Value::Array(vec![1.into(), 2.into()])),
+            (0x52.into(), Value::Map(vec![(0x00.into(), stack)])),
+            (0x31.into(), "top".into()),
+        ]);
+        rmpv::encode::write_value(&mut response, &header).unwrap();
+        rmpv::encode::write_value(&mut response, &body).unwrap();
+        conn.process_incoming(&mut Cursor::new(response)).unwrap();
+
+        let err = match conn.take_response(sync, &api::Ping).unwrap() {
+            Err(Error::Response(e)) => e,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(err.code(), 32);
+        assert_eq!(err.message(), "top");
+        let extended = err.extended().unwrap();
+        assert_eq!(extended.error_type, "CustomError");
+        assert_eq!(extended.file, "app.lua");
+        assert_eq!(extended.line, 13);
+        assert_eq!(extended.message, "top");
+        assert_eq!(extended.code, 32);
+        assert_eq!(extended.fields["custom_type"], Value::from("MyError"));
+        assert_eq!(extended.to_string(), "CustomError: top");
+
+        let cause = extended.cause().unwrap();
+        assert_eq!(cause.error_type, "ClientError");
+        assert_eq!(cause.message, "cause");
+        assert_eq!(cause.errno, 2);
+        assert_eq!(cause.code, 3);
+        assert!(cause.fields.is_empty());
+        assert!(cause.cause().is_none());
+        let source = std::error::Error::source(extended).unwrap();
+        assert_eq!(source.to_string(), "ClientError: cause");
+    }
 }
@@ -0,0 +1,925 @@
+//! Tarantool based binary protocol (IPROTO) [`Server`].
+//!
+//! Can be used only from inside Tarantool as it makes heavy use of fibers and coio.
+//!
+//! The server accepts connections on a [`CoIOListener`], sends the greeting,
+//! authenticates the users and passes the decoded [`Request`]s to
+//! a user supplied [`Handler`], the response of which is then sent back to
+//! the client. This way proxies and custom services can be written which
+//! any Tarantool connector, including [`net_box`] and
+//! [`network::client`], can talk to.
+//!
+//! # Example
+//! ```no_run
+//! use tarantool::error::TarantoolErrorCode;
+//! use tarantool::network::protocol::ResponseError;
+//! use tarantool::network::server::{Request, Response, Server, Session};
+//!
+//! let server = Server::bind("127.0.0.1:3302", |_: &Session, request: Request| {
+//!     match request {
+//!         Request::Ping => Ok(Response::empty()),
+//!         // Return the arguments back to the client
+//!         Request::Call { function_name, args } if function_name == "echo" => {
+//!             Ok(Response::from_raw(args))
+//!         }
+//!         _ => Err(ResponseError::new(
+//!             TarantoolErrorCode::UnknownRequestType as u32,
+//!             "request is not supported",
+//!         )),
+//!     }
+//! })
+//! .unwrap()
+//! .user("test_user", "password")
+//! .allow_guest(false);
+//! server.serve().unwrap();
+//! ```
+//!
+//! # Implementation
+//! Every connection is served by a separate fiber. Requests of a connection
+//! are handled one at a time in the order they are received, while
+//! the requests of different connections are handled concurrently
+//! if the [`Handler`] yields.
+//!
+//! [`net_box`]: crate::net_box
+//! [`network::client`]: super::client
+
+mod request;
+
+pub use request::Request;
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, Cursor, Read, Write};
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::rc::Rc;
+
+use crate::auth::{self, AuthMethod};
+use crate::coio::{CoIOListener, CoIOStream};
+use crate::error::TarantoolErrorCode;
+use crate::fiber;
+use crate::msgpack;
+use crate::network::protocol::codec::IProtoType;
+use crate::network::protocol::ResponseError;
+use crate::tuple::{RawByteBuf, ToTupleBuffer};
+use crate::uuid::Uuid;
+
+const REQUEST_TYPE: u64 = 0x00;
+const SYNC: u64 = 0x01;
+const SCHEMA_VERSION: u8 = 0x05;
+const USER_NAME: u64 = 0x23;
+const TUPLE: u64 = 0x21;
+const DATA: u8 = 0x30;
+const ERROR: u8 = 0x31;
+const VERSION: u8 = 0x54;
+const FEATURES: u8 = 0x55;
+
+/// Version of the binary protocol reported in response to the id request.
+const PROTOCOL_VERSION: u64 = 1;
+
+/// Name of the user the sessions belong to until authenticated.
+pub const GUEST: &str = "guest";
+
+/// Default limit of the size of a received request, see
+/// [`Server::max_frame_size`].
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Limit of the size of the requests received from the clients which haven't
+/// authenticated yet, if guests aren't allowed. Is enough for the id, auth &
+/// ping requests.
+const MAX_UNAUTHENTICATED_FRAME_SIZE: usize = 4096;
+
+/// Handles the [`Request`]s received by a [`Server`].
+///
+/// Is implemented for closures taking a [`Session`] and a [`Request`].
+pub trait Handler {
+    /// Handles the `request` received in `session` and returns the response
+    /// to be sent to the client. The error is sent to the client as
+    /// a [`ResponseError`], the connection stays open.
+    ///
+    /// This function may yield, the other requests of the same session are
+    /// not handled until it returns.
+    fn handle(&self, session: &Session, request: Request) -> Result<Response, ResponseError>;
+}
+
+impl<F> Handler for F
+where
+    F: Fn(&Session, Request) -> Result<Response, ResponseError>,
+{
+    #[inline(always)]
+    fn handle(&self, session: &Session, request: Request) -> Result<Response, ResponseError> {
+        self(session, request)
+    }
+}
+
+/// A client connection to a [`Server`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    id: u64,
+    user: String,
+}
+
+impl Session {
+    /// Returns the id of the session, unique within a server.
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the name of the authenticated user or [`GUEST`] if the client
+    /// didn't authenticate.
+    #[inline(always)]
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+}
+
+/// Response to a [`Request`] returned by a [`Handler`].
+///
+/// The response data is sent to the client as the `IPROTO_DATA` value and
+/// should have the form the client expects for the respective request:
+/// - an array of the returned values for [`Request::Call`] and
+///   [`Request::Eval`], see [`Response::data`]
+/// - an array of tuples for the other requests, see [`Response::tuples`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Response {
+    data: Option<RawByteBuf>,
+}
+
+impl Response {
+    /// Creates a response without data, e.g. to [`Request::Ping`].
+    #[inline(always)]
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Creates a response with already msgpack encoded `data`.
+    #[inline(always)]
+    pub fn from_raw(data: impl Into<RawByteBuf>) -> Self {
+        Self {
+            data: Some(data.into()),
+        }
+    }
+
+    /// Creates a response with `data` encoded as a msgpack array, e.g.
+    /// `Response::data(&(1, "foo"))` for a function returning `1, "foo"`.
+    pub fn data<T>(data: &T) -> crate::Result<Self>
+    where
+        T: ToTupleBuffer + ?Sized,
+    {
+        let mut buf = Vec::new();
+        data.write_tuple_data(&mut buf)?;
+        Ok(Self::from_raw(buf))
+    }
+
+    /// Creates a response with a msgpack array of `tuples`, e.g. the result
+    /// of a [`Request::Select`].
+    pub fn tuples<T>(tuples: &[T]) -> crate::Result<Self>
+    where
+        T: ToTupleBuffer,
+    {
+        let mut buf = Vec::new();
+        rmp::encode::write_array_len(&mut buf, tuples.len() as _)?;
+        for tuple in tuples {
+            tuple.write_tuple_data(&mut buf)?;
+        }
+        Ok(Self::from_raw(buf))
+    }
+
+    fn encode_body(&self, out: &mut impl Write) -> crate::Result<()> {
+        match &self.data {
+            Some(data) => {
+                rmp::encode::write_map_len(out, 1)?;
+                rmp::encode::write_pfix(out, DATA)?;
+                out.write_all(&data.0)?;
+            }
+            None => {
+                rmp::encode::write_map_len(out, 0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Binary protocol server, see [module level documentation](self) for
+/// details.
+pub struct Server {
+    listener: CoIOListener,
+    config: Rc<Config>,
+    state: Rc<State>,
+}
+
+struct Config {
+    handler: Box<dyn Handler>,
+    /// Passwords by user names.
+    users: HashMap<String, String>,
+    allow_guest: bool,
+    schema_version: u64,
+    max_frame_size: usize,
+    instance_uuid: Uuid,
+}
+
+/// State of a running server shared with its [`StopHandle`]s.
+#[derive(Debug)]
+struct State {
+    stopped: Cell<bool>,
+    listener_fd: RawFd,
+    /// Sockets of the served connections by session ids.
+    connections: RefCell<HashMap<u64, RawFd>>,
+}
+
+impl Server {
+    /// Creates a server accepting connections on the `listener`, requests are
+    /// passed to the `handler`.
+    pub fn new(listener: CoIOListener, handler: impl Handler + 'static) -> Self {
+        let state = State {
+            stopped: Cell::new(false),
+            listener_fd: listener.as_raw_fd(),
+            connections: RefCell::new(HashMap::new()),
+        };
+        Self {
+            listener,
+            config: Rc::new(Config {
+                handler: Box::new(handler),
+                users: HashMap::new(),
+                allow_guest: true,
+                schema_version: 0,
+                max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+                instance_uuid: Uuid::random(),
+            }),
+            state: Rc::new(state),
+        }
+    }
+
+    /// Creates a server listening on `addr`, requests are passed to
+    /// the `handler`.
+    pub fn bind(addr: impl ToSocketAddrs, handler: impl Handler + 'static) -> crate::Result<Self> {
+        let listener = CoIOListener::try_from(TcpListener::bind(addr)?)?;
+        Ok(Self::new(listener, handler))
+    }
+
+    /// Adds a user which can authenticate with `password`.
+    ///
+    /// Only [`AuthMethod::ChapSha1`] is accepted. [`AuthMethod::PapSha256`]
+    /// sends the password in plain text and is refused with
+    /// [`TarantoolErrorCode::UnknownAuthMethod`] as the connections aren't
+    /// encrypted.
+    pub fn user(mut self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        self.config_mut().users.insert(name.into(), password.into());
+        self
+    }
+
+    /// Sets whether the requests of the clients which didn't authenticate
+    /// are passed to the handler. If not, such requests are rejected with
+    /// [`TarantoolErrorCode::AccessDenied`], except for pings.
+    ///
+    /// Default: `true`.
+    pub fn allow_guest(mut self, allow_guest: bool) -> Self {
+        self.config_mut().allow_guest = allow_guest;
+        self
+    }
+
+    /// Sets the schema version sent to the clients in the response headers.
+    ///
+    /// Default: `0`.
+    pub fn schema_version(mut self, schema_version: u64) -> Self {
+        self.config_mut().schema_version = schema_version;
+        self
+    }
+
+    /// Sets the maximum size of a received request in bytes. The connection
+    /// of a client sending a larger request is closed without reading it.
+    ///
+    /// The requests of the clients which haven't authenticated are limited to
+    /// a few kilobytes regardless of this setting, unless
+    /// [guests are allowed](Self::allow_guest).
+    ///
+    /// Default: [`DEFAULT_MAX_FRAME_SIZE`].
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.config_mut().max_frame_size = max_frame_size;
+        self
+    }
+
+    #[inline]
+    fn config_mut(&mut self) -> &mut Config {
+        Rc::get_mut(&mut self.config).expect("is not shared while the server isn't running")
+    }
+
+    /// Returns the local address the server is listening on.
+    #[inline(always)]
+    pub fn local_addr(&self) -> crate::Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Returns a handle which can be used to stop the server from another
+    /// fiber.
+    #[inline(always)]
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Accepts the connections and serves them until the server is stopped
+    /// with [`StopHandle::stop`] or accepting a connection fails.
+    /// This function yields.
+    ///
+    /// Returns once all the served connections are closed. A stopped server
+    /// can't be served again.
+    pub fn serve(&self) -> crate::Result<()> {
+        let mut connections = HashMap::new();
+        // Ids of the sessions the fibers of which are finished
+        let finished = Rc::new(RefCell::new(Vec::new()));
+        let mut next_session_id = 1;
+        let result = loop {
+            if self.state.stopped.get() {
+                break Ok(());
+            }
+            let stream = match self.listener.accept() {
+                Ok(_) if self.state.stopped.get() => break Ok(()),
+                Ok(stream) => stream,
+                Err(_) if self.state.stopped.get() => break Ok(()),
+                Err(e) => break Err(e.into()),
+            };
+
+            let finished_ids: Vec<u64> = finished.borrow_mut().drain(..).collect();
+            for id in finished_ids {
+                let handle: fiber::UnitJoinHandle = connections.remove(&id).expect("is present");
+                handle.join();
+            }
+
+            let id = next_session_id;
+            next_session_id += 1;
+            self.state
+                .connections
+                .borrow_mut()
+                .insert(id, stream.as_raw_fd());
+            let config = self.config.clone();
+            let state = self.state.clone();
+            let finished = finished.clone();
+            let handle = fiber::Builder::new()
+                .name(format!("iproto_session_{}", id))
+                .proc(move || {
+                    let session = Session {
+                        id,
+                        user: GUEST.into(),
+                    };
+                    if let Err(e) = Connection::new(stream, config, session).serve() {
+                        log::warn!("session {} closed with error: {}", id, e);
+                    }
+                    state.connections.borrow_mut().remove(&id);
+                    finished.borrow_mut().push(id);
+                })
+                .start();
+            match handle {
+                Ok(handle) => {
+                    connections.insert(id, handle);
+                }
+                Err(e) => {
+                    self.state.connections.borrow_mut().remove(&id);
+                    break Err(e);
+                }
+            }
+        };
+        for (_, handle) in connections {
+            handle.join();
+        }
+        result
+    }
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server")
+            .field("local_addr", &self.listener.local_addr().ok())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Stops a running [`Server`], see [`Server::stop_handle`].
+#[derive(Debug, Clone)]
+pub struct StopHandle {
+    state: Rc<State>,
+}
+
+impl StopHandle {
+    /// Stops the server. New connections are no longer accepted and
+    /// the served ones are closed once the requests being handled are
+    /// responded to. [`Server::serve`] returns once all the connections are
+    /// closed.
+    pub fn stop(&self) {
+        if self.state.stopped.replace(true) {
+            return;
+        }
+        // Wakes the fibers waiting for new connections and requests
+        unsafe {
+            libc::shutdown(self.state.listener_fd, libc::SHUT_RD);
+        }
+        for &fd in self.state.connections.borrow().values() {
+            unsafe {
+                libc::shutdown(fd, libc::SHUT_RD);
+            }
+        }
+    }
+}
+
+/// Header of a received request.
+struct Header {
+    request_type: u64,
+    sync: u64,
+}
+
+/// Serves a single client connection.
+struct Connection {
+    stream: CoIOStream,
+    config: Rc<Config>,
+    session: Session,
+    salt: [u8; 32],
+}
+
+impl Connection {
+    fn new(stream: CoIOStream, config: Rc<Config>, session: Session) -> Self {
+        Self {
+            stream,
+            config,
+            session,
+            salt: [0; 32],
+        }
+    }
+
+    /// Greets the client and handles its requests until the connection is
+    /// closed. This function yields.
+    fn serve(&mut self) -> crate::Result<()> {
+        // Same random source tarantool uses for its own greeting, which
+        // doesn't block the tx thread
+        for chunk in self.salt.chunks_mut(16) {
+            chunk.copy_from_slice(Uuid::random().as_bytes());
+        }
+        let greeting = greeting(&self.config.instance_uuid, &self.salt);
+        self.stream.write_all(&greeting)?;
+
+        loop {
+            let len: u64 = match rmp::decode::read_int(&mut self.stream) {
+                Ok(len) => len,
+                Err(rmp::decode::NumValueReadError::InvalidMarkerRead(e))
+                    if e.kind() == io::ErrorKind::UnexpectedEof =>
+                {
+                    // The client closed the connection
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let max_frame_size = self.max_frame_size();
+            if len > max_frame_size as u64 {
+                // Don't even try to read the request, the client is either
+                // broken or malicious
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "request size {} exceeds the limit of {} bytes",
+                        len, max_frame_size
+                    ),
+                )
+                .into());
+            }
+            let mut data = vec![0; len as usize];
+            self.stream.read_exact(&mut data)?;
+
+            let mut cursor = Cursor::new(&data[..]);
+            let header = decode_header(&mut cursor)?;
+            let body = &data[cursor.position() as usize..];
+            let result = self.process(header.request_type, body);
+            self.respond(header.sync, result)?;
+        }
+    }
+
+    /// Returns the maximum size of the next request.
+    fn max_frame_size(&self) -> usize {
+        if self.session.user == GUEST && !self.config.allow_guest {
+            self.config
+                .max_frame_size
+                .min(MAX_UNAUTHENTICATED_FRAME_SIZE)
+        } else {
+            self.config.max_frame_size
+        }
+    }
+
+    /// Processes the request and returns the encoded response body.
+    fn process(&mut self, request_type: u64, body: &[u8]) -> Result<Vec<u8>, ResponseError> {
+        let mut out = Vec::new();
+        if request_type == IProtoType::Id as u64 {
+            // No optional features are supported
+            encode_id(&mut out).map_err(server_error)?;
+            return Ok(out);
+        }
+        if request_type == IProtoType::Auth as u64 {
+            self.authenticate(body)?;
+            Response::empty()
+                .encode_body(&mut out)
+                .map_err(server_error)?;
+            return Ok(out);
+        }
+        let request = Request::decode(request_type, body)?;
+        if !self.config.allow_guest && self.session.user == GUEST && request != Request::Ping {
+            return Err(ResponseError::new(
+                TarantoolErrorCode::AccessDenied as u32,
+                format!("Access is denied for user '{}'", GUEST),
+            ));
+        }
+        let response = self.config.handler.handle(&self.session, request)?;
+        response.encode_body(&mut out).map_err(server_error)?;
+        Ok(out)
+    }
+
+    /// Checks the credentials sent in the auth request and sets the user of
+    /// the session on success.
+    fn authenticate(&mut self, body: &[u8]) -> Result<(), ResponseError> {
+        let (user, method, data) = decode_auth(body).map_err(|e| {
+            ResponseError::new(
+                TarantoolErrorCode::InvalidMsgpack as u32,
+                format!("Invalid MsgPack - authentication request body: {}", e),
+            )
+        })?;
+        // The password is sent in plain text with pap-sha256, so like
+        // Tarantool the server would only accept it over an encrypted
+        // connection, which isn't supported
+        if !matches!(method.parse(), Ok(AuthMethod::ChapSha1)) {
+            return Err(ResponseError::new(
+                TarantoolErrorCode::UnknownAuthMethod as u32,
+                format!("Unknown authentication method '{}'", method),
+            ));
+        }
+        let is_valid = match self.config.users.get(&user) {
+            Some(password) => {
+                constant_time_eq(&data, &auth::chap_sha1_scramble(password, &self.salt))
+            }
+            None => user == GUEST && self.config.allow_guest,
+        };
+        if !is_valid {
+            return Err(ResponseError::new(
+                TarantoolErrorCode::PasswordMismatch as u32,
+                "User not found or supplied credentials are invalid",
+            ));
+        }
+        self.session.user = user;
+        Ok(())
+    }
+
+    fn respond(&mut self, sync: u64, result: Result<Vec<u8>, ResponseError>) -> crate::Result<()> {
+        let mut out = Cursor::new(Vec::new());
+        // Size placeholder
+        rmp::encode::write_u32(&mut out, 0)?;
+        let status = match &result {
+            Ok(_) => 0,
+            // See `IPROTO_TYPE_ERROR` in tarantool docs
+            Err(e) => 0x8000 | e.code(),
+        };
+        rmp::encode::write_map_len(&mut out, 3)?;
+        rmp::encode::write_pfix(&mut out, REQUEST_TYPE as u8)?;
+        rmp::encode::write_uint(&mut out, status as u64)?;
+        rmp::encode::write_pfix(&mut out, SYNC as u8)?;
+        rmp::encode::write_uint(&mut out, sync)?;
+        rmp::encode::write_pfix(&mut out, SCHEMA_VERSION)?;
+        rmp::encode::write_uint(&mut out, self.config.schema_version)?;
+        match result {
+            Ok(body) => out.write_all(&body)?,
+            Err(e) => {
+                rmp::encode::write_map_len(&mut out, 1)?;
+                rmp::encode::write_pfix(&mut out, ERROR)?;
+                rmp::encode::write_str(&mut out, e.message())?;
+            }
+        }
+        let len = out.get_ref().len() - 5;
+        out.set_position(0);
+        rmp::encode::write_u32(&mut out, len as u32)?;
+        self.stream.write_all(out.get_ref())?;
+        Ok(())
+    }
+}
+
+/// Compares the secrets in time which doesn't depend on their contents, so
+/// that it can't be used to guess them.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0_u8, |diff, (x, y)| diff | (x ^ y));
+    // Prevents the compiler from short circuiting the comparison
+    unsafe { std::ptr::read_volatile(&diff) == 0 }
+}
+
+/// Returns the greeting message, see [tarantool docs](https://www.tarantool.io/en/doc/latest/dev_guide/internals/iproto/authentication/#greeting-message).
+fn greeting(instance_uuid: &Uuid, salt: &[u8]) -> Vec<u8> {
+    let mut greeting = Vec::with_capacity(128);
+    for line in [
+        format!("Tarantool 2.10.0 (Binary) {}", instance_uuid),
+        base64::encode(salt),
+    ] {
+        greeting.extend(line.as_bytes());
+        greeting.resize(greeting.len() + 63 - line.len(), b' ');
+        greeting.push(b'\n');
+    }
+    greeting
+}
+
+fn decode_header(cursor: &mut Cursor<&[u8]>) -> crate::Result<Header> {
+    let mut request_type = None;
+    let mut sync = None;
+    let len = rmp::decode::read_map_len(cursor)?;
+    for _ in 0..len {
+        match rmp::decode::read_int(cursor)? {
+            REQUEST_TYPE => request_type = Some(rmp::decode::read_int(cursor)?),
+            SYNC => sync = Some(rmp::decode::read_int(cursor)?),
+            _ => msgpack::skip_value(cursor)?,
+        }
+    }
+    match (request_type, sync) {
+        (Some(request_type), Some(sync)) => Ok(Header { request_type, sync }),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "incomplete request header").into()),
+    }
+}
+
+/// Decodes the user name, the auth method and the auth data from the body
+/// of the auth request.
+fn decode_auth(body: &[u8]) -> crate::Result<(String, String, Vec<u8>)> {
+    let mut user = None;
+    let mut tuple = None;
+    let mut cursor = Cursor::new(body);
+    let len = rmp::decode::read_map_len(&mut cursor)?;
+    for _ in 0..len {
+        match rmp::decode::read_int(&mut cursor)? {
+            USER_NAME => user = Some(rmp_serde::from_read::<_, rmpv::Value>(&mut cursor)?),
+            TUPLE => tuple = Some(rmp_serde::from_read::<_, rmpv::Value>(&mut cursor)?),
+            _ => msgpack::skip_value(&mut cursor)?,
+        }
+    }
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid auth request");
+    let user = user
+        .as_ref()
+        .and_then(rmpv::Value::as_str)
+        .ok_or_else(invalid)?
+        .to_owned();
+    let tuple = tuple.as_ref().and_then(rmpv::Value::as_array);
+    let (method, data) = match tuple.map(Vec::as_slice) {
+        Some([method, data]) => (method, data),
+        // Guest doesn't have to send any auth data
+        Some([]) => return Ok((user, AuthMethod::ChapSha1.as_str().into(), Vec::new())),
+        _ => return Err(invalid().into()),
+    };
+    let method = method.as_str().ok_or_else(invalid)?.to_owned();
+    let data = match data {
+        rmpv::Value::String(data) => data.as_bytes().to_vec(),
+        rmpv::Value::Binary(data) => data.clone(),
+        _ => return Err(invalid().into()),
+    };
+    Ok((user, method, data))
+}
+
+fn encode_id(out: &mut impl Write) -> crate::Result<()> {
+    rmp::encode::write_map_len(out, 2)?;
+    rmp::encode::write_pfix(out, VERSION)?;
+    rmp::encode::write_uint(out, PROTOCOL_VERSION)?;
+    rmp::encode::write_pfix(out, FEATURES)?;
+    rmp::encode::write_array_len(out, 0)?;
+    Ok(())
+}
+
+fn server_error(e: crate::error::Error) -> ResponseError {
+    ResponseError::new(TarantoolErrorCode::ProcC as u32, e.to_string())
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::index::IteratorType;
+    use crate::net_box::{Conn, ConnOptions, Options};
+    use crate::network::client::{Client, Error as ClientError};
+    use crate::network::protocol::{self, Error as ProtocolError};
+    use crate::tuple::Tuple;
+
+    fn test_server() -> Server {
+        Server::bind(
+            "127.0.0.1:0",
+            |session: &Session, request: Request| match request {
+                Request::Ping => Ok(Response::empty()),
+                Request::Call {
+                    function_name,
+                    args,
+                } if function_name == "echo" => Ok(Response::from_raw(args)),
+                Request::Call { function_name, .. } if function_name == "whoami" => {
+                    Response::data(&(session.user(),)).map_err(server_error)
+                }
+                Request::Eval { expr, .. } => Response::data(&(expr,)).map_err(server_error),
+                Request::Select { key, limit, .. } => {
+                    let (id,): (u32,) =
+                        rmp_serde::from_slice(&key).map_err(|e| server_error(e.into()))?;
+                    let tuples: Vec<_> = (0..limit).map(|i| (id, i)).collect();
+                    Response::tuples(&tuples).map_err(server_error)
+                }
+                _ => Err(ResponseError::new(
+                    TarantoolErrorCode::UnknownRequestType as u32,
+                    "request is not supported",
+                )),
+            },
+        )
+        .unwrap()
+        .user("test_user", "password")
+    }
+
+    /// Serves the `server` in a separate fiber while `f` is running.
+    fn with_server(server: Server, f: impl FnOnce(u16)) {
+        let port = server.local_addr().unwrap().port();
+        let stop = server.stop_handle();
+        let handle = fiber::start(move || server.serve());
+        f(port);
+        stop.stop();
+        handle.join().unwrap();
+    }
+
+    async fn connect(port: u16, creds: Option<(&str, &str)>) -> Result<Client, ClientError> {
+        Client::connect_with_config(
+            "127.0.0.1",
+            port,
+            protocol::Config {
+                creds: creds.map(|(user, password)| (user.into(), password.into())),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    fn response_error(err: ClientError) -> ResponseError {
+        match err {
+            ClientError::Protocol(ProtocolError::Response(e)) => e,
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn client_requests() {
+        with_server(test_server(), |port| {
+            fiber::block_on(async {
+                let client = connect(port, Some(("test_user", "password")))
+                    .await
+                    .unwrap();
+                client.ping().await.unwrap();
+
+                let result = client.call("echo", &(1, "foo")).await.unwrap().unwrap();
+                assert_eq!(result.decode::<(u32, String)>().unwrap(), (1, "foo".into()));
+
+                let result = client.call("whoami", &()).await.unwrap().unwrap();
+                assert_eq!(result.decode::<(String,)>().unwrap(), ("test_user".into(),));
+
+                let result = client.eval("return 1", &()).await.unwrap().unwrap();
+                assert_eq!(result.decode::<(String,)>().unwrap(), ("return 1".into(),));
+
+                let tuples = client
+                    .select(512, 0, IteratorType::Eq, &(7,), Some(2), 0)
+                    .await
+                    .unwrap();
+                let tuples: Vec<(u32, u32)> = tuples.iter().map(|t| t.decode().unwrap()).collect();
+                assert_eq!(tuples, [(7, 0), (7, 1)]);
+
+                let err = client.call("unknown", &()).await.unwrap_err();
+                let err = response_error(err);
+                assert_eq!(err.code(), TarantoolErrorCode::UnknownRequestType as u32);
+                assert_eq!(err.message(), "request is not supported");
+
+                // The connection survives the errors
+                client.ping().await.unwrap();
+            });
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn authentication() {
+        with_server(test_server(), |port| {
+            fiber::block_on(async {
+                let err = connect(port, Some(("test_user", "wrong")))
+                    .await
+                    .unwrap_err();
+                assert_eq!(
+                    response_error(err).code(),
+                    TarantoolErrorCode::PasswordMismatch as u32
+                );
+
+                let err = connect(port, Some(("unknown", "password")))
+                    .await
+                    .unwrap_err();
+                assert_eq!(
+                    response_error(err).code(),
+                    TarantoolErrorCode::PasswordMismatch as u32
+                );
+
+                let client = connect(port, None).await.unwrap();
+                let result = client.call("whoami", &()).await.unwrap().unwrap();
+                assert_eq!(result.decode::<(String,)>().unwrap(), (GUEST.into(),));
+
+                // The password would be sent in plain text
+                let err = Client::connect_with_config(
+                    "127.0.0.1",
+                    port,
+                    protocol::Config {
+                        creds: Some(("test_user".into(), "password".into())),
+                        auth_method: AuthMethod::PapSha256,
+                    },
+                )
+                .await
+                .unwrap_err();
+                assert!(matches!(
+                    err,
+                    ClientError::Protocol(ProtocolError::UnsupportedAuthMethod(
+                        AuthMethod::PapSha256
+                    ))
+                ));
+            });
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn guest_denied() {
+        with_server(test_server().allow_guest(false), |port| {
+            fiber::block_on(async {
+                let client = connect(port, None).await.unwrap();
+                client.ping().await.unwrap();
+                let err = client.call("echo", &()).await.unwrap_err();
+                assert_eq!(
+                    response_error(err).code(),
+                    TarantoolErrorCode::AccessDenied as u32
+                );
+
+                let client = connect(port, Some(("test_user", "password")))
+                    .await
+                    .unwrap();
+                client.call("echo", &()).await.unwrap();
+            });
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn max_frame_size() {
+        let server = test_server().allow_guest(false).max_frame_size(1024);
+        with_server(server, |port| {
+            // Unauthenticated clients can't send large requests
+            let mut stream = CoIOStream::connect(("127.0.0.1", port)).unwrap();
+            let mut greeting = [0; 128];
+            stream.read_exact(&mut greeting).unwrap();
+            rmp::encode::write_u64(&mut stream, u64::MAX).unwrap();
+            let mut buf = [0; 1];
+            assert_eq!(stream.read(&mut buf).unwrap(), 0);
+
+            let mut stream = CoIOStream::connect(("127.0.0.1", port)).unwrap();
+            stream.read_exact(&mut greeting).unwrap();
+            rmp::encode::write_u32(&mut stream, MAX_UNAUTHENTICATED_FRAME_SIZE as u32 + 1).unwrap();
+            assert_eq!(stream.read(&mut buf).unwrap(), 0);
+
+            // Authenticated ones are limited by the configured size
+            fiber::block_on(async {
+                let client = connect(port, Some(("test_user", "password")))
+                    .await
+                    .unwrap();
+                client.call("echo", &(vec![0_u8; 512],)).await.unwrap();
+                client.call("echo", &(vec![0_u8; 2048],)).await.unwrap_err();
+            });
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn net_box_requests() {
+        with_server(test_server(), |port| {
+            let conn = Conn::new(
+                ("127.0.0.1", port),
+                ConnOptions {
+                    user: "test_user".into(),
+                    password: "password".into(),
+                    ..ConnOptions::default()
+                },
+                None,
+            )
+            .unwrap();
+            conn.ping(&Options::default()).unwrap();
+            let result: Option<Tuple> =
+                conn.call("echo", &(1, "foo"), &Options::default()).unwrap();
+            assert_eq!(
+                result.unwrap().decode::<(u32, String)>().unwrap(),
+                (1, "foo".into())
+            );
+            conn.close();
+        });
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn stop() {
+        let server = test_server();
+        let port = server.local_addr().unwrap().port();
+        let stop = server.stop_handle();
+        let handle = fiber::start(move || server.serve());
+        let client = fiber::block_on(connect(port, None)).unwrap();
+        fiber::block_on(client.ping()).unwrap();
+
+        stop.stop();
+        handle.join().unwrap();
+        assert!(fiber::block_on(client.ping()).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use std::io::Cursor;
+
+use crate::error::TarantoolErrorCode;
+use crate::index::IteratorType;
+use crate::msgpack;
+use crate::network::protocol::codec::{value_slice, IProtoType};
+use crate::network::protocol::ResponseError;
+use crate::tuple::RawByteBuf;
+
+const SPACE_ID: u64 = 0x10;
+const INDEX_ID: u64 = 0x11;
+const LIMIT: u64 = 0x12;
+const OFFSET: u64 = 0x13;
+const ITERATOR: u64 = 0x14;
+const KEY: u64 = 0x20;
+const TUPLE: u64 = 0x21;
+const FUNCTION_NAME: u64 = 0x22;
+const EXPR: u64 = 0x27;
+const OPS: u64 = 0x28;
+
+/// Request received by a [`Server`](super::Server) and passed to
+/// a [`Handler`](super::Handler).
+///
+/// Keys, tuples, arguments and update operations are left msgpack encoded
+/// as they were sent by the client and can be decoded with
+/// [`rmp_serde::from_slice`] or [`Tuple::try_from_slice`].
+///
+/// [`Tuple::try_from_slice`]: crate::tuple::Tuple::try_from_slice
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Request {
+    Ping,
+    Call {
+        function_name: String,
+        /// Msgpack array of the arguments.
+        args: RawByteBuf,
+    },
+    Eval {
+        expr: String,
+        /// Msgpack array of the arguments.
+        args: RawByteBuf,
+    },
+    Select {
+        space_id: u32,
+        index_id: u32,
+        limit: u32,
+        offset: u32,
+        iterator_type: IteratorType,
+        key: RawByteBuf,
+    },
+    Insert {
+        space_id: u32,
+        tuple: RawByteBuf,
+    },
+    Replace {
+        space_id: u32,
+        tuple: RawByteBuf,
+    },
+    Update {
+        space_id: u32,
+        index_id: u32,
+        key: RawByteBuf,
+        ops: RawByteBuf,
+    },
+    Upsert {
+        space_id: u32,
+        tuple: RawByteBuf,
+        ops: RawByteBuf,
+    },
+    Delete {
+        space_id: u32,
+        index_id: u32,
+        key: RawByteBuf,
+    },
+}
+
+/// Body of a request with all the fields the server understands.
+#[derive(Default)]
+struct Body {
+    space_id: Option<u32>,
+    index_id: Option<u32>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    iterator: Option<u32>,
+    key: Option<RawByteBuf>,
+    tuple: Option<RawByteBuf>,
+    function_name: Option<String>,
+    expr: Option<String>,
+    ops: Option<RawByteBuf>,
+}
+
+impl Request {
+    /// Decodes the body of the request of type `request_type`.
+    ///
+    /// # Errors
+    /// Returns the error to be sent to the client if the request type is not
+    /// supported or the body is malformed.
+    pub(super) fn decode(request_type: u64, body: &[u8]) -> Result<Self, ResponseError> {
+        let body = Body::decode(body).map_err(|e| {
+            ResponseError::new(
+                TarantoolErrorCode::InvalidMsgpack as u32,
+                format!("Invalid MsgPack - request body: {}", e),
+            )
+        })?;
+        let request = match request_type {
+            t if t == IProtoType::Ping as u64 => Self::Ping,
+            t if t == IProtoType::Call as u64 => Self::Call {
+                function_name: required(body.function_name, "function name")?,
+                args: body.tuple.unwrap_or_else(empty_array),
+            },
+            t if t == IProtoType::Eval as u64 => Self::Eval {
+                expr: required(body.expr, "expression")?,
+                args: body.tuple.unwrap_or_else(empty_array),
+            },
+            t if t == IProtoType::Select as u64 => {
+                let iterator = body.iterator.unwrap_or(IteratorType::Eq as u32);
+                Self::Select {
+                    space_id: required(body.space_id, "space id")?,
+                    index_id: body.index_id.unwrap_or(0),
+                    limit: body.limit.unwrap_or(u32::MAX),
+                    offset: body.offset.unwrap_or(0),
+                    iterator_type: iterator_type(iterator).ok_or_else(|| {
+                        ResponseError::new(
+                            TarantoolErrorCode::IllegalParams as u32,
+                            format!("Unknown iterator type {}", iterator),
+                        )
+                    })?,
+                    key: body.key.unwrap_or_else(empty_array),
+                }
+            }
+            t if t == IProtoType::Insert as u64 => Self::Insert {
+                space_id: required(body.space_id, "space id")?,
+                tuple: required(body.tuple, "tuple")?,
+            },
+            t if t == IProtoType::Replace as u64 => Self::Replace {
+                space_id: required(body.space_id, "space id")?,
+                tuple: required(body.tuple, "tuple")?,
+            },
+            t if t == IProtoType::Update as u64 => Self::Update {
+                space_id: required(body.space_id, "space id")?,
+                index_id: body.index_id.unwrap_or(0),
+                key: required(body.key, "key")?,
+                ops: required(body.tuple, "tuple")?,
+            },
+            t if t == IProtoType::Upsert as u64 => Self::Upsert {
+                space_id: required(body.space_id, "space id")?,
+                tuple: required(body.tuple, "tuple")?,
+                ops: required(body.ops, "operations")?,
+            },
+            t if t == IProtoType::Delete as u64 => Self::Delete {
+                space_id: required(body.space_id, "space id")?,
+                index_id: body.index_id.unwrap_or(0),
+                key: required(body.key, "key")?,
+            },
+            t => {
+                return Err(ResponseError::new(
+                    TarantoolErrorCode::UnknownRequestType as u32,
+                    format!("Unknown request type {}", t),
+                ))
+            }
+        };
+        Ok(request)
+    }
+}
+
+impl Body {
+    fn decode(data: &[u8]) -> crate::Result<Self> {
+        let mut body = Self::default();
+        if data.is_empty() {
+            // Body is optional for requests without parameters
+            return Ok(body);
+        }
+        let mut cursor = Cursor::new(data);
+        let len = rmp::decode::read_map_len(&mut cursor)?;
+        for _ in 0..len {
+            let key: u64 = rmp::decode::read_int(&mut cursor)?;
+            match key {
+                SPACE_ID => body.space_id = Some(rmp::decode::read_int(&mut cursor)?),
+                INDEX_ID => body.index_id = Some(rmp::decode::read_int(&mut cursor)?),
+                LIMIT => body.limit = Some(rmp::decode::read_int(&mut cursor)?),
+                OFFSET => body.offset = Some(rmp::decode::read_int(&mut cursor)?),
+                ITERATOR => body.iterator = Some(rmp::decode::read_int(&mut cursor)?),
+                KEY => body.key = Some(raw_value(&mut cursor)?),
+                TUPLE => body.tuple = Some(raw_value(&mut cursor)?),
+                OPS => body.ops = Some(raw_value(&mut cursor)?),
+                FUNCTION_NAME => body.function_name = Some(read_str(&mut cursor)?),
+                EXPR => body.expr = Some(read_str(&mut cursor)?),
+                _ => msgpack::skip_value(&mut cursor)?,
+            }
+        }
+        Ok(body)
+    }
+}
+
+fn raw_value(cursor: &mut Cursor<&[u8]>) -> crate::Result<RawByteBuf> {
+    Ok(RawByteBuf(value_slice(cursor)?.to_vec()))
+}
+
+fn read_str(cursor: &mut Cursor<&[u8]>) -> crate::Result<String> {
+    let len = rmp::decode::read_str_len(cursor)? as usize;
+    let start = cursor.position() as usize;
+    let data = cursor
+        .get_ref()
+        .get(start..start + len)
+        .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+    cursor.set_position((start + len) as u64);
+    Ok(String::from_utf8_lossy(data).into_owned())
+}
+
+fn required<T>(value: Option<T>, name: &str) -> Result<T, ResponseError> {
+    value.ok_or_else(|| {
+        ResponseError::new(
+            TarantoolErrorCode::MissingRequestField as u32,
+            format!("Missing mandatory field '{}' in request", name),
+        )
+    })
+}
+
+fn empty_array() -> RawByteBuf {
+    RawByteBuf(vec![0x90])
+}
+
+fn iterator_type(value: u32) -> Option<IteratorType> {
+    let iterator_type = match value {
+        0 => IteratorType::Eq,
+        1 => IteratorType::Req,
+        2 => IteratorType::All,
+        3 => IteratorType::LT,
+        4 => IteratorType::LE,
+        5 => IteratorType::GE,
+        6 => IteratorType::GT,
+        7 => IteratorType::BitsAllSet,
+        8 => IteratorType::BitsAnySet,
+        9 => IteratorType::BitsAllNotSet,
+        10 => IteratorType::Overlaps,
+        11 => IteratorType::Neighbor,
+        _ => return None,
+    };
+    Some(iterator_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::protocol::api::{Call, Delete, Ping, Select, Update};
+    use crate::network::protocol::SyncIndex;
+
+    /// Returns the request type and the body of the encoded `request`.
+    fn encode(request: &impl crate::network::protocol::api::Request) -> (u64, Vec<u8>) {
+        let mut data = vec![];
+        request.encode(&mut data, SyncIndex(1)).unwrap();
+        let mut cursor = Cursor::new(&data[..]);
+        let mut request_type = None;
+        for _ in 0..rmp::decode::read_map_len(&mut cursor).unwrap() {
+            match rmp::decode::read_int::<u64, _>(&mut cursor).unwrap() {
+                0x00 => request_type = Some(rmp::decode::read_int(&mut cursor).unwrap()),
+                _ => msgpack::skip_value(&mut cursor).unwrap(),
+            }
+        }
+        let body = data[cursor.position() as usize..].to_vec();
+        (request_type.unwrap(), body)
+    }
+
+    fn decode(request: &impl crate::network::protocol::api::Request) -> Request {
+        let (request_type, body) = encode(request);
+        Request::decode(request_type, &body).unwrap()
+    }
+
+    #[test]
+    fn decode_requests() {
+        assert_eq!(decode(&Ping), Request::Ping);
+
+        assert_eq!(
+            decode(&Call {
+                fn_name: "foo",
+                args: &(1, "bar"),
+            }),
+            Request::Call {
+                function_name: "foo".into(),
+                args: RawByteBuf(rmp_serde::to_vec(&(1, "bar")).unwrap()),
+            }
+        );
+
+        assert_eq!(
+            decode(&Select {
+                space_id: 512,
+                index_id: 1,
+                limit: 10,
+                offset: 5,
+                iterator_type: IteratorType::GE,
+                key: &(7,),
+            }),
+            Request::Select {
+                space_id: 512,
+                index_id: 1,
+                limit: 10,
+                offset: 5,
+                iterator_type: IteratorType::GE,
+                key: RawByteBuf(rmp_serde::to_vec(&(7,)).unwrap()),
+            }
+        );
+
+        let ops = [("=", 1, "baz")];
+        assert_eq!(
+            decode(&Update {
+                space_id: 512,
+                index_id: 0,
+                key: &(7,),
+                ops: &ops,
+            }),
+            Request::Update {
+                space_id: 512,
+                index_id: 0,
+                key: RawByteBuf(rmp_serde::to_vec(&(7,)).unwrap()),
+                ops: RawByteBuf(rmp_serde::to_vec(&ops).unwrap()),
+            }
+        );
+
+        assert_eq!(
+            decode(&Delete {
+                space_id: 512,
+                index_id: 0,
+                key: &(7,),
+            }),
+            Request::Delete {
+                space_id: 512,
+                index_id: 0,
+                key: RawByteBuf(rmp_serde::to_vec(&(7,)).unwrap()),
+            }
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        let err = Request::decode(IProtoType::Begin as u64, &[0x80]).unwrap_err();
+        assert_eq!(err.code(), TarantoolErrorCode::UnknownRequestType as u32);
+
+        let err = Request::decode(IProtoType::Insert as u64, &[0x80]).unwrap_err();
+        assert_eq!(err.code(), TarantoolErrorCode::MissingRequestField as u32);
+        assert_eq!(
+            err.message(),
+            "Missing mandatory field 'space id' in request"
+        );
+
+        let err = Request::decode(IProtoType::Insert as u64, &[0x81, 0x10]).unwrap_err();
+        assert_eq!(err.code(), TarantoolErrorCode::InvalidMsgpack as u32);
+    }
+}
@@ -0,0 +1,245 @@
+//! Addresses of Tarantool instances.
+//!
+//! Both [`net_box`] and [`client`] connect to an address in one of the
+//! following formats:
+//! - `host:port`, e.g. `localhost:3301` or `[::1]:3301` (ipv6 addresses must
+//!   be enclosed in brackets)
+//! - `port`, which is the same as `localhost:port`
+//! - `unix/:path`, e.g. `unix/:/var/run/tarantool.sock`
+//!
+//! [`net_box`]: crate::net_box
+//! [`client`]: super::client
+
+use std::fmt::{self, Display};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const UNIX_PREFIX: &str = "unix/:";
+
+/// Error returned when parsing a [`Uri`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("port is missing in uri {0:?}")]
+    MissingPort(String),
+    #[error("host is missing in uri {0:?}")]
+    MissingHost(String),
+    #[error("invalid port in uri {0:?}")]
+    InvalidPort(String),
+    #[error("unix socket path is missing in uri {0:?}")]
+    MissingPath(String),
+}
+
+/// Address of a Tarantool instance.
+///
+/// See module level [documentation](self) for the supported formats.
+///
+/// # Example
+/// ```
+/// use tarantool::network::uri::Uri;
+///
+/// let uri: Uri = "unix/:/var/run/tarantool.sock".parse().unwrap();
+/// assert_eq!(uri, Uri::Unix("/var/run/tarantool.sock".into()));
+///
+/// let uri: Uri = "localhost:3301".parse().unwrap();
+/// assert_eq!(uri, Uri::Tcp { host: "localhost".into(), port: 3301 });
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Uri {
+    /// TCP address, `host` is either a domain name or an ip address.
+    Tcp { host: String, port: u16 },
+    /// Path to a Unix domain socket.
+    Unix(PathBuf),
+}
+
+impl FromStr for Uri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix(UNIX_PREFIX) {
+            if path.is_empty() {
+                return Err(Error::MissingPath(s.into()));
+            }
+            return Ok(Self::Unix(path.into()));
+        }
+
+        let (host, port) = match s.rsplit_once(':') {
+            Some((host, port)) => (host, port),
+            // Only the port is specified
+            None if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => ("localhost", s),
+            None => return Err(Error::MissingPort(s.into())),
+        };
+        let host = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host);
+        if host.is_empty() {
+            return Err(Error::MissingHost(s.into()));
+        }
+        let port = port.parse().map_err(|_| Error::InvalidPort(s.into()))?;
+        Ok(Self::Tcp {
+            host: host.into(),
+            port,
+        })
+    }
+}
+
+impl Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp { host, port } if host.contains(':') => write!(f, "[{}]:{}", host, port),
+            Self::Tcp { host, port } => write!(f, "{}:{}", host, port),
+            Self::Unix(path) => write!(f, "{}{}", UNIX_PREFIX, path.display()),
+        }
+    }
+}
+
+/// Values which can be converted into a [`Uri`].
+///
+/// Strings are parsed according to the formats described in the module level
+/// [documentation](self). Pairs of a host and a port as well as socket
+/// addresses are converted into [`Uri::Tcp`].
+pub trait ToUri {
+    fn to_uri(&self) -> Result<Uri, Error>;
+}
+
+impl ToUri for Uri {
+    #[inline(always)]
+    fn to_uri(&self) -> Result<Uri, Error> {
+        Ok(self.clone())
+    }
+}
+
+impl ToUri for str {
+    #[inline(always)]
+    fn to_uri(&self) -> Result<Uri, Error> {
+        self.parse()
+    }
+}
+
+impl ToUri for String {
+    #[inline(always)]
+    fn to_uri(&self) -> Result<Uri, Error> {
+        self.parse()
+    }
+}
+
+impl ToUri for (&str, u16) {
+    #[inline(always)]
+    fn to_uri(&self) -> Result<Uri, Error> {
+        Ok(Uri::Tcp {
+            host: self.0.into(),
+            port: self.1,
+        })
+    }
+}
+
+impl ToUri for (String, u16) {
+    #[inline(always)]
+    fn to_uri(&self) -> Result<Uri, Error> {
+        (self.0.as_str(), self.1).to_uri()
+    }
+}
+
+macro_rules! impl_to_uri_for_socket_addr {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl ToUri for $t {
+                #[inline(always)]
+                fn to_uri(&self) -> Result<Uri, Error> {
+                    let addr = SocketAddr::from(*self);
+                    Ok(Uri::Tcp {
+                        host: addr.ip().to_string(),
+                        port: addr.port(),
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl_to_uri_for_socket_addr! {
+    SocketAddr, SocketAddrV4, SocketAddrV6, (IpAddr, u16), (Ipv4Addr, u16), (Ipv6Addr, u16),
+}
+
+impl<T: ToUri + ?Sized> ToUri for &T {
+    #[inline(always)]
+    fn to_uri(&self) -> Result<Uri, Error> {
+        (**self).to_uri()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tcp(host: &str, port: u16) -> Uri {
+        Uri::Tcp {
+            host: host.into(),
+            port,
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("localhost:3301".parse(), Ok(tcp("localhost", 3301)));
+        assert_eq!("127.0.0.1:3301".parse(), Ok(tcp("127.0.0.1", 3301)));
+        assert_eq!("[::1]:3301".parse(), Ok(tcp("::1", 3301)));
+        assert_eq!("3301".parse(), Ok(tcp("localhost", 3301)));
+        assert_eq!(
+            "unix/:/var/run/tarantool.sock".parse(),
+            Ok(Uri::Unix("/var/run/tarantool.sock".into()))
+        );
+        assert_eq!(
+            "unix/:./tarantool.sock".parse(),
+            Ok(Uri::Unix("./tarantool.sock".into()))
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!(
+            "localhost".parse::<Uri>(),
+            Err(Error::MissingPort("localhost".into()))
+        );
+        assert_eq!(
+            "localhost:port".parse::<Uri>(),
+            Err(Error::InvalidPort("localhost:port".into()))
+        );
+        assert_eq!(
+            "localhost:65536".parse::<Uri>(),
+            Err(Error::InvalidPort("localhost:65536".into()))
+        );
+        assert_eq!(
+            ":3301".parse::<Uri>(),
+            Err(Error::MissingHost(":3301".into()))
+        );
+        assert_eq!(
+            "unix/:".parse::<Uri>(),
+            Err(Error::MissingPath("unix/:".into()))
+        );
+    }
+
+    #[test]
+    fn display() {
+        for uri in ["localhost:3301", "[::1]:3301", "unix/:/tmp/tarantool.sock"] {
+            assert_eq!(uri.parse::<Uri>().unwrap().to_string(), uri);
+        }
+    }
+
+    #[test]
+    fn to_uri() {
+        assert_eq!(("localhost", 3301).to_uri(), Ok(tcp("localhost", 3301)));
+        assert_eq!(
+            (String::from("localhost"), 3301).to_uri(),
+            Ok(tcp("localhost", 3301))
+        );
+        let addr = SocketAddr::from(([127, 0, 0, 1], 3301));
+        assert_eq!(addr.to_uri(), Ok(tcp("127.0.0.1", 3301)));
+        assert_eq!((Ipv6Addr::LOCALHOST, 3301).to_uri(), Ok(tcp("::1", 3301)));
+        assert_eq!(
+            "unix/:/tmp/tarantool.sock".to_uri(),
+            Ok(Uri::Unix("/tmp/tarantool.sock".into()))
+        );
+    }
+}
@@ -12,7 +12,7 @@ use std::os::raw::c_char;
 
 use num_derive::ToPrimitive;
 use num_traits::ToPrimitive;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
 use crate::error::{Error, TarantoolError};
@@ -20,9 +20,14 @@ use crate::ffi::tarantool as ffi;
 use crate::index::{Index, IndexIterator, IteratorType};
 #[cfg(feature = "schema")]
 use crate::schema::space::SpaceMetadata;
+use crate::trigger::{BeforeReplace, ReplaceEvent, SpaceTrigger};
 use crate::tuple::{Encode, ToTupleBuffer, Tuple, TupleBuffer};
 use crate::tuple_from_box_api;
 
+mod typed;
+pub use tarantool_proc::Space;
+pub use typed::{KnownFieldType, SpaceSchema, TypedIterator, TypedSpace};
+
 /// End of the reserved range of system spaces.
 pub const SYSTEM_ID_MAX: u32 = 511;
 
@@ -146,11 +151,12 @@ impl Default for SpaceCreateOptions {
 #[deprecated = "Use `space::Field` instead"]
 pub type SpaceFieldFormat = Field;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Field {
     pub name: String, // TODO(gmoshkin): &str
     #[serde(alias = "type")]
     pub field_type: FieldType,
+    #[serde(default)]
     pub is_nullable: bool,
 }
 
@@ -792,6 +798,77 @@ impl Space {
         let tuple = sys_space.get(&(self.id,))?.ok_or(Error::MetaNotFound)?;
         tuple.decode::<SpaceMetadata>()
     }
+
+    /// Set a trigger called after a tuple is inserted, replaced, updated or
+    /// deleted in the space.
+    ///
+    /// `f` is called within the transaction making the change and
+    /// **must not yield**.
+    ///
+    /// Returns a handle which can be used to remove the trigger.
+    ///
+    /// ```no_run
+    /// use tarantool::space::Space;
+    /// use tarantool::trigger::Operation;
+    ///
+    /// let space = Space::find("users").unwrap();
+    /// let trigger = space
+    ///     .on_replace(|event| {
+    ///         if event.operation() == Operation::Delete {
+    ///             println!("deleted: {:?}", event.old);
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// // ...
+    /// trigger.remove().unwrap();
+    /// ```
+    ///
+    /// See also [box.space.on_replace()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/on_replace/).
+    #[inline(always)]
+    pub fn on_replace<F>(&self, f: F) -> Result<SpaceTrigger, Error>
+    where
+        F: FnMut(ReplaceEvent) + 'static,
+    {
+        crate::trigger::on_replace(self.id, f)
+    }
+
+    /// Set a trigger called before a tuple is inserted, replaced, updated or
+    /// deleted in the space.
+    ///
+    /// The value returned by `f` decides what happens to the change, see
+    /// [`BeforeReplace`]. If `f` returns an error the request fails with it,
+    /// the code of a [`Error::Tarantool`] is preserved, other errors are
+    /// reported with [`TarantoolErrorCode::ProcC`].
+    /// `f` **must not yield**.
+    ///
+    /// Returns a handle which can be used to remove the trigger.
+    ///
+    /// ```no_run
+    /// use tarantool::space::Space;
+    /// use tarantool::trigger::BeforeReplace;
+    ///
+    /// let space = Space::find("users").unwrap();
+    /// space
+    ///     .before_replace(|event| {
+    ///         // Forbid deleting tuples
+    ///         match event.new {
+    ///             Some(_) => Ok(BeforeReplace::Keep),
+    ///             None => Ok(BeforeReplace::Skip),
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// ```
+    ///
+    /// See also [box.space.before_replace()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/before_replace/).
+    ///
+    /// [`TarantoolErrorCode::ProcC`]: crate::error::TarantoolErrorCode::ProcC
+    #[inline(always)]
+    pub fn before_replace<F>(&self, f: F) -> Result<SpaceTrigger, Error>
+    where
+        F: FnMut(ReplaceEvent) -> Result<BeforeReplace, Error> + 'static,
+    {
+        crate::trigger::before_replace(self.id, f)
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,275 @@
+//! Spaces storing values of a rust type, see [`TypedSpace`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::datetime::Datetime;
+use crate::decimal::Decimal;
+use crate::error::Error;
+use crate::index::{IndexIterator, IndexOptions, IteratorType};
+use crate::tuple::{DecodeOwned, Encode, ToTupleBuffer, Tuple};
+use crate::uuid::Uuid;
+
+use super::{Field, FieldType, Space};
+
+/// Description of a space storing values of the implementing type, where
+/// each struct field corresponds to a tuple field.
+///
+/// Should be derived with [`#[derive(Space)]`](macro@super::Space), see
+/// [`TypedSpace`] for an example.
+pub trait SpaceSchema: Encode + DecodeOwned {
+    /// Name of the space.
+    const SPACE_NAME: &'static str;
+
+    /// Format of the space, one [`Field`] per struct field.
+    fn format() -> Vec<Field>;
+
+    /// Names and options of the space indexes, the first one is the primary
+    /// key.
+    fn indexes() -> Vec<(&'static str, IndexOptions)>;
+}
+
+/// Types with a known [`FieldType`] in the space format.
+///
+/// Used by [`#[derive(Space)]`](macro@super::Space) to generate the space
+/// format. Fields of other types must specify the type explicitly with
+/// `#[space(field_type = "...")]`.
+pub trait KnownFieldType {
+    const FIELD_TYPE: FieldType;
+    const IS_NULLABLE: bool = false;
+}
+
+macro_rules! impl_known_field_type {
+    ($field_type:ident: $($t:ty)+) => {
+        $(
+            impl KnownFieldType for $t {
+                const FIELD_TYPE: FieldType = FieldType::$field_type;
+            }
+        )+
+    };
+}
+
+impl_known_field_type! { Boolean: bool }
+impl_known_field_type! { Unsigned: u8 u16 u32 u64 usize }
+impl_known_field_type! { Integer: i8 i16 i32 i64 isize }
+impl_known_field_type! { Double: f32 f64 }
+impl_known_field_type! { String: String str }
+impl_known_field_type! { Decimal: Decimal }
+impl_known_field_type! { Uuid: Uuid }
+impl_known_field_type! { Datetime: Datetime }
+
+impl<T> KnownFieldType for Vec<T> {
+    const FIELD_TYPE: FieldType = FieldType::Array;
+}
+
+impl<T, const N: usize> KnownFieldType for [T; N] {
+    const FIELD_TYPE: FieldType = FieldType::Array;
+}
+
+impl<K, V, S> KnownFieldType for HashMap<K, V, S> {
+    const FIELD_TYPE: FieldType = FieldType::Map;
+}
+
+impl<K, V> KnownFieldType for BTreeMap<K, V> {
+    const FIELD_TYPE: FieldType = FieldType::Map;
+}
+
+impl<T> KnownFieldType for Option<T>
+where
+    T: KnownFieldType,
+{
+    const FIELD_TYPE: FieldType = T::FIELD_TYPE;
+    const IS_NULLABLE: bool = true;
+}
+
+/// A [`Space`](struct@Space) storing values of type `T` instead of raw [`Tuple`]s.
+///
+/// The space format & indexes are described by the [`SpaceSchema`] trait,
+/// which can be derived with [`#[derive(Space)]`](macro@super::Space):
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use tarantool::index::IteratorType;
+/// use tarantool::space::{Space, SpaceCreateOptions, TypedSpace};
+/// use tarantool::tuple::Encode;
+///
+/// #[derive(Debug, Serialize, Deserialize, Space)]
+/// #[space(name = "users")]
+/// #[space(index(name = "pk", parts(id)))]
+/// #[space(index(name = "by_name", parts(name), unique = false))]
+/// struct User {
+///     id: u64,
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// impl Encode for User {}
+///
+/// let users = TypedSpace::<User>::create(&SpaceCreateOptions {
+///     if_not_exists: true,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// users.insert(&User { id: 1, name: "Alice".into(), email: None }).unwrap();
+/// let alice: Option<User> = users.get(&(1,)).unwrap();
+/// for user in users.select(IteratorType::All, &()).unwrap() {
+///     println!("{:?}", user.unwrap());
+/// }
+/// ```
+///
+/// The derive macro supports the following attributes:
+/// - `#[space(name = "...")]` on the struct: name of the space, defaults to
+///   the struct name in snake case.
+/// - `#[space(index(name = "...", parts(field, ...), type = "...", unique = ...))]`
+///   on the struct: an index of the space, at least one is required and the
+///   first one is the primary key.
+/// - `#[space(field_type = "...", is_nullable)]` on a field: type of a field
+///   which doesn't implement [`KnownFieldType`].
+///
+/// The struct must also implement [`Encode`], which is required by
+/// [`SpaceSchema`] and isn't implemented by the macro.
+pub struct TypedSpace<T> {
+    space: Space,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedSpace<T>
+where
+    T: SpaceSchema,
+{
+    /// Wraps an existing `space`, its format isn't checked against `T`.
+    #[inline(always)]
+    pub fn from_space(space: Space) -> Self {
+        Self {
+            space,
+            marker: PhantomData,
+        }
+    }
+
+    /// Find the space named [`T::SPACE_NAME`](SpaceSchema::SPACE_NAME).
+    ///
+    /// Returns `None` if the space doesn't exist.
+    #[inline]
+    pub fn find() -> Option<Self> {
+        Space::find(T::SPACE_NAME).map(Self::from_space)
+    }
+
+    /// Memorized version of [`TypedSpace::find`], see [`Space::find_cached`].
+    #[inline]
+    pub fn find_cached() -> Option<Self> {
+        Space::find_cached(T::SPACE_NAME).map(Self::from_space)
+    }
+
+    /// Create the space and its indexes as described by [`SpaceSchema`].
+    ///
+    /// `opts.format` is ignored. If `opts.if_not_exists` is set, existing
+    /// space & indexes are left as is.
+    #[cfg(feature = "schema")]
+    pub fn create(opts: &super::SpaceCreateOptions) -> Result<Self, Error> {
+        let opts = super::SpaceCreateOptions {
+            format: Some(T::format()),
+            ..opts.clone()
+        };
+        let space = Space::create(T::SPACE_NAME, &opts)?;
+        for (name, mut index_opts) in T::indexes() {
+            index_opts.if_not_exists = Some(opts.if_not_exists);
+            crate::schema::index::create_index(space.id(), name, &index_opts)?;
+        }
+        Ok(Self::from_space(space))
+    }
+
+    /// The underlying untyped space.
+    #[inline(always)]
+    pub fn space(&self) -> &Space {
+        &self.space
+    }
+
+    /// Search for a value by `key` in the primary index, see [`Space::get`].
+    #[inline]
+    pub fn get<K>(&self, key: &K) -> Result<Option<T>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.space.get(key)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Insert a `value` into the space, see [`Space::insert`].
+    ///
+    /// Returns the inserted value, which may differ from `value` if it was
+    /// modified by a trigger.
+    #[inline]
+    pub fn insert(&self, value: &T) -> Result<T, Error> {
+        self.space.insert(value)?.decode()
+    }
+
+    /// Insert a `value` into the space, replacing the existing one with the
+    /// same primary key, see [`Space::replace`].
+    #[inline]
+    pub fn replace(&self, value: &T) -> Result<T, Error> {
+        self.space.replace(value)?.decode()
+    }
+
+    /// Search for values in the primary index, see [`Space::select`].
+    #[inline]
+    pub fn select<K>(&self, iterator_type: IteratorType, key: &K) -> Result<TypedIterator<T>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        let inner = self.space.select(iterator_type, key)?;
+        Ok(TypedIterator {
+            inner,
+            marker: PhantomData,
+        })
+    }
+
+    /// Delete a value by `key` in the primary index, see [`Space::delete`].
+    ///
+    /// Returns the deleted value if any.
+    #[inline]
+    pub fn delete<K>(&self, key: &K) -> Result<Option<T>, Error>
+    where
+        K: ToTupleBuffer,
+    {
+        self.space.delete(key)?.map(|t| t.decode()).transpose()
+    }
+}
+
+impl<T> Clone for TypedSpace<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            space: self.space.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TypedSpace<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TypedSpace")
+            .field("space", &self.space)
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// Iterator over the values returned by [`TypedSpace::select`].
+///
+/// Yields an error if a tuple can't be decoded as `T`.
+pub struct TypedIterator<T> {
+    inner: IndexIterator,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Iterator for TypedIterator<T>
+where
+    T: DecodeOwned,
+{
+    type Item = Result<T, Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|t: Tuple| t.decode())
+    }
+}
@@ -1,150 +1,381 @@
-#![cfg(any(feature = "picodata", doc))]
-
-use crate::error::TarantoolError;
-use crate::ffi;
-use crate::ffi::sql::{Bind, ObufWrapper, Port, PortSql, SqlStatement};
-use serde::{de::DeserializeOwned, Serialize};
-use std::collections::HashMap;
-use std::ffi::CStr;
-use std::io::Read;
-use std::os::raw::c_char;
-use std::str;
-
-/// Create new SQL prepared statement.
-/// query - SQL query.
-pub fn prepare(query: &str) -> crate::Result<Statement> {
-    let port = Port::zeroed();
-
-    if unsafe {
-        ffi::sql::sql_prepare(
-            query.as_ptr() as *const c_char,
-            query.len() as u32,
-            &port as *const Port,
-        )
-    } < 0
-    {
-        return Err(TarantoolError::last().into());
-    }
+//! SQL prepared statements and responses to the SQL requests.
+//!
+//! [`SqlResponse`] is returned by the remote `execute` requests, e.g.
+//! [`network::client::Client::execute`] & [`net_box::Conn::execute`].
+//!
+//! # Prepared statements
+//!
+//! Statements prepared on the remote server with
+//! [`network::client::Client::prepare`] & [`net_box::Conn::prepare`] are
+//! executed by id. The metadata of the statement's result columns and bind
+//! parameters is available via the `response` to the prepare request.
+//!
+//! Prepared statements belong to the session of the connection, so they must
+//! be prepared again after reconnecting. A statement is deallocated on the
+//! server once all the handles to it are dropped. Preparing the same sql twice
+//! returns handles to the same statement.
+//!
+//! [`network::client::Client::execute`]: crate::network::client::Client::execute
+//! [`net_box::Conn::execute`]: crate::net_box::Conn::execute
+//! [`network::client::Client::prepare`]: crate::network::client::Client::prepare
+//! [`net_box::Conn::prepare`]: crate::net_box::Conn::prepare
+
+use std::cmp::min;
+use std::io::{self, Read, Seek};
+
+use serde::de::DeserializeOwned;
 
-    let sql_port = &port as *const Port as *const PortSql;
-    let stmt = unsafe { (*sql_port).sql_stmt };
-    let stmt_id =
-        unsafe { ffi::sql::sql_stmt_calculate_id(query.as_ptr() as *const c_char, query.len()) };
+use crate::tuple::Tuple;
+
+#[cfg(any(feature = "picodata", doc))]
+mod statement;
+#[cfg(any(feature = "picodata", doc))]
+pub use statement::{prepare, Statement};
+
+/// Metadata of a column of the SQL query result or of a bind parameter of
+/// the SQL statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMetadata {
+    /// Column name or a bind parameter name, e.g. `"?"` or `":id"`.
+    pub name: String,
+    /// Column type, e.g. `"integer"`, `"string"` or `"any"`.
+    pub field_type: String,
+    /// Collation of the column. Only sent if it's set for the column.
+    pub collation: Option<String>,
+    /// Only sent if `sql_full_metadata` session setting is enabled.
+    pub is_nullable: Option<bool>,
+    /// Only sent if `sql_full_metadata` session setting is enabled.
+    pub is_autoincrement: Option<bool>,
+    /// Original expression of the column. Only sent if `sql_full_metadata`
+    /// session setting is enabled.
+    pub span: Option<String>,
+}
 
-    Ok(Statement {
-        inner: stmt,
-        id: stmt_id,
-    })
+/// Information about the changes made by a data-changing SQL statement
+/// (`INSERT`, `UPDATE`, `DELETE`, etc.)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqlInfo {
+    /// Number of rows affected by the statement.
+    pub row_count: u64,
+    /// Values generated for the autoincrement fields of the inserted rows.
+    pub autoincrement_ids: Vec<i64>,
 }
 
-/// SQL prepared statement.
-pub struct Statement {
-    inner: *const SqlStatement,
-    id: u32,
+/// Response to an SQL `execute` or `prepare` request.
+///
+/// Contains the resulting rows and the column metadata for the `SELECT`-like
+/// statements or the [`SqlInfo`] for the data-changing statements.
+///
+/// Rows can be accessed as tuples via [`SqlResponse::rows`] or deserialized
+/// by column names via [`SqlResponse::decode_rows`].
+#[derive(Debug, Default)]
+pub struct SqlResponse {
+    metadata: Vec<ColumnMetadata>,
+    bind_metadata: Vec<ColumnMetadata>,
+    bind_count: Option<u32>,
+    stmt_id: Option<u32>,
+    info: Option<SqlInfo>,
+    rows: Vec<Tuple>,
 }
 
-impl Statement {
-    /// Returns original query.
-    pub fn source(&self) -> Result<&str, std::str::Utf8Error> {
-        unsafe {
-            let query = ffi::sql::sql_stmt_query_str(self.inner);
-            CStr::from_ptr(query)
+impl SqlResponse {
+    /// Decodes the body of a response to an `execute` or `prepare` request.
+    ///
+    /// If `limit` is set, at most `limit` rows are kept.
+    pub fn decode(stream: &mut (impl Read + Seek), limit: Option<usize>) -> crate::Result<Self> {
+        let mut response = Self::default();
+        let map_len = rmp::decode::read_map_len(stream)?;
+        for _ in 0..map_len {
+            match rmp::decode::read_int(stream)? {
+                IPROTO_DATA => {
+                    let total = rmp::decode::read_array_len(stream)? as usize;
+                    let rows_count = limit.map_or(total, |limit| min(limit, total));
+                    let mut rows = Vec::with_capacity(rows_count);
+                    for _ in 0..rows_count {
+                        let data = read_value(stream)?;
+                        // Safety: `data` is validated by `read_value`
+                        rows.push(unsafe { Tuple::from_slice(&data) });
+                    }
+                    // Skip the rows exceeding the limit
+                    for _ in rows_count..total {
+                        crate::msgpack::skip_value(stream)?;
+                    }
+                    response.rows = rows;
+                }
+                IPROTO_METADATA => response.metadata = decode_metadata(stream)?,
+                IPROTO_BIND_METADATA => response.bind_metadata = decode_metadata(stream)?,
+                IPROTO_BIND_COUNT => response.bind_count = Some(rmp::decode::read_int(stream)?),
+                IPROTO_SQL_INFO => response.info = Some(decode_sql_info(stream)?),
+                IPROTO_STMT_ID => response.stmt_id = Some(rmp::decode::read_int(stream)?),
+                _ => crate::msgpack::skip_value(stream)?,
+            }
         }
-        .to_str()
+        Ok(response)
     }
 
-    /// Returns internal Tarantool id of the prepared statement.
-    pub fn id(&self) -> u32 {
-        self.id
+    /// Metadata of the result columns. Empty for the statements which don't
+    /// return any data.
+    #[inline(always)]
+    pub fn metadata(&self) -> &[ColumnMetadata] {
+        &self.metadata
     }
 
-    /// Executes prepared statement and returns a wrapper over the raw msgpack bytes.
-    pub fn execute_raw<IN>(&self, bind_params: &IN) -> crate::Result<impl Read>
-    where
-        IN: Serialize,
-    {
-        let mut port = Port::zeroed();
-
-        let execute_result = if std::mem::size_of::<IN>() != 0 {
-            let params = rmp_serde::to_vec_named(bind_params)?;
-            let mut bind_ptr: *const Bind = unsafe { std::mem::zeroed() };
-            let bind_cnt = unsafe {
-                ffi::sql::sql_bind_list_decode(
-                    params.as_ptr() as *const c_char,
-                    &mut bind_ptr as *mut *const Bind,
-                )
-            };
-            if bind_cnt < 0 {
-                return Err(TarantoolError::last().into());
-            }
+    /// Metadata of the bind parameters of the statement. Only sent in
+    /// response to a `prepare` request.
+    #[inline(always)]
+    pub fn bind_metadata(&self) -> &[ColumnMetadata] {
+        &self.bind_metadata
+    }
 
-            unsafe {
-                ffi::sql::sql_execute_prepared_ext(
-                    self.id,
-                    bind_ptr as *const Bind,
-                    bind_cnt as u32,
-                    &port as *const Port,
-                )
-            }
-        } else {
-            unsafe {
-                ffi::sql::sql_execute_prepared_ext(
-                    self.id,
-                    std::ptr::null::<Bind>() as *const Bind,
-                    0,
-                    &port as *const Port,
-                )
-            }
-        };
-        if execute_result < 0 {
-            // Tarantool has already called `port_destroy()` and has possibly
-            // trashed `vtab` pointer. We need to reset it to avoid UB.
-            port.vtab = std::ptr::null();
-            return Err(TarantoolError::last().into());
-        }
+    /// Number of the bind parameters of the statement. Only sent in
+    /// response to a `prepare` request.
+    #[inline(always)]
+    pub fn bind_count(&self) -> Option<u32> {
+        self.bind_count
+    }
+
+    /// Id of the prepared statement. Only sent in response to a `prepare`
+    /// request.
+    #[inline(always)]
+    pub fn stmt_id(&self) -> Option<u32> {
+        self.stmt_id
+    }
 
-        let buf = ObufWrapper::new(1024);
+    /// Information about the changes made by a data-changing statement.
+    /// `None` for the statements which return data.
+    #[inline(always)]
+    pub fn info(&self) -> Option<&SqlInfo> {
+        self.info.as_ref()
+    }
+
+    /// Number of rows affected by a data-changing statement.
+    #[inline]
+    pub fn row_count(&self) -> Option<u64> {
+        self.info.as_ref().map(|info| info.row_count)
+    }
 
-        unsafe {
-            ((*port.vtab).dump_msgpack)(&port as *const Port, buf.obuf());
-        };
-        Ok(buf)
+    /// Values generated for the autoincrement fields of the rows inserted by
+    /// the statement.
+    #[inline]
+    pub fn autoincrement_ids(&self) -> &[i64] {
+        self.info
+            .as_ref()
+            .map(|info| info.autoincrement_ids.as_slice())
+            .unwrap_or_default()
     }
 
-    /// Executes a *returning data* prepared statement with binding variables.
+    /// Rows returned by the statement.
+    #[inline(always)]
+    pub fn rows(&self) -> &[Tuple] {
+        &self.rows
+    }
+
+    #[inline(always)]
+    pub fn into_rows(self) -> Vec<Tuple> {
+        self.rows
+    }
+
+    /// Deserializes the returned rows into `T` matching the values with the
+    /// struct fields (or map keys) by the column names from
+    /// [`SqlResponse::metadata`] rather than by the position.
     ///
     /// Example:
     /// ```no_run
-    /// #[cfg(feature = "picodata")]
-    /// {
-    ///     use tarantool::sql;
-    ///
-    ///     let stmt = sql::prepare("SELECT * FROM S WHERE ID > ?").unwrap();
-    ///     let result: Vec<(u8, String)> = stmt.execute(&(100,)).unwrap();
-    ///     println!("SQL query result: {:?}", result);
+    /// # use tarantool::sql::SqlResponse;
+    /// #[derive(serde::Deserialize)]
+    /// struct Row {
+    ///     #[serde(rename = "ID")]
+    ///     id: u64,
+    ///     #[serde(rename = "NAME")]
+    ///     name: String,
     /// }
+    /// # let response = SqlResponse::default();
+    /// // SELECT "NAME", "ID" FROM "T"
+    /// let rows: Vec<Row> = response.decode_rows().unwrap();
     /// ```
-    pub fn execute<IN, OUT>(&self, bind_params: &IN) -> crate::Result<OUT>
+    pub fn decode_rows<T>(&self) -> crate::Result<Vec<T>>
     where
-        IN: Serialize,
-        OUT: DeserializeOwned,
+        T: DeserializeOwned,
     {
-        let buf = self.execute_raw(bind_params)?;
-        let mut map = rmp_serde::decode::from_read::<_, HashMap<u32, rmpv::Value>>(buf)?;
-        let data = map.remove(&ffi::sql::IPROTO_DATA).ok_or_else(|| {
-            rmp_serde::decode::Error::Syntax("Invalid execution result format".to_string())
-        })?;
-        let values = rmpv::ext::from_value::<OUT>(data)
-            .map_err(|e| rmp_serde::decode::Error::Syntax(e.to_string()))?;
-
-        Ok(values)
+        let mut buf = Vec::new();
+        let mut result = Vec::with_capacity(self.rows.len());
+        for row in &self.rows {
+            buf.clear();
+            let data = row.as_buffer();
+            let mut cur = io::Cursor::new(&data);
+            let len = rmp::decode::read_array_len(&mut cur)? as usize;
+            if len != self.metadata.len() {
+                return Err(rmp_serde::decode::Error::Syntax(format!(
+                    "row has {} fields, but {} columns are described in the metadata",
+                    len,
+                    self.metadata.len()
+                ))
+                .into());
+            }
+            rmp::encode::write_map_len(&mut buf, len as u32)?;
+            for column in &self.metadata {
+                rmp::encode::write_str(&mut buf, &column.name)?;
+                let start = cur.position() as usize;
+                crate::msgpack::skip_value(&mut cur)?;
+                buf.extend_from_slice(&data[start..cur.position() as usize]);
+            }
+            result.push(rmp_serde::from_slice(&buf)?);
+        }
+        Ok(result)
+    }
+}
+
+/// Reads the next msgpack value from `stream` into a buffer.
+fn read_value(stream: &mut (impl Read + Seek)) -> crate::Result<Vec<u8>> {
+    let start = stream.stream_position()?;
+    crate::msgpack::skip_value(stream)?;
+    let end = stream.stream_position()?;
+    let mut data = vec![0; (end - start) as usize];
+    stream.seek(io::SeekFrom::Start(start))?;
+    stream.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn read_str(stream: &mut impl Read) -> crate::Result<String> {
+    let len = rmp::decode::read_str_len(stream)? as usize;
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|e| e.utf8_error().into())
+}
+
+fn decode_metadata(stream: &mut (impl Read + Seek)) -> crate::Result<Vec<ColumnMetadata>> {
+    let len = rmp::decode::read_array_len(stream)?;
+    let mut result = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let mut column = ColumnMetadata::default();
+        let map_len = rmp::decode::read_map_len(stream)?;
+        for _ in 0..map_len {
+            match rmp::decode::read_int(stream)? {
+                IPROTO_FIELD_NAME => column.name = read_str(stream)?,
+                IPROTO_FIELD_TYPE => column.field_type = read_str(stream)?,
+                IPROTO_FIELD_COLL => column.collation = Some(read_str(stream)?),
+                IPROTO_FIELD_IS_NULLABLE => {
+                    column.is_nullable = Some(rmp::decode::read_bool(stream)?)
+                }
+                IPROTO_FIELD_IS_AUTOINCREMENT => {
+                    column.is_autoincrement = Some(rmp::decode::read_bool(stream)?)
+                }
+                IPROTO_FIELD_SPAN => column.span = rmp_serde::from_read(&mut *stream)?,
+                _ => crate::msgpack::skip_value(stream)?,
+            }
+        }
+        result.push(column);
     }
+    Ok(result)
 }
 
-impl Drop for Statement {
-    fn drop(&mut self) {
-        unsafe {
-            ffi::sql::sql_unprepare(self.id);
+fn decode_sql_info(stream: &mut (impl Read + Seek)) -> crate::Result<SqlInfo> {
+    let mut info = SqlInfo::default();
+    let map_len = rmp::decode::read_map_len(stream)?;
+    for _ in 0..map_len {
+        match rmp::decode::read_int(stream)? {
+            SQL_INFO_ROW_COUNT => info.row_count = rmp::decode::read_int(stream)?,
+            SQL_INFO_AUTOINCREMENT_IDS => {
+                let len = rmp::decode::read_array_len(stream)?;
+                for _ in 0..len {
+                    info.autoincrement_ids.push(rmp::decode::read_int(stream)?);
+                }
+            }
+            _ => crate::msgpack::skip_value(stream)?,
         }
     }
+    Ok(info)
+}
+
+// Keys of the `execute` & `prepare` response body
+const IPROTO_DATA: u8 = 0x30;
+const IPROTO_METADATA: u8 = 0x32;
+const IPROTO_BIND_METADATA: u8 = 0x33;
+const IPROTO_BIND_COUNT: u8 = 0x34;
+const IPROTO_SQL_INFO: u8 = 0x42;
+const IPROTO_STMT_ID: u8 = 0x43;
+
+// Keys of the column metadata maps
+const IPROTO_FIELD_NAME: u8 = 0x00;
+const IPROTO_FIELD_TYPE: u8 = 0x01;
+const IPROTO_FIELD_COLL: u8 = 0x02;
+const IPROTO_FIELD_IS_NULLABLE: u8 = 0x03;
+const IPROTO_FIELD_IS_AUTOINCREMENT: u8 = 0x04;
+const IPROTO_FIELD_SPAN: u8 = 0x05;
+
+// Keys of the `IPROTO_SQL_INFO` map
+const SQL_INFO_ROW_COUNT: u8 = 0x00;
+const SQL_INFO_AUTOINCREMENT_IDS: u8 = 0x01;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_metadata_and_info() {
+        use rmpv::Value;
+
+        let body = Value::Map(vec![
+            (
+                IPROTO_METADATA.into(),
+                Value::Array(vec![
+                    Value::Map(vec![
+                        (IPROTO_FIELD_NAME.into(), "ID".into()),
+                        (IPROTO_FIELD_TYPE.into(), "integer".into()),
+                        (IPROTO_FIELD_IS_NULLABLE.into(), false.into()),
+                        (IPROTO_FIELD_IS_AUTOINCREMENT.into(), true.into()),
+                        (IPROTO_FIELD_SPAN.into(), Value::Nil),
+                    ]),
+                    Value::Map(vec![
+                        (IPROTO_FIELD_NAME.into(), "NAME".into()),
+                        (IPROTO_FIELD_TYPE.into(), "string".into()),
+                        (IPROTO_FIELD_COLL.into(), "unicode_ci".into()),
+                        // Unknown keys are skipped
+                        (0x60.into(), "foo".into()),
+                    ]),
+                ]),
+            ),
+            (
+                IPROTO_SQL_INFO.into(),
+                Value::Map(vec![
+                    (SQL_INFO_ROW_COUNT.into(), 2.into()),
+                    (
+                        SQL_INFO_AUTOINCREMENT_IDS.into(),
+                        Value::Array(vec![1.into(), 2.into()]),
+                    ),
+                ]),
+            ),
+            (IPROTO_BIND_COUNT.into(), 0.into()),
+            (IPROTO_STMT_ID.into(), 42.into()),
+        ]);
+        let mut data = vec![];
+        rmpv::encode::write_value(&mut data, &body).unwrap();
+
+        let response = SqlResponse::decode(&mut io::Cursor::new(data), None).unwrap();
+        assert_eq!(
+            response.metadata(),
+            [
+                ColumnMetadata {
+                    name: "ID".into(),
+                    field_type: "integer".into(),
+                    collation: None,
+                    is_nullable: Some(false),
+                    is_autoincrement: Some(true),
+                    span: None,
+                },
+                ColumnMetadata {
+                    name: "NAME".into(),
+                    field_type: "string".into(),
+                    collation: Some("unicode_ci".into()),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert!(response.bind_metadata().is_empty());
+        assert_eq!(response.bind_count(), Some(0));
+        assert_eq!(response.stmt_id(), Some(42));
+        assert_eq!(response.row_count(), Some(2));
+        assert_eq!(response.autoincrement_ids(), [1, 2]);
+        assert!(response.rows().is_empty());
+    }
 }
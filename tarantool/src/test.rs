@@ -107,6 +107,11 @@ pub fn collect_tester() -> Vec<TestDescAndFn> {
 #[cfg(feature = "internal_test")]
 pub mod util {
     use std::convert::Infallible;
+    use std::io;
+    use std::net::{Shutdown, TcpStream};
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+    use std::thread;
 
     /// The default port where tarantool listens in tests
     pub const TARANTOOL_LISTEN: u16 = 3301;
@@ -122,4 +127,41 @@ pub mod util {
     pub fn ok<T>(v: T) -> std::result::Result<T, Infallible> {
         Ok(v)
     }
+
+    /// Binds a Unix domain socket at `path` and forwards every connection
+    /// accepted on it to `localhost:port` from separate threads.
+    ///
+    /// Is used to test connecting over Unix domain sockets, as the test
+    /// instance only listens on a TCP port.
+    pub fn unix_socket_proxy(path: impl AsRef<Path>, port: u16) -> io::Result<()> {
+        let path = path.as_ref();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        thread::spawn(move || {
+            for client in listener.incoming() {
+                let forward = || -> io::Result<()> {
+                    let mut client = client?;
+                    let mut server = TcpStream::connect(("localhost", port))?;
+                    let mut client_writer = client.try_clone()?;
+                    let mut server_reader = server.try_clone()?;
+                    // Once either side closes the connection, so does the proxy
+                    thread::spawn(move || {
+                        let _ = io::copy(&mut server_reader, &mut client_writer);
+                        let _ = client_writer.shutdown(Shutdown::Both);
+                    });
+                    thread::spawn(move || {
+                        let _ = io::copy(&mut client, &mut server);
+                        let _ = server.shutdown(Shutdown::Both);
+                    });
+                    Ok(())
+                };
+                // The error can't be logged from a thread not managed by
+                // tarantool, the client sees the connection closed instead
+                let _ = forward();
+            }
+        });
+        Ok(())
+    }
 }
@@ -27,40 +27,465 @@
 //! - [Lua reference: Functions for transaction management](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_txn_management/)
 //! - [C API reference: Module txn](https://www.tarantool.io/en/doc/latest/dev_guide/reference_capi/txn/)
 
-use crate::error::TransactionError;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::error::{TarantoolErrorCode, TransactionError};
 use crate::ffi::tarantool as ffi;
+use crate::tuple::Tuple;
+
+/// Transaction isolation level.
+///
+/// See [transaction isolation levels](https://www.tarantool.io/en/doc/latest/concepts/atomic/txn_mode_mvcc/#txn-mode-mvcc-options)
+/// for details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IsolationLevel {
+    /// The level set by `box.cfg.txn_isolation`.
+    Default = 0,
+    /// Read changes that are committed but not confirmed yet.
+    ReadCommitted = 1,
+    /// Read only confirmed changes.
+    ReadConfirmed = 2,
+    /// Determine isolation level automatically.
+    BestEffort = 3,
+    /// Read the latest committed changes of the whole replicaset. Requires
+    /// tarantool 3.0 or later.
+    Linearizable = 4,
+}
+
+impl Default for IsolationLevel {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+/// Returns `true` if there is an active transaction in the current fiber.
+#[inline(always)]
+pub fn is_in_transaction() -> bool {
+    unsafe { ffi::box_txn() }
+}
 
 /// Begin a transaction in the current fiber.
 ///
 /// A transaction is attached to caller fiber, therefore one fiber can have
-/// only one active transaction.
+/// only one active transaction. If a transaction is already active, `f` is
+/// invoked within a nested one, see below.
 ///
 /// - `f` - function will be invoked within transaction
 ///
 /// Returns result of function `f` execution. Depending on the function result:
 /// - will **commit** - if function completes successfully
 /// - will **rollback** - if function completes with any error
+///
+/// # Nested transactions
+/// If called inside an active transaction, a [`Savepoint`] is created instead
+/// of starting a new transaction. If `f` fails, only the changes made by it
+/// are rolled back, otherwise they are kept and committed or rolled back
+/// along with the outer transaction. This allows to compose functions which
+/// use `start_transaction` without knowing whether the caller has already
+/// started one.
+///
+/// ```no_run
+/// use tarantool::error::Error;
+/// use tarantool::space::Space;
+/// use tarantool::transaction::start_transaction;
+///
+/// fn insert_pair(space: &Space, id: u32) -> Result<(), Error> {
+///     start_transaction(|| {
+///         space.insert(&(id,))?;
+///         space.insert(&(id + 1,))?;
+///         Ok(())
+///     })
+/// }
+///
+/// let space = Space::find("pairs").unwrap();
+/// start_transaction(|| -> Result<(), Error> {
+///     insert_pair(&space, 1)?;
+///     // Fails if 2 already exists, in which case only the second pair is
+///     // rolled back
+///     let _ = insert_pair(&space, 2);
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
 pub fn start_transaction<T, E, F>(f: F) -> Result<T, E>
 where
     F: FnOnce() -> Result<T, E>,
     E: From<TransactionError>,
 {
+    start_transaction_with_options(&TransactionOptions::default(), f)
+}
+
+/// Options of a transaction started with [`start_transaction_with_options`].
+///
+/// Isolation levels and timeouts require tarantool 2.10 or later, see
+/// [`has_transaction_options`]. Isolation levels other than
+/// [`IsolationLevel::Default`] only take effect with the memtx MVCC engine
+/// (`box.cfg.memtx_use_mvcc_engine`).
+///
+/// [`has_transaction_options`]: crate::ffi::has_transaction_options
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransactionOptions {
+    isolation: IsolationLevel,
+    timeout: Option<Duration>,
+}
+
+impl TransactionOptions {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the isolation level of the transaction.
+    ///
+    /// Default: [`IsolationLevel::Default`]
+    #[inline(always)]
+    pub fn isolation(mut self, isolation: IsolationLevel) -> Self {
+        self.isolation = isolation;
+        self
+    }
+
+    /// Sets the timeout after which the transaction is rolled back, in which
+    /// case committing it fails with [`TransactionError::TimedOut`].
+    ///
+    /// Default: `box.cfg.txn_timeout`
+    #[inline(always)]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Applies the options to the transaction which has just been started.
+    fn apply(&self) -> Result<(), TransactionError> {
+        if *self == Self::default() {
+            return Ok(());
+        }
+        if !crate::ffi::has_transaction_options() {
+            return Err(TransactionError::FailedToSetOptions);
+        }
+        if self.isolation != IsolationLevel::Default
+            && unsafe { ffi::box_txn_set_isolation(self.isolation as u32) } < 0
+        {
+            return Err(TransactionError::FailedToSetOptions);
+        }
+        if let Some(timeout) = self.timeout {
+            if unsafe { ffi::box_txn_set_timeout(timeout.as_secs_f64()) } < 0 {
+                return Err(TransactionError::FailedToSetOptions);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Same as [`start_transaction`], but the transaction is started with the
+/// given `options`.
+///
+/// The options are ignored if a transaction is already active, in which
+/// case a nested transaction is started.
+///
+/// If the transaction is aborted because of a conflict with a concurrent one,
+/// the function fails with [`TransactionError::Conflict`] and the transaction
+/// can be retried. This is the case both if committing the transaction fails
+/// and if `f` fails because its statement is executed within an aborted
+/// transaction, in which case the error returned by `f` is discarded.
+/// Likewise, [`TransactionError::TimedOut`] is returned if the transaction
+/// is rolled back because of the [timeout](TransactionOptions::timeout).
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tarantool::error::{Error, TransactionError};
+/// use tarantool::space::Space;
+/// use tarantool::transaction::{start_transaction_with_options, IsolationLevel, TransactionOptions};
+///
+/// let space = Space::find("counters").unwrap();
+/// let options = TransactionOptions::new()
+///     .isolation(IsolationLevel::ReadCommitted)
+///     .timeout(Duration::from_secs(1));
+/// loop {
+///     let result = start_transaction_with_options(&options, || -> Result<(), Error> {
+///         space.upsert(&("hits", 1), [("+", 1, 1)])?;
+///         Ok(())
+///     });
+///     match result {
+///         Err(Error::Transaction(TransactionError::Conflict)) => continue,
+///         result => break result.unwrap(),
+///     }
+/// }
+/// ```
+pub fn start_transaction_with_options<T, E, F>(options: &TransactionOptions, f: F) -> Result<T, E>
+where
+    F: FnOnce() -> Result<T, E>,
+    E: From<TransactionError>,
+{
+    if is_in_transaction() {
+        let savepoint = Savepoint::new()?;
+        let result = f();
+        match &result {
+            Ok(_) => savepoint.release(),
+            Err(_) => savepoint.rollback()?,
+        }
+        return result;
+    }
+
     if unsafe { ffi::box_txn_begin() } < 0 {
         return Err(TransactionError::AlreadyStarted.into());
     }
+    if let Err(e) = options.apply() {
+        unsafe { ffi::box_txn_rollback() };
+        return Err(e.into());
+    }
+    // Any error set from now on happened within the transaction
+    unsafe { ffi::box_error_clear() };
 
     let result = f();
     match &result {
         Ok(_) => {
             if unsafe { ffi::box_txn_commit() } < 0 {
-                return Err(TransactionError::FailedToCommit.into());
+                return Err(abort_reason()
+                    .unwrap_or(TransactionError::FailedToCommit)
+                    .into());
             }
         }
         Err(_) => {
+            // Statements executed within an aborted transaction fail with
+            // the reason it was aborted
+            let abort_reason = abort_reason();
             if unsafe { ffi::box_txn_rollback() } < 0 {
                 return Err(TransactionError::FailedToRollback.into());
             }
+            if let Some(e) = abort_reason {
+                return Err(e.into());
+            }
         }
     }
     result
 }
+
+/// Returns the reason the current transaction was aborted if the last error
+/// is caused by a conflict or the timeout of the transaction.
+fn abort_reason() -> Option<TransactionError> {
+    let error = unsafe { ffi::box_error_last() };
+    if error.is_null() {
+        return None;
+    }
+    match unsafe { ffi::box_error_code(error) } {
+        code if code == TarantoolErrorCode::TransactionConflict as u32 => {
+            Some(TransactionError::Conflict)
+        }
+        code if code == TarantoolErrorCode::TransactionTimeout as u32 => {
+            Some(TransactionError::TimedOut)
+        }
+        _ => None,
+    }
+}
+
+/// A point inside the current transaction to which it can be partially rolled
+/// back, discarding only the changes made after the savepoint was created.
+///
+/// Unless [released](Self::release), the changes are rolled back once the
+/// savepoint is dropped.
+///
+/// A savepoint is only valid within the transaction it was created in. Using
+/// it after the transaction has ended or from another fiber fails with
+/// [`TransactionError::SavepointExpired`], dropping it in that case does
+/// nothing.
+///
+/// ```no_run
+/// use tarantool::error::Error;
+/// use tarantool::space::Space;
+/// use tarantool::transaction::{start_transaction, Savepoint};
+///
+/// let space = Space::find("test").unwrap();
+/// start_transaction(|| -> Result<(), Error> {
+///     space.insert(&(1,))?;
+///     let savepoint = Savepoint::new()?;
+///     space.insert(&(2,))?;
+///     // Only the second insert is rolled back
+///     savepoint.rollback()?;
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Savepoint {
+    inner: NonNull<ffi::BoxTxnSavepoint>,
+    /// Id of the transaction the savepoint was created in. The savepoint is
+    /// allocated in the transaction's memory and is freed along with it.
+    txn_id: i64,
+    /// Savepoints are bound to the transaction of the current fiber.
+    marker: PhantomData<*const ()>,
+}
+
+impl Savepoint {
+    /// Creates a savepoint in the active transaction of the current fiber.
+    ///
+    /// Fails with [`TransactionError::FailedToCreateSavepoint`] if there is no
+    /// active transaction.
+    pub fn new() -> Result<Self, TransactionError> {
+        let inner = unsafe { ffi::box_txn_savepoint() };
+        let inner = NonNull::new(inner).ok_or(TransactionError::FailedToCreateSavepoint)?;
+        Ok(Self {
+            inner,
+            txn_id: unsafe { ffi::box_txn_id() },
+            marker: PhantomData,
+        })
+    }
+
+    /// Rolls back the changes made after the savepoint was created.
+    /// The changes made before it are kept.
+    pub fn rollback(self) -> Result<(), TransactionError> {
+        let res = self.rollback_impl();
+        std::mem::forget(self);
+        res
+    }
+
+    fn rollback_impl(&self) -> Result<(), TransactionError> {
+        // The savepoint's memory is freed once its transaction ends, so it
+        // must not be touched from any other transaction.
+        if unsafe { ffi::box_txn_id() } != self.txn_id {
+            return Err(TransactionError::SavepointExpired);
+        }
+        if unsafe { ffi::box_txn_rollback_to_savepoint(self.inner.as_ptr()) } < 0 {
+            return Err(TransactionError::FailedToRollbackToSavepoint);
+        }
+        Ok(())
+    }
+
+    /// Keeps the changes made after the savepoint was created. They are
+    /// committed or rolled back along with the transaction.
+    #[inline(always)]
+    pub fn release(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Savepoint {
+    fn drop(&mut self) {
+        match self.rollback_impl() {
+            // The savepoint is gone along with its transaction
+            Ok(()) | Err(TransactionError::SavepointExpired) => {}
+            Err(e) => log::warn!("{}: {}", e, crate::error::TarantoolError::last()),
+        }
+    }
+}
+
+/// Registers `f` to be called once the current transaction is committed.
+///
+/// `f` receives the statements of the transaction in the order they were
+/// executed. It is called after the changes are written to WAL, so it can be
+/// used to notify other systems about the changes which can't be rolled back
+/// anymore.
+///
+/// `f` **must not yield**. Fails if there is no active transaction.
+///
+/// ```no_run
+/// use tarantool::error::Error;
+/// use tarantool::space::Space;
+/// use tarantool::transaction::{on_commit, on_rollback, start_transaction};
+///
+/// let space = Space::find("orders").unwrap();
+/// start_transaction(|| -> Result<(), Error> {
+///     space.insert(&(1, "new"))?;
+///     on_commit(|statements| {
+///         for statement in statements {
+///             println!("space {}: {:?}", statement.space_id, statement.new);
+///         }
+///     })?;
+///     on_rollback(|| println!("not saved"))?;
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+pub fn on_commit<F>(f: F) -> crate::Result<()>
+where
+    F: FnOnce(Statements) + 'static,
+{
+    let statements = Rc::new(RefCell::new(Vec::new()));
+    let push = tlua::function3({
+        let statements = statements.clone();
+        move |old: Option<Tuple>, new: Option<Tuple>, space_id: u32| {
+            statements
+                .borrow_mut()
+                .push(Statement { space_id, old, new })
+        }
+    });
+    let mut f = Some(f);
+    let done = tlua::function0(move || {
+        if let Some(f) = f.take() {
+            f(Statements(statements.take().into_iter()));
+        }
+    });
+    crate::lua_state()
+        .exec_with(
+            "local push, done = ...
+            box.on_commit(function(iterator)
+                for _, old, new, space_id in iterator() do
+                    push(old, new, space_id)
+                end
+                done()
+            end)",
+            (push, done),
+        )
+        .map_err(tlua::LuaError::from)?;
+    Ok(())
+}
+
+/// Registers `f` to be called once the current transaction is rolled back.
+///
+/// `f` **must not yield**. Fails if there is no active transaction.
+///
+/// See also [`on_commit`].
+pub fn on_rollback<F>(f: F) -> crate::Result<()>
+where
+    F: FnOnce() + 'static,
+{
+    let mut f = Some(f);
+    let done = tlua::function0(move || {
+        if let Some(f) = f.take() {
+            f();
+        }
+    });
+    crate::lua_state()
+        .exec_with(
+            "local done = ...
+            box.on_rollback(function() done() end)",
+            done,
+        )
+        .map_err(tlua::LuaError::from)?;
+    Ok(())
+}
+
+/// Statement of a committed transaction, see [`on_commit`].
+#[derive(Debug)]
+pub struct Statement {
+    /// Id of the space the statement was executed on.
+    pub space_id: u32,
+    /// The tuple before the statement, `None` for inserts.
+    pub old: Option<Tuple>,
+    /// The tuple after the statement, `None` for deletes.
+    pub new: Option<Tuple>,
+}
+
+/// Iterator over the [`Statement`]s of a committed transaction, see
+/// [`on_commit`].
+#[derive(Debug)]
+pub struct Statements(std::vec::IntoIter<Statement>);
+
+impl Iterator for Statements {
+    type Item = Statement;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Statement> {
+        self.0.next()
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Statements {}
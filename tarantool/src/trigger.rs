@@ -1,6 +1,7 @@
-use crate::error::{TarantoolError, TarantoolErrorCode};
+use crate::error::{Error, TarantoolError, TarantoolErrorCode};
 use crate::ffi::tarantool as ffi;
 use crate::set_error;
+use crate::tuple::Tuple;
 
 use nix::errno;
 
@@ -26,3 +27,230 @@ pub fn on_shutdown<F: FnOnce() + 'static>(cb: F) -> Result<(), TarantoolError> {
         0
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Space triggers
+////////////////////////////////////////////////////////////////////////////////
+
+/// Key of the Lua registry table holding the installed space triggers, so that
+/// they can be found when a [`SpaceTrigger`] is removed.
+const SPACE_TRIGGERS_KEY: &str = "tarantool.space_triggers";
+
+crate::define_str_enum! {
+    /// Type of the request which caused a space trigger to fire.
+    pub enum RequestType {
+        Insert = "INSERT",
+        Replace = "REPLACE",
+        Update = "UPDATE",
+        Upsert = "UPSERT",
+        Delete = "DELETE",
+    }
+}
+
+/// Kind of change made to a tuple, see [`ReplaceEvent::operation`].
+///
+/// Unlike [`RequestType`] it describes the effect of the request rather than
+/// the request itself, e.g. an upsert may result in either an insertion or
+/// a replacement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// A new tuple is inserted, `old` is `None`.
+    Insert,
+    /// An existing tuple is replaced, both `old` and `new` are present.
+    Replace,
+    /// An existing tuple is deleted, `new` is `None`.
+    Delete,
+}
+
+/// Change passed to [`Space::on_replace`] and [`Space::before_replace`]
+/// triggers.
+///
+/// [`Space::on_replace`]: crate::space::Space::on_replace
+/// [`Space::before_replace`]: crate::space::Space::before_replace
+#[derive(Debug)]
+pub struct ReplaceEvent {
+    /// The tuple before the change, `None` for insertions.
+    pub old: Option<Tuple>,
+    /// The tuple after the change, `None` for deletions.
+    pub new: Option<Tuple>,
+    /// The request which caused the change.
+    pub request_type: RequestType,
+}
+
+impl ReplaceEvent {
+    /// Returns the kind of change made to the tuple.
+    #[inline]
+    pub fn operation(&self) -> Operation {
+        match (&self.old, &self.new) {
+            (None, Some(_)) => Operation::Insert,
+            (Some(_), Some(_)) => Operation::Replace,
+            (_, None) => Operation::Delete,
+        }
+    }
+}
+
+/// Decision made by a [`Space::before_replace`] trigger.
+///
+/// [`Space::before_replace`]: crate::space::Space::before_replace
+#[derive(Debug)]
+pub enum BeforeReplace {
+    /// Apply the change as is.
+    Keep,
+    /// Store the given tuple instead of [`ReplaceEvent::new`].
+    Replace(Tuple),
+    /// Silently skip the change leaving the old tuple (if any) in place.
+    Skip,
+}
+
+/// Handle to a trigger set with [`Space::on_replace`] or
+/// [`Space::before_replace`].
+///
+/// Dropping the handle does **not** remove the trigger, call
+/// [`SpaceTrigger::remove`] for that.
+///
+/// [`Space::on_replace`]: crate::space::Space::on_replace
+/// [`Space::before_replace`]: crate::space::Space::before_replace
+#[derive(Debug)]
+pub struct SpaceTrigger {
+    space_id: u32,
+    kind: &'static str,
+    id: u64,
+}
+
+impl SpaceTrigger {
+    /// Id of the space the trigger is set on.
+    #[inline(always)]
+    pub fn space_id(&self) -> u32 {
+        self.space_id
+    }
+
+    /// Removes the trigger from the space.
+    ///
+    /// Does nothing if the space has been dropped since.
+    pub fn remove(self) -> crate::Result<()> {
+        crate::lua_state()
+            .exec_with(
+                "local key, space_id, kind, id = ...
+                local triggers = debug.getregistry()[key]
+                local trigger = triggers and triggers[id]
+                if trigger == nil then
+                    return
+                end
+                triggers[id] = nil
+                local space = box.space[space_id]
+                if space ~= nil then
+                    space[kind](space, nil, trigger)
+                end",
+                (SPACE_TRIGGERS_KEY, self.space_id, self.kind, self.id),
+            )
+            .map_err(tlua::LuaError::from)?;
+        Ok(())
+    }
+}
+
+/// Sets an `on_replace` trigger on space `space_id`, see
+/// [`Space::on_replace`](crate::space::Space::on_replace).
+pub(crate) fn on_replace<F>(space_id: u32, mut f: F) -> crate::Result<SpaceTrigger>
+where
+    F: FnMut(ReplaceEvent) + 'static,
+{
+    let callback = tlua::function3(
+        move |old: Option<Tuple>, new: Option<Tuple>, request_type: RequestType| {
+            f(ReplaceEvent {
+                old,
+                new,
+                request_type,
+            })
+        },
+    );
+    set_space_trigger(
+        space_id,
+        "on_replace",
+        "function(old, new, _, request_type)
+            callback(old, new, request_type)
+        end",
+        callback,
+    )
+}
+
+/// Sets a `before_replace` trigger on space `space_id`, see
+/// [`Space::before_replace`](crate::space::Space::before_replace).
+pub(crate) fn before_replace<F>(space_id: u32, mut f: F) -> crate::Result<SpaceTrigger>
+where
+    F: FnMut(ReplaceEvent) -> crate::Result<BeforeReplace> + 'static,
+{
+    // Returns the resulting tuple or the code and the message of the error
+    // to be raised, so that the error code is preserved
+    let callback = tlua::function3(
+        move |old: Option<Tuple>,
+              new: Option<Tuple>,
+              request_type: RequestType|
+              -> (Option<Tuple>, Option<u32>, Option<String>) {
+            let event = ReplaceEvent {
+                old: old.clone(),
+                new: new.clone(),
+                request_type,
+            };
+            match f(event) {
+                Ok(BeforeReplace::Keep) => (new, None, None),
+                Ok(BeforeReplace::Replace(tuple)) => (Some(tuple), None, None),
+                Ok(BeforeReplace::Skip) => (old, None, None),
+                Err(Error::Tarantool(e)) => (None, Some(e.error_code()), Some(e.message().into())),
+                Err(e) => (
+                    None,
+                    Some(TarantoolErrorCode::ProcC as u32),
+                    Some(e.to_string()),
+                ),
+            }
+        },
+    );
+    set_space_trigger(
+        space_id,
+        "before_replace",
+        "function(old, new, _, request_type)
+            local tuple, code, reason = callback(old, new, request_type)
+            if code ~= nil then
+                box.error({ code = code, reason = reason })
+            end
+            return tuple
+        end",
+        callback,
+    )
+}
+
+/// Sets the Lua function `trigger` calling `callback` as a trigger of `kind`
+/// on space `space_id`.
+fn set_space_trigger<C>(
+    space_id: u32,
+    kind: &'static str,
+    trigger: &str,
+    callback: C,
+) -> crate::Result<SpaceTrigger>
+where
+    (&'static str, u32, &'static str, C): tlua::PushInto<tlua::LuaState>,
+    <(&'static str, u32, &'static str, C) as tlua::PushInto<tlua::LuaState>>::Err: Into<tlua::Void>,
+{
+    let code = format!(
+        "local key, space_id, kind, callback = ...
+        local space = box.space[space_id]
+        if space == nil then
+            box.error(box.error.NO_SUCH_SPACE, '#' .. space_id)
+        end
+        local trigger = {}
+        space[kind](space, trigger)
+        local registry = debug.getregistry()
+        local triggers = registry[key]
+        if triggers == nil then
+            triggers = {{ last_id = 0 }}
+            registry[key] = triggers
+        end
+        triggers.last_id = triggers.last_id + 1
+        triggers[triggers.last_id] = trigger
+        return triggers.last_id",
+        trigger
+    );
+    let id = crate::lua_state()
+        .eval_with(&code, (SPACE_TRIGGERS_KEY, space_id, kind, callback))
+        .map_err(tlua::LuaError::from)?;
+    Ok(SpaceTrigger { space_id, kind, id })
+}
@@ -8,7 +8,7 @@ struct Metadata {
 }
 
 fn main() {
-    let filter = env::args().skip(1);
+    let filter: Vec<_> = env::args().skip(1).collect();
     let tarantool_exec =
         env::var("TARANTOOL_EXECUTABLE").unwrap_or_else(|_| "tarantool".to_owned());
     let metadata = Command::new("cargo")
@@ -18,10 +18,19 @@ fn main() {
         .expect("failed to get cargo metadata output");
     let metadata: Metadata =
         serde_json::from_slice(&metadata.stdout).expect("failed to parse cargo metadata output");
-    let status = Command::new(tarantool_exec)
-        .arg(format!("{}/tests/run_tests.lua", metadata.workspace_root))
-        .args(filter)
-        .status()
-        .expect("failed to run tarantool child process");
-    assert!(status.success())
+    // The tests which require the memtx mvcc engine are run in a separate
+    // instance, see `run_tests.lua`
+    for mvcc in [false, true] {
+        let mut command = Command::new(&tarantool_exec);
+        command
+            .arg(format!("{}/tests/run_tests.lua", metadata.workspace_root))
+            .args(&filter);
+        if mvcc {
+            command.env("TARANTOOL_MODULE_TEST_MVCC", "1");
+        }
+        let status = command
+            .status()
+            .expect("failed to run tarantool child process");
+        assert!(status.success())
+    }
 }
@@ -27,11 +27,24 @@ end
 
 local port = free_port()
 
+-- The tests provoking transaction conflicts and timeouts require the memtx
+-- mvcc engine, which is available since 2.6. As it changes the isolation of
+-- all the transactions, they are run in a separate instance
+local mvcc = os.getenv('TARANTOOL_MODULE_TEST_MVCC') ~= nil
+if mvcc then
+    local major, minor = _TARANTOOL:match('^(%d+)%.(%d+)')
+    if tonumber(major) * 100 + tonumber(minor) < 206 then
+        fio.rmtree(tmpdir)
+        os.exit(0)
+    end
+end
+
 box.cfg{
     log_level = 'verbose',
     listen = port,
     wal_mode = 'none',
     memtx_dir = tmpdir,
+    memtx_use_mvcc_engine = mvcc or nil,
 }
 
 fio.rmtree(tmpdir)
@@ -56,6 +69,13 @@ function test_timeout()
     fiber.sleep(1.5)
 end
 
+function test_pushes(n)
+    for i = 1, n do
+        box.session.push(i)
+    end
+    return n
+end
+
 function test_schema_update()
     box.schema.space.create('test_s_tmp')
 end
@@ -92,6 +112,7 @@ package.cpath = string.format(
 cfg = json.encode {
     filter = arg[1] or "",
     listen = port,
+    mvcc = mvcc,
 }
 
 -- Run tests
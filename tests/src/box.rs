@@ -1,16 +1,20 @@
 use rand::Rng;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
+use std::rc::Rc;
 
-use tarantool::index::{self, IndexOptions, IteratorType};
+use tarantool::error::{Error, TarantoolError, TarantoolErrorCode};
+use tarantool::index::{self, IndexOptions, IteratorType, TuplePosition};
 use tarantool::sequence::Sequence;
 use tarantool::space::UpdateOps;
 use tarantool::space::{self, Field, Space, SpaceCreateOptions, SpaceEngineType, SystemSpace};
+use tarantool::trigger::{BeforeReplace, Operation, RequestType};
 use tarantool::tuple::Tuple;
 use tarantool::util::Value;
 use tarantool::{update, upsert};
 
-use crate::common::{QueryOperation, S1Record, S2Key, S2Record};
+use crate::common::{pagination_supported, QueryOperation, S1Record, S2Key, S2Record};
 
 pub fn space_get_by_name() {
     assert!(Space::find("test_s1").is_some());
@@ -187,6 +191,51 @@ pub fn select() {
     );
 }
 
+pub fn select_page() {
+    let index = Space::find("test_s2").unwrap().primary_key();
+    if !pagination_supported() {
+        assert!(index.select_page(IteratorType::All, &(), None, 5).is_err());
+        return;
+    }
+
+    let mut ids = vec![];
+    let mut pages = 0;
+    let mut after = None;
+    loop {
+        let (tuples, position) = index
+            .select_page(IteratorType::GE, &(3,), after.as_ref(), 7)
+            .unwrap();
+        if tuples.is_empty() {
+            assert!(position.is_none());
+            break;
+        }
+        pages += 1;
+        assert!(tuples.len() <= 7);
+        ids.extend(tuples.iter().map(|t| t.decode::<S1Record>().unwrap().id));
+        after = position;
+    }
+    assert_eq!(ids, (3..=20).collect::<Vec<_>>());
+    assert_eq!(pages, 3);
+
+    let tuple = index.get(&(10,)).unwrap().unwrap();
+    let position = index.tuple_position(&tuple).unwrap();
+    let (tuples, _) = index
+        .select_page(IteratorType::All, &(), Some(&position), 2)
+        .unwrap();
+    let ids: Vec<_> = tuples
+        .iter()
+        .map(|t| t.decode::<S1Record>().unwrap().id)
+        .collect();
+    assert_eq!(ids, [11, 12]);
+
+    // Position can be restored from bytes
+    let position = TuplePosition::from_bytes(position.into_bytes());
+    let (tuples, _) = index
+        .select_page(IteratorType::LT, &(), Some(&position), 1)
+        .unwrap();
+    assert_eq!(tuples[0].decode::<S1Record>().unwrap().id, 9);
+}
+
 pub fn select_composite_key() {
     let space = Space::find("test_s2").unwrap();
     let idx = space.index("idx_2").unwrap();
@@ -991,3 +1040,199 @@ pub fn index_parts() {
     );
     assert!(iter.next().is_none());
 }
+
+pub fn space_on_replace() {
+    let space = Space::builder("on_replace_test").create().unwrap();
+    space.index_builder("pk").create().unwrap();
+
+    let events = Rc::new(RefCell::new(vec![]));
+    let trigger = space
+        .on_replace({
+            let events = events.clone();
+            move |event| {
+                let old = event
+                    .old
+                    .as_ref()
+                    .map(|t| t.decode::<(i32, i32)>().unwrap());
+                let new = event
+                    .new
+                    .as_ref()
+                    .map(|t| t.decode::<(i32, i32)>().unwrap());
+                events
+                    .borrow_mut()
+                    .push((event.operation(), event.request_type, old, new));
+            }
+        })
+        .unwrap();
+    assert_eq!(trigger.space_id(), space.id());
+
+    space.insert(&(1, 1)).unwrap();
+    space.replace(&(1, 2)).unwrap();
+    space
+        .update(&[1], UpdateOps::new().add(1, 1).unwrap())
+        .unwrap();
+    space.delete(&[1]).unwrap();
+    assert_eq!(
+        events.take(),
+        [
+            (Operation::Insert, RequestType::Insert, None, Some((1, 1))),
+            (
+                Operation::Replace,
+                RequestType::Replace,
+                Some((1, 1)),
+                Some((1, 2))
+            ),
+            (
+                Operation::Replace,
+                RequestType::Update,
+                Some((1, 2)),
+                Some((1, 3))
+            ),
+            (Operation::Delete, RequestType::Delete, Some((1, 3)), None),
+        ]
+    );
+
+    trigger.remove().unwrap();
+    space.insert(&(2, 2)).unwrap();
+    assert!(events.borrow().is_empty());
+
+    space.drop().unwrap();
+}
+
+pub fn space_before_replace() {
+    let space = Space::builder("before_replace_test").create().unwrap();
+    space.index_builder("pk").create().unwrap();
+
+    let trigger = space
+        .before_replace(|event| {
+            let new = match &event.new {
+                Some(new) => new.decode::<(i32, i32)>()?,
+                None => return Ok(BeforeReplace::Skip),
+            };
+            match new {
+                (_, 0) => {
+                    tarantool::set_error!(TarantoolErrorCode::IllegalParams, "zero is not allowed");
+                    Err(TarantoolError::last().into())
+                }
+                (id, v) if v < 0 => Ok(BeforeReplace::Replace(Tuple::new(&(id, -v))?)),
+                _ => Ok(BeforeReplace::Keep),
+            }
+        })
+        .unwrap();
+
+    // Kept as is
+    space.insert(&(1, 1)).unwrap();
+    // Replaced by the trigger
+    space.insert(&(2, -2)).unwrap();
+    // Rejected by the trigger, the error code is preserved
+    let err = space.insert(&(3, 0)).unwrap_err();
+    assert!(err.to_string().contains("zero is not allowed"), "{}", err);
+    assert!(
+        matches!(&err, Error::Tarantool(e) if e.error_code() == TarantoolErrorCode::IllegalParams as u32),
+        "{}",
+        err
+    );
+    // Errors other than tarantool ones are reported as `ProcC`
+    let err = space.insert(&(3, "three")).unwrap_err();
+    assert!(
+        matches!(&err, Error::Tarantool(e) if e.error_code() == TarantoolErrorCode::ProcC as u32),
+        "{}",
+        err
+    );
+    // Skipped by the trigger
+    space.delete(&[1]).unwrap();
+
+    let rows: Vec<(i32, i32)> = space
+        .select(IteratorType::All, &())
+        .unwrap()
+        .map(|t| t.decode().unwrap())
+        .collect();
+    assert_eq!(rows, [(1, 1), (2, 2)]);
+
+    trigger.remove().unwrap();
+    space.delete(&[1]).unwrap();
+    assert_eq!(space.len().unwrap(), 1);
+
+    space.drop().unwrap();
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, tarantool::space::Space)]
+#[space(name = "typed_space_test")]
+#[space(index(name = "pk", parts(id)))]
+#[space(index(name = "by_name", parts(name, age), unique = false))]
+struct TypedRecord {
+    id: u32,
+    name: String,
+    age: Option<u8>,
+    #[space(field_type = "any")]
+    extra: rmpv::Value,
+}
+
+impl tarantool::tuple::Encode for TypedRecord {}
+
+pub fn typed_space() {
+    use tarantool::space::{SpaceSchema, TypedSpace};
+
+    let format = TypedRecord::format();
+    let format: Vec<_> = format
+        .iter()
+        .map(|f| (f.name.as_str(), f.field_type, f.is_nullable))
+        .collect();
+    assert_eq!(
+        format,
+        [
+            ("id", space::FieldType::Unsigned, false),
+            ("name", space::FieldType::String, false),
+            ("age", space::FieldType::Unsigned, true),
+            ("extra", space::FieldType::Any, false),
+        ]
+    );
+
+    let typed = TypedSpace::<TypedRecord>::create(&Default::default()).unwrap();
+    assert!(TypedSpace::<TypedRecord>::find().is_some());
+    let by_name = typed.space().index("by_name").unwrap();
+
+    let alice = TypedRecord {
+        id: 1,
+        name: "Alice".into(),
+        age: Some(30),
+        extra: rmpv::Value::from("foo"),
+    };
+    let bob = TypedRecord {
+        id: 2,
+        name: "Bob".into(),
+        age: None,
+        extra: rmpv::Value::from(13),
+    };
+    assert_eq!(typed.insert(&alice).unwrap(), alice);
+    assert_eq!(typed.insert(&bob).unwrap(), bob);
+    typed.insert(&bob).unwrap_err();
+
+    assert_eq!(typed.get(&(1,)).unwrap(), Some(alice));
+    assert_eq!(typed.get(&(3,)).unwrap(), None);
+
+    let bob = TypedRecord {
+        age: Some(40),
+        ..bob
+    };
+    assert_eq!(typed.replace(&bob).unwrap(), bob);
+
+    let rows: Vec<TypedRecord> = typed
+        .select(IteratorType::GE, &(2,))
+        .unwrap()
+        .collect::<tarantool::Result<_>>()
+        .unwrap();
+    assert_eq!(rows, [bob]);
+    assert_eq!(
+        by_name
+            .select(IteratorType::Eq, &("Bob", 40))
+            .unwrap()
+            .count(),
+        1
+    );
+
+    assert_eq!(typed.delete(&(2,)).unwrap().map(|r| r.id), Some(2));
+    assert_eq!(typed.delete(&(2,)).unwrap(), None);
+
+    typed.space().drop().unwrap();
+}
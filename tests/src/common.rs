@@ -157,3 +157,11 @@ pub fn lib_name() -> String {
             .clone()
     })
 }
+
+/// Returns `true` if the running tarantool supports keyset pagination
+/// (`after` & `fetch_pos` select options), which was added in 2.11.
+pub fn pagination_supported() -> bool {
+    tarantool::lua_state()
+        .eval("return box.space._space.index[0].tuple_pos ~= nil")
+        .unwrap()
+}
@@ -73,6 +73,11 @@ struct TestConfig {
 
     #[serde(default = "default_listen")]
     listen: u16,
+
+    /// The instance is configured with the memtx mvcc engine, in which case
+    /// only the tests requiring it are run.
+    #[serde(default)]
+    mvcc: bool,
 }
 
 const fn default_listen() -> u16 {
@@ -442,6 +447,7 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 r#box::truncate,
                 r#box::get,
                 r#box::select,
+                r#box::select_page,
                 r#box::select_composite_key,
                 r#box::len,
                 r#box::random,
@@ -461,6 +467,9 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 r#box::space_drop,
                 r#box::index_create_drop,
                 r#box::index_parts,
+                r#box::space_on_replace,
+                r#box::space_before_replace,
+                r#box::typed_space,
                 tuple::tuple_new_from_struct,
                 tuple::new_tuple_from_flatten_struct,
                 tuple::tuple_field_count,
@@ -493,6 +502,12 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 coio::channel_tx_closed,
                 transaction::transaction_commit,
                 transaction::transaction_rollback,
+                transaction::transaction_nested_commit,
+                transaction::transaction_nested_rollback,
+                transaction::savepoint,
+                transaction::savepoint_expired,
+                transaction::transaction_options,
+                transaction::transaction_triggers,
                 log::log_with_user_defined_mapping,
                 #[should_panic]
                 log::zlog,
@@ -500,33 +515,45 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 latch::latch_try_lock,
                 net_box::immediate_close,
                 net_box::ping,
+                net_box::auth_method_unsupported,
                 net_box::ping_timeout,
                 net_box::ping_concurrent,
                 net_box::call,
                 net_box::call_async,
+                net_box::call_with_pushes,
+                net_box::call_with_pushes_timeout,
                 net_box::call_async_error,
                 net_box::call_async_disconnected,
                 net_box::call_timeout,
                 net_box::call_async_timeout,
                 net_box::call_async_wait_disconnected,
+                net_box::call_async_await,
                 net_box::eval,
                 net_box::eval_async,
+                net_box::extended_error,
                 net_box::async_common_cond,
                 net_box::connection_error,
+                net_box::connect_unix,
                 net_box::is_connected,
                 net_box::schema_sync,
                 net_box::select,
+                net_box::select_page,
                 net_box::get,
                 net_box::insert,
                 net_box::replace,
                 net_box::update,
                 net_box::upsert,
                 net_box::delete,
+                net_box::crud_async,
+                net_box::interceptor,
                 net_box::cancel_recv,
                 net_box::triggers_connect,
                 net_box::triggers_reject,
                 net_box::triggers_schema_sync,
                 net_box::execute,
+                net_box::execute_sql_response,
+                net_box::prepare,
+                net_box::prepare_twice,
                 session::uid,
                 session::euid,
                 proc::simple,
@@ -565,6 +592,15 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                     tuple_picodata::tuple_as_named_buffer,
                 ])
             }
+            // The memtx mvcc engine changes the isolation of all the
+            // transactions, so the tests requiring it are run in a separately
+            // configured instance, see `run_tests.lua`
+            if cfg.mvcc {
+                tests = tests![
+                    transaction::transaction_conflict,
+                    transaction::transaction_timeout,
+                ];
+            }
             tests
         },
     )
@@ -3,15 +3,21 @@ use std::io;
 use std::rc::Rc;
 use std::time::Duration;
 
-use tarantool::error::Error;
-use tarantool::fiber::{reschedule, sleep, start_proc, Cond, Fiber};
+use tarantool::auth::AuthMethod;
+use tarantool::error::{Error, TarantoolErrorCode};
+use tarantool::fiber::r#async::timeout::IntoTimeout;
+use tarantool::fiber::{self, reschedule, sleep, start_proc, Cond, Fiber};
 use tarantool::index::IteratorType;
-use tarantool::net_box::{promise::State, Conn, ConnOptions, ConnTriggers, Options};
+use tarantool::net_box::{
+    promise::State, pushes::CallMessage, Conn, ConnOptions, ConnTriggers, Options,
+};
+use tarantool::network::interceptor::{Interceptor, RequestInfo, RequestKind, ResponseInfo};
 use tarantool::space::Space;
+use tarantool::test::util::unix_socket_proxy;
 use tarantool::tuple::Tuple;
 
 use crate::{
-    common::{QueryOperation, S1Record, S2Record},
+    common::{pagination_supported, QueryOperation, S1Record, S2Record},
     LISTEN,
 };
 use std::cell::{Cell, RefCell};
@@ -48,6 +54,25 @@ pub fn ping() {
     conn.ping(&Options::default()).unwrap();
 }
 
+pub fn auth_method_unsupported() {
+    let conn = Conn::new(
+        ("localhost", unsafe { LISTEN }),
+        ConnOptions {
+            user: "test_user".into(),
+            password: "password".into(),
+            auth_method: AuthMethod::PapSha256,
+            ..ConnOptions::default()
+        },
+        None,
+    )
+    .unwrap();
+    let err = conn.ping(&Options::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::UnsupportedAuthMethod(AuthMethod::PapSha256)
+    ));
+}
+
 pub fn execute() {
     Space::find("test_s1")
         .unwrap()
@@ -63,7 +88,7 @@ pub fn execute() {
     let result = conn
         .execute(r#"SELECT * FROM "test_s1""#, &(), &Options::default())
         .expect("IPROTO execute sql request fail");
-    assert!(result.len() >= 2);
+    assert!(result.rows().len() >= 2);
 
     let result = conn
         .execute(
@@ -73,13 +98,157 @@ pub fn execute() {
         )
         .expect("IPROTO execute sql request fail");
 
-    assert_eq!(result.len(), 1);
+    assert_eq!(result.rows().len(), 1);
     assert_eq!(
-        result.get(0).unwrap().decode::<(u64, String)>().unwrap(),
+        result.rows()[0].decode::<(u64, String)>().unwrap(),
         (6002, "6002".to_string())
     );
 }
 
+pub fn execute_sql_response() {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Row {
+        #[serde(rename = "NAME")]
+        name: String,
+        #[serde(rename = "ID")]
+        id: i64,
+    }
+
+    let conn: Conn = test_user_conn();
+    let options = Options::default();
+
+    conn.execute(
+        "CREATE TABLE SQL_RESPONSE_TEST (ID INTEGER PRIMARY KEY AUTOINCREMENT, NAME STRING)",
+        &(),
+        &options,
+    )
+    .unwrap();
+
+    let result = conn
+        .execute(
+            "INSERT INTO SQL_RESPONSE_TEST VALUES (NULL, 'foo'), (NULL, 'bar')",
+            &(),
+            &options,
+        )
+        .unwrap();
+    assert_eq!(result.row_count(), Some(2));
+    assert_eq!(result.autoincrement_ids(), [1, 2]);
+    assert!(result.rows().is_empty());
+
+    let result = conn
+        .execute(
+            "SELECT NAME, ID FROM SQL_RESPONSE_TEST WHERE ID > ? ORDER BY ID",
+            &(0,),
+            &options,
+        )
+        .unwrap();
+    assert!(result.info().is_none());
+    let columns: Vec<_> = result
+        .metadata()
+        .iter()
+        .map(|c| (c.name.as_str(), c.field_type.as_str()))
+        .collect();
+    assert_eq!(columns, [("NAME", "string"), ("ID", "integer")]);
+    assert_eq!(
+        result.decode_rows::<Row>().unwrap(),
+        [
+            Row {
+                name: "foo".into(),
+                id: 1
+            },
+            Row {
+                name: "bar".into(),
+                id: 2
+            },
+        ]
+    );
+
+    let result = conn
+        .execute("DELETE FROM SQL_RESPONSE_TEST", &(), &options)
+        .unwrap();
+    assert_eq!(result.row_count(), Some(2));
+
+    conn.execute("DROP TABLE SQL_RESPONSE_TEST", &(), &options)
+        .unwrap();
+}
+
+pub fn prepare() {
+    fn stmt_count() -> usize {
+        tarantool::lua_state()
+            .eval("return box.info.sql().cache.stmt_count")
+            .unwrap()
+    }
+
+    let space = Space::find("test_s1").unwrap();
+    space.insert(&(6501, "6501")).unwrap();
+    space.insert(&(6502, "6502")).unwrap();
+
+    let conn: Conn = test_user_conn();
+    let options = Options::default();
+    conn.ping(&options).unwrap();
+    let initial_count = stmt_count();
+
+    let stmt = conn
+        .prepare(r#"SELECT "text" FROM "test_s1" WHERE "id" = ?"#, &options)
+        .unwrap();
+    assert_eq!(stmt_count(), initial_count + 1);
+    assert_eq!(stmt.response().bind_count(), Some(1));
+    assert_eq!(stmt.response().bind_metadata()[0].name, "?");
+    assert_eq!(stmt.response().metadata().len(), 1);
+    assert_eq!(stmt.response().metadata()[0].name, "text");
+
+    for id in [6501, 6502] {
+        let result = stmt.execute(&(id,), &options).unwrap();
+        assert_eq!(result.rows().len(), 1);
+        assert_eq!(
+            result.rows()[0].decode::<(String,)>().unwrap(),
+            (id.to_string(),)
+        );
+    }
+
+    // Statement is deallocated on the server once dropped
+    drop(stmt);
+    conn.ping(&options).unwrap();
+    assert_eq!(stmt_count(), initial_count);
+
+    let err = conn
+        .prepare("SELECT * FROM unexistent_space", &options)
+        .unwrap_err();
+    assert!(matches!(err, Error::Remote(_)));
+
+    space.delete(&(6501,)).unwrap();
+    space.delete(&(6502,)).unwrap();
+}
+
+pub fn prepare_twice() {
+    fn stmt_count() -> usize {
+        tarantool::lua_state()
+            .eval("return box.info.sql().cache.stmt_count")
+            .unwrap()
+    }
+
+    let conn: Conn = test_user_conn();
+    let options = Options::default();
+    conn.ping(&options).unwrap();
+    let initial_count = stmt_count();
+
+    let sql = r#"SELECT "text" FROM "test_s1" WHERE "id" = ?"#;
+    let stmt_1 = conn.prepare(sql, &options).unwrap();
+    let stmt_2 = conn.prepare(sql, &options).unwrap();
+    assert_eq!(stmt_1.id(), stmt_2.id());
+    assert_eq!(stmt_count(), initial_count + 1);
+
+    // The statement is still prepared while it has handles
+    drop(stmt_1);
+    conn.ping(&options).unwrap();
+    assert_eq!(stmt_count(), initial_count + 1);
+    stmt_2.execute(&(6501,), &options).unwrap();
+
+    drop(stmt_2);
+    conn.ping(&options).unwrap();
+    assert_eq!(stmt_count(), initial_count);
+}
+
 pub fn ping_timeout() {
     let conn = default_conn();
 
@@ -142,6 +311,58 @@ pub fn call_async() {
     assert_eq!(tuple.decode::<(i32,)>().unwrap(), (489,));
 }
 
+pub fn call_with_pushes() {
+    let conn = test_user_conn();
+    let messages = conn
+        .call_with_pushes("test_pushes", (3,), &Options::default())
+        .unwrap();
+    let messages: Vec<_> = messages.map(Result::unwrap).collect();
+    assert_eq!(messages.len(), 4);
+    for (i, message) in messages[..3].iter().enumerate() {
+        match message {
+            CallMessage::Push(data) => {
+                assert_eq!(rmp_serde::from_slice::<usize>(data).unwrap(), i + 1);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+    match &messages[3] {
+        CallMessage::Response(Some(result)) => {
+            assert_eq!(result.decode::<(i32,)>().unwrap(), (3,));
+        }
+        other => panic!("unexpected message: {:?}", other),
+    }
+
+    // Pushes are ignored by other requests
+    let result = conn
+        .call("test_pushes", &(2,), &Options::default())
+        .unwrap();
+    assert_eq!(result.unwrap().decode::<(i32,)>().unwrap(), (2,));
+    let result: (i32,) = conn
+        .call_async("test_pushes", (2,))
+        .unwrap()
+        .wait()
+        .unwrap();
+    assert_eq!(result, (2,));
+}
+
+pub fn call_with_pushes_timeout() {
+    let conn = test_user_conn();
+    let mut messages = conn
+        .call_with_pushes(
+            "test_timeout",
+            (),
+            &Options {
+                timeout: Some(Duration::from_millis(100)),
+                ..Options::default()
+            },
+        )
+        .unwrap();
+    let err = messages.next().unwrap().unwrap_err();
+    assert!(matches!(err, Error::IO(ref e) if e.kind() == io::ErrorKind::TimedOut));
+    assert!(messages.next().is_none());
+}
+
 pub fn call_async_error() {
     let conn = test_user_conn();
     let p = conn
@@ -216,6 +437,34 @@ pub fn call_async_wait_disconnected() {
     jh.join();
 }
 
+pub fn call_async_await() {
+    let conn = test_user_conn();
+    let (r1, r2) = fiber::block_on(async {
+        let p1 = conn
+            .call_async::<_, (i32,)>("test_stored_proc", (69, 420))
+            .unwrap();
+        let p2 = conn
+            .call_async::<_, (i32,)>("test_stored_proc", (13, 37))
+            .unwrap();
+        futures::join!(p1, p2)
+    });
+    assert_eq!(r1.unwrap(), (489,));
+    assert_eq!(r2.unwrap(), (50,));
+
+    let p = conn.call_async::<_, ()>("test_timeout", ()).unwrap();
+    let res = fiber::block_on(p.timeout(Duration::from_millis(100)));
+    assert!(matches!(res, Err(fiber::r#async::timeout::Error::Expired)));
+
+    let p = conn.call_async::<_, ()>("test_timeout", ()).unwrap();
+    let jh = start_proc(|| {
+        reschedule();
+        drop(conn);
+    });
+    let err = fiber::block_on(p).unwrap_err();
+    assert_eq!(err.to_string(), "IO error: not connected");
+    jh.join();
+}
+
 pub fn eval() {
     let conn = test_user_conn();
     let result = conn
@@ -224,6 +473,35 @@ pub fn eval() {
     assert_eq!(result.unwrap().decode::<(i32, i32)>().unwrap(), (1, 2));
 }
 
+pub fn extended_error() {
+    let conn = test_user_conn();
+    let expr = "local e = box.error.new{type = 'MyError', reason = 'boom'}
+        e:set_prev(box.error.new(box.error.ILLEGAL_PARAMS, 'bad'))
+        error(e)";
+    let check = |err: Error| {
+        let err = match err {
+            Error::Remote(e) => e,
+            e => panic!("unexpected error: {}", e),
+        };
+        assert_eq!(err.message(), "boom");
+        let extended = err.extended().unwrap();
+        assert_eq!(extended.error_type, "CustomError");
+        assert_eq!(extended.fields["custom_type"], rmpv::Value::from("MyError"));
+        let cause = extended.cause().unwrap();
+        assert_eq!(cause.error_type, "ClientError");
+        assert_eq!(cause.code, TarantoolErrorCode::IllegalParams as u32);
+        assert_eq!(cause.message, "Illegal parameters, bad");
+    };
+
+    check(conn.eval(expr, &(), &Options::default()).unwrap_err());
+    check(
+        conn.eval_async::<_, ()>(expr, ())
+            .unwrap()
+            .wait()
+            .unwrap_err(),
+    );
+}
+
 pub fn eval_async() {
     let conn = test_user_conn();
     let expr = "return require 'math'.modf(...)";
@@ -287,6 +565,53 @@ pub fn connection_error() {
     assert!(matches!(conn.ping(&Options::default()), Err(_)));
 }
 
+pub fn connect_unix() {
+    let port = unsafe { LISTEN };
+    let path = std::env::temp_dir().join(format!("net-box-{}.sock", std::process::id()));
+    unix_socket_proxy(&path, port).unwrap();
+
+    let conn = Conn::new(
+        format!("unix/:{}", path.display()),
+        ConnOptions {
+            user: "test_user".into(),
+            password: "password".into(),
+            ..ConnOptions::default()
+        },
+        None,
+    )
+    .unwrap();
+    conn.ping(&Options::default()).unwrap();
+    assert!(conn.space("test_s2").unwrap().is_some());
+    conn.close();
+
+    let conn = Conn::new(
+        format!("unix/:{}", path.display()),
+        ConnOptions {
+            user: "test_user".into(),
+            password: "password".into(),
+            connect_timeout: Duration::from_secs(3),
+            ..ConnOptions::default()
+        },
+        None,
+    )
+    .unwrap();
+    conn.ping(&Options::default()).unwrap();
+    conn.close();
+
+    let conn = Conn::new(
+        "unix/:/nonexistent/tarantool.sock",
+        ConnOptions::default(),
+        None,
+    )
+    .unwrap();
+    assert!(matches!(conn.ping(&Options::default()), Err(Error::IO(_))));
+
+    let res = Conn::new("localhost", ConnOptions::default(), None);
+    assert!(matches!(res, Err(Error::IO(e)) if e.kind() == io::ErrorKind::InvalidInput));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
 pub fn is_connected() {
     let port = unsafe { LISTEN };
     let conn = Conn::new(
@@ -371,6 +696,39 @@ pub fn select() {
     );
 }
 
+pub fn select_page() {
+    let conn = test_user_conn();
+    let index = conn.space("test_s2").unwrap().unwrap().primary_key();
+    let options = Options {
+        limit: Some(7),
+        ..Options::default()
+    };
+
+    if !pagination_supported() {
+        let err = index
+            .select_page(IteratorType::All, &(), None, &options)
+            .unwrap_err();
+        assert!(matches!(err, Error::IO(e) if e.kind() == io::ErrorKind::Unsupported));
+        return;
+    }
+
+    let mut ids = vec![];
+    let mut after = None;
+    loop {
+        let (tuples, position) = index
+            .select_page(IteratorType::GE, &(3,), after.as_ref(), &options)
+            .unwrap();
+        if tuples.is_empty() {
+            assert!(position.is_none());
+            break;
+        }
+        assert!(tuples.len() <= 7);
+        ids.extend(tuples.iter().map(|t| t.decode::<S1Record>().unwrap().id));
+        after = position;
+    }
+    assert_eq!(ids, (3..=20).collect::<Vec<_>>());
+}
+
 pub fn insert() {
     let local_space = Space::find("test_s1").unwrap();
     local_space.truncate().unwrap();
@@ -529,6 +887,158 @@ pub fn delete() {
     assert!(output.is_none());
 }
 
+pub fn crud_async() {
+    let local_space = Space::find("test_s1").unwrap();
+    local_space.truncate().unwrap();
+
+    let conn = test_user_conn();
+    let remote_space = conn.space("test_s1").unwrap().unwrap();
+    let record = |id, text: &str| S1Record {
+        id,
+        text: text.into(),
+    };
+    let decode = |t: Option<Tuple>| t.map(|t| t.decode::<S1Record>().unwrap());
+
+    fiber::block_on(async {
+        let (r1, r2) = futures::join!(
+            remote_space.insert_async(&record(1, "one")).unwrap(),
+            remote_space.insert_async(&record(2, "two")).unwrap(),
+        );
+        assert_eq!(decode(r1.unwrap()), Some(record(1, "one")));
+        assert_eq!(decode(r2.unwrap()), Some(record(2, "two")));
+
+        let res = remote_space.insert_async(&record(1, "one")).unwrap().await;
+        assert!(
+            matches!(res, Err(Error::Remote(e)) if e.code() == TarantoolErrorCode::TupleFound as u32)
+        );
+
+        let res = remote_space
+            .replace_async(&record(2, "zwei"))
+            .unwrap()
+            .await;
+        assert_eq!(decode(res.unwrap()), Some(record(2, "zwei")));
+
+        let ops = [QueryOperation {
+            op: "=".to_string(),
+            field_id: 1,
+            value: "uno".into(),
+        }];
+        let res = remote_space.update_async(&(1,), &ops).unwrap().await;
+        assert_eq!(decode(res.unwrap()), Some(record(1, "uno")));
+
+        let res = remote_space
+            .upsert_async(&record(3, "three"), &ops)
+            .unwrap()
+            .await;
+        assert!(res.unwrap().is_none());
+
+        let res = remote_space.get_async(&(3,)).unwrap().await;
+        assert_eq!(decode(res.unwrap()), Some(record(3, "three")));
+        let res = remote_space.get_async(&(4,)).unwrap().await;
+        assert!(res.unwrap().is_none());
+
+        let res = remote_space
+            .select_async(
+                IteratorType::GE,
+                &(2,),
+                &Options {
+                    limit: Some(1),
+                    ..Options::default()
+                },
+            )
+            .unwrap()
+            .await;
+        let tuples: Vec<_> = res.unwrap().into_iter().map(Some).map(decode).collect();
+        assert_eq!(tuples, vec![Some(record(2, "zwei"))]);
+
+        let res = remote_space.delete_async(&(1,)).unwrap().await;
+        assert_eq!(decode(res.unwrap()), Some(record(1, "uno")));
+    });
+
+    let output = local_space.get(&(1,)).unwrap();
+    assert!(output.is_none());
+    assert_eq!(local_space.len().unwrap(), 2);
+}
+
+#[derive(Default)]
+struct Recorder {
+    requests: RefCell<Vec<RequestInfo>>,
+    responses: RefCell<Vec<(RequestInfo, usize, Option<String>)>>,
+}
+
+impl Interceptor for Recorder {
+    fn before_request(&self, request: &RequestInfo) {
+        self.requests.borrow_mut().push(request.clone());
+    }
+
+    fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+        self.responses.borrow_mut().push((
+            request.clone(),
+            response.size,
+            response.error.map(ToString::to_string),
+        ));
+    }
+}
+
+pub fn interceptor() {
+    let conn = test_user_conn();
+    let space = conn.space("test_s2").unwrap().unwrap();
+    let recorder = Rc::new(Recorder::default());
+    conn.add_interceptor(recorder.clone());
+
+    conn.call("test_stored_proc", &(1, 2), &Options::default())
+        .unwrap();
+    let p = conn
+        .call_async::<_, ()>("Procedure is not defined", ())
+        .unwrap();
+    p.wait().unwrap_err();
+    space
+        .select(IteratorType::Eq, &(16,), &Options::default())
+        .unwrap();
+
+    let requests = recorder.requests.borrow().clone();
+    let responses = recorder.responses.borrow().clone();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(responses.len(), 3);
+    for (request, (response_to, size, _)) in requests.iter().zip(&responses) {
+        assert_eq!(request, response_to);
+        assert!(request.size > 0);
+        assert!(*size > 0);
+    }
+
+    assert_eq!(requests[0].kind, RequestKind::Call);
+    assert_eq!(requests[0].name.as_deref(), Some("test_stored_proc"));
+    assert_eq!(responses[0].2, None);
+
+    assert_eq!(
+        requests[1].name.as_deref(),
+        Some("Procedure is not defined")
+    );
+    assert_eq!(requests[1].sync, requests[0].sync + 1);
+    assert_eq!(
+        responses[1].2.as_deref(),
+        Some("Server responded with error: Procedure 'Procedure is not defined' is not defined")
+    );
+
+    assert_eq!(requests[2].kind, RequestKind::Select);
+    assert_eq!(
+        requests[2].space_id,
+        Some(Space::find("test_s2").unwrap().id())
+    );
+    assert_eq!(requests[2].name.as_deref(), Some("test_s2"));
+    assert_eq!(responses[2].2, None);
+
+    // Requests awaiting response fail once the connection is closed
+    let p = conn.call_async::<_, ()>("test_timeout", ()).unwrap();
+    conn.close();
+    p.wait().unwrap_err();
+    let responses = recorder.responses.borrow();
+    let (request, size, error) = responses.last().unwrap();
+    assert_eq!(request.name.as_deref(), Some("test_timeout"));
+    assert_eq!(*size, 0);
+    assert!(error.is_some());
+}
+
 pub fn cancel_recv() {
     let conn = Rc::new(default_conn());
 
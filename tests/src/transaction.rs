@@ -1,8 +1,15 @@
+use std::cell::{Cell, RefCell};
 use std::io;
+use std::rc::Rc;
+use std::time::Duration;
 
-use tarantool::error::Error;
+use tarantool::error::{Error, TransactionError};
+use tarantool::fiber;
 use tarantool::space::Space;
-use tarantool::transaction::start_transaction;
+use tarantool::transaction::{
+    is_in_transaction, on_commit, on_rollback, start_transaction, start_transaction_with_options,
+    IsolationLevel, Savepoint, TransactionOptions,
+};
 
 use crate::common::S1Record;
 
@@ -42,3 +49,298 @@ pub fn transaction_rollback() {
     let output = space.get(&(1,)).unwrap();
     assert!(output.is_none());
 }
+
+fn record(id: u32) -> S1Record {
+    S1Record {
+        id,
+        text: "test".to_string(),
+    }
+}
+
+pub fn transaction_nested_commit() {
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    let result = start_transaction(|| -> Result<(), Error> {
+        space.insert(&record(1))?;
+        start_transaction(|| -> Result<(), Error> {
+            assert!(is_in_transaction());
+            space.insert(&record(2))?;
+            Ok(())
+        })
+    });
+    assert!(result.is_ok());
+    assert!(!is_in_transaction());
+
+    assert!(space.get(&(1,)).unwrap().is_some());
+    assert!(space.get(&(2,)).unwrap().is_some());
+}
+
+pub fn transaction_nested_rollback() {
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    let result = start_transaction(|| -> Result<(), Error> {
+        space.insert(&record(1))?;
+        let nested = start_transaction(|| -> Result<(), Error> {
+            space.insert(&record(2))?;
+            Err(Error::IO(io::ErrorKind::Interrupted.into()))
+        });
+        assert!(nested.is_err());
+        // Only the changes of the nested transaction are rolled back
+        assert!(space.get(&(2,)).unwrap().is_none());
+        space.insert(&record(3))?;
+        Ok(())
+    });
+    assert!(result.is_ok());
+
+    assert!(space.get(&(1,)).unwrap().is_some());
+    assert!(space.get(&(2,)).unwrap().is_none());
+    assert!(space.get(&(3,)).unwrap().is_some());
+
+    // The outer rollback discards the changes of the nested transaction
+    let result = start_transaction(|| -> Result<(), Error> {
+        start_transaction(|| -> Result<(), Error> {
+            space.insert(&record(4))?;
+            Ok(())
+        })?;
+        Err(Error::IO(io::ErrorKind::Interrupted.into()))
+    });
+    assert!(result.is_err());
+    assert!(space.get(&(4,)).unwrap().is_none());
+}
+
+pub fn savepoint() {
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    start_transaction(|| -> Result<(), Error> {
+        space.insert(&record(1))?;
+
+        let savepoint = Savepoint::new()?;
+        space.insert(&record(2))?;
+        savepoint.rollback()?;
+        assert!(space.get(&(2,)).unwrap().is_none());
+
+        let savepoint = Savepoint::new()?;
+        space.insert(&record(3))?;
+        savepoint.release();
+
+        {
+            let _savepoint = Savepoint::new()?;
+            space.insert(&record(4))?;
+            // Is rolled back on drop
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    assert!(space.get(&(1,)).unwrap().is_some());
+    assert!(space.get(&(2,)).unwrap().is_none());
+    assert!(space.get(&(3,)).unwrap().is_some());
+    assert!(space.get(&(4,)).unwrap().is_none());
+
+    let err = Savepoint::new().unwrap_err();
+    assert!(matches!(err, TransactionError::FailedToCreateSavepoint));
+}
+
+pub fn savepoint_expired() {
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    // Savepoint escapes the transaction it was created in
+    let savepoint = start_transaction(Savepoint::new).unwrap();
+    start_transaction(|| -> Result<(), Error> {
+        space.insert(&record(1))?;
+        let err = savepoint.rollback().unwrap_err();
+        assert!(matches!(err, TransactionError::SavepointExpired));
+        Ok(())
+    })
+    .unwrap();
+    assert!(space.get(&(1,)).unwrap().is_some());
+
+    // Dropping an expired savepoint inside another transaction does nothing
+    let savepoint = start_transaction(Savepoint::new).unwrap();
+    start_transaction(|| -> Result<(), Error> {
+        space.insert(&record(2))?;
+        drop(savepoint);
+        Ok(())
+    })
+    .unwrap();
+    assert!(space.get(&(2,)).unwrap().is_some());
+}
+
+pub fn transaction_options() {
+    if !tarantool::ffi::has_transaction_options() {
+        return;
+    }
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    let options = TransactionOptions::new()
+        .isolation(IsolationLevel::ReadCommitted)
+        .timeout(Duration::from_secs(10));
+    let result = start_transaction_with_options(&options, || -> Result<(), Error> {
+        space.insert(&record(1))?;
+        // The options are ignored by the nested transaction
+        start_transaction_with_options(&options, || -> Result<(), Error> {
+            space.insert(&record(2))?;
+            Ok(())
+        })
+    });
+    assert!(result.is_ok());
+
+    assert!(space.get(&(1,)).unwrap().is_some());
+    assert!(space.get(&(2,)).unwrap().is_some());
+}
+
+/// Returns `true` if transactions can yield, which is required to provoke
+/// a conflict or a timeout.
+fn has_mvcc() -> bool {
+    tarantool::ffi::has_transaction_options()
+        && tarantool::lua_state()
+            .eval::<Option<bool>>("return box.cfg.memtx_use_mvcc_engine")
+            .unwrap()
+            .unwrap_or(false)
+}
+
+pub fn transaction_conflict() {
+    if !has_mvcc() {
+        return;
+    }
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    space.insert(&record(1)).unwrap();
+
+    // Key 1 is read by the transaction and then replaced by a concurrent one
+    let conflicting_replace = || {
+        fiber::start(|| {
+            let record = S1Record {
+                id: 1,
+                text: "conflict".into(),
+            };
+            space.replace(&record).unwrap();
+        })
+        .join()
+    };
+
+    // The conflict is reported on commit
+    let result = start_transaction(|| -> Result<(), Error> {
+        space.get(&(1,))?;
+        space.insert(&record(2))?;
+        conflicting_replace();
+        Ok(())
+    });
+    assert!(matches!(
+        result,
+        Err(Error::Transaction(TransactionError::Conflict))
+    ));
+
+    // The conflict is reported by a statement of the aborted transaction
+    let result = start_transaction(|| -> Result<(), Error> {
+        space.get(&(1,))?;
+        space.insert(&record(2))?;
+        conflicting_replace();
+        space.insert(&record(3))?;
+        Ok(())
+    });
+    assert!(matches!(
+        result,
+        Err(Error::Transaction(TransactionError::Conflict))
+    ));
+
+    assert!(space.get(&(2,)).unwrap().is_none());
+    assert!(space.get(&(3,)).unwrap().is_none());
+}
+
+pub fn transaction_timeout() {
+    if !has_mvcc() {
+        return;
+    }
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+
+    let options = TransactionOptions::new().timeout(Duration::from_millis(10));
+
+    // The timeout is reported on commit
+    let result = start_transaction_with_options(&options, || -> Result<(), Error> {
+        space.insert(&record(1))?;
+        fiber::sleep(Duration::from_millis(100));
+        Ok(())
+    });
+    assert!(matches!(
+        result,
+        Err(Error::Transaction(TransactionError::TimedOut))
+    ));
+
+    // The timeout is reported by a statement of the aborted transaction
+    let result = start_transaction_with_options(&options, || -> Result<(), Error> {
+        space.insert(&record(1))?;
+        fiber::sleep(Duration::from_millis(100));
+        space.insert(&record(2))?;
+        Ok(())
+    });
+    assert!(matches!(
+        result,
+        Err(Error::Transaction(TransactionError::TimedOut))
+    ));
+
+    assert!(space.get(&(1,)).unwrap().is_none());
+    assert!(space.get(&(2,)).unwrap().is_none());
+}
+
+pub fn transaction_triggers() {
+    let space = Space::find("test_s1").unwrap();
+    space.truncate().unwrap();
+    space.insert(&record(1)).unwrap();
+
+    let committed = Rc::new(RefCell::new(vec![]));
+    let rolled_back = Rc::new(Cell::new(false));
+    start_transaction(|| -> Result<(), Error> {
+        space.insert(&record(2))?;
+        space.delete(&(1,))?;
+        on_commit({
+            let committed = committed.clone();
+            move |statements| {
+                for statement in statements {
+                    let old = statement.old.map(|t| t.decode::<S1Record>().unwrap().id);
+                    let new = statement.new.map(|t| t.decode::<S1Record>().unwrap().id);
+                    committed.borrow_mut().push((statement.space_id, old, new));
+                }
+            }
+        })?;
+        on_rollback({
+            let rolled_back = rolled_back.clone();
+            move || rolled_back.set(true)
+        })?;
+        // Is not called until the transaction ends
+        assert!(committed.borrow().is_empty());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        *committed.borrow(),
+        [(space.id(), None, Some(2)), (space.id(), Some(1), None)]
+    );
+    assert!(!rolled_back.get());
+
+    let committed = Rc::new(Cell::new(false));
+    let result = start_transaction(|| -> Result<(), Error> {
+        space.insert(&record(3))?;
+        on_commit({
+            let committed = committed.clone();
+            move |_| committed.set(true)
+        })?;
+        on_rollback({
+            let rolled_back = rolled_back.clone();
+            move || rolled_back.set(true)
+        })?;
+        Err(Error::IO(io::ErrorKind::Interrupted.into()))
+    });
+    assert!(result.is_err());
+    assert!(!committed.get());
+    assert!(rolled_back.get());
+
+    // There is no active transaction
+    assert!(on_commit(|_| {}).is_err());
+}